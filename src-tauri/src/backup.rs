@@ -0,0 +1,299 @@
+use crate::db::{self, DbState};
+use crate::error::BlueprintError;
+use crate::i18n::{self, tr, trf};
+use anyhow::{bail, Result};
+use chrono::{Datelike, Local, NaiveDateTime, TimeZone};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+const FILE_PREFIX: &str = "blueprint-";
+const FILE_EXT: &str = ".db";
+const FILE_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+type BucketKey = fn(&NaiveDateTime) -> String;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+    pub encrypted: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub daily: usize,
+    pub weekly: usize,
+    pub monthly: usize,
+}
+
+impl RetentionPolicy {
+    fn from_settings(conn: &Connection) -> Self {
+        let read = |key: &str, default: usize| {
+            db::get_setting(conn, key)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        RetentionPolicy {
+            daily: read("backup_keep_daily", 7),
+            weekly: read("backup_keep_weekly", 4),
+            monthly: read("backup_keep_monthly", 12),
+        }
+    }
+}
+
+pub fn backups_dir(app: &AppHandle) -> Result<PathBuf> {
+    let dir = app.path().app_data_dir()?.join("backups");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn parse_file_time(file_name: &str) -> Option<NaiveDateTime> {
+    let stem = file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(FILE_EXT)?;
+    NaiveDateTime::parse_from_str(stem, FILE_TIME_FORMAT).ok()
+}
+
+fn backup_info(path: &Path) -> Option<BackupInfo> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    let time = parse_file_time(&file_name)?;
+    let created_at = Local.from_local_datetime(&time).earliest()?.to_rfc3339();
+    let size_bytes = std::fs::metadata(path).ok()?.len();
+    Some(BackupInfo {
+        file_name,
+        created_at,
+        size_bytes,
+        encrypted: db::is_encrypted(path),
+    })
+}
+
+pub fn list(dir: &Path) -> Result<Vec<BackupInfo>> {
+    let mut backups: Vec<BackupInfo> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| backup_info(&entry.path()))
+        .collect();
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(backups)
+}
+
+fn copy_database(from: &Connection, to: &mut Connection) -> Result<()> {
+    Backup::new(from, to)?.run_to_completion(256, Duration::ZERO, None)?;
+    Ok(())
+}
+
+// Backups of an encrypted database are written with the same key, so the
+// snapshot on disk is never less protected than the live file.
+pub fn snapshot(conn: &Connection, key: Option<&str>, dir: &Path) -> Result<BackupInfo> {
+    let file_name = format!(
+        "{}{}{}",
+        FILE_PREFIX,
+        Local::now().format(FILE_TIME_FORMAT),
+        FILE_EXT
+    );
+    let path = dir.join(&file_name);
+    if !path.exists() {
+        let mut target = db::open_keyed(&path, key, OpenFlags::default())?;
+        copy_database(conn, &mut target)?;
+    }
+    backup_info(&path)
+        .ok_or_else(|| anyhow::anyhow!(trf("error.backup_unreadable", &[("name", &file_name)])))
+}
+
+// Keeps the newest backup of each of the last N days, ISO weeks and months;
+// everything not claimed by any bucket is returned for deletion.
+pub fn expired_backups(file_names: &[String], policy: RetentionPolicy) -> Vec<String> {
+    let mut dated: Vec<(NaiveDateTime, &String)> = file_names
+        .iter()
+        .filter_map(|name| parse_file_time(name).map(|t| (t, name)))
+        .collect();
+    dated.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+
+    let mut keep: HashSet<&String> = HashSet::new();
+    let buckets: [(usize, BucketKey); 3] = [
+        (policy.daily, |t| t.format("%Y-%m-%d").to_string()),
+        (policy.weekly, |t| {
+            let week = t.iso_week();
+            format!("{}-W{:02}", week.year(), week.week())
+        }),
+        (policy.monthly, |t| t.format("%Y-%m").to_string()),
+    ];
+    for (limit, bucket_of) in buckets {
+        let mut seen = HashSet::new();
+        for (time, name) in &dated {
+            if seen.len() >= limit {
+                break;
+            }
+            if seen.insert(bucket_of(time)) {
+                keep.insert(name);
+            }
+        }
+    }
+
+    dated
+        .iter()
+        .filter(|(_, name)| !keep.contains(name))
+        .map(|(_, name)| (*name).clone())
+        .collect()
+}
+
+pub fn apply_retention(dir: &Path, policy: RetentionPolicy) -> Result<Vec<String>> {
+    let names: Vec<String> = list(dir)?.into_iter().map(|b| b.file_name).collect();
+    let expired = expired_backups(&names, policy);
+    for name in &expired {
+        std::fs::remove_file(dir.join(name))?;
+    }
+    Ok(expired)
+}
+
+pub fn verify(path: &Path, key: Option<&str>) -> Result<()> {
+    if db::is_encrypted(path) && key.is_none() {
+        bail!(BlueprintError::validation(tr("error.backup_encrypted")));
+    }
+    let conn = db::open_keyed(path, key, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let result: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if result != "ok" {
+        bail!(BlueprintError::validation(trf(
+            "error.backup_corrupt",
+            &[("result", &result)]
+        )));
+    }
+    let version = db::schema_version(&conn)?;
+    if version > db::SCHEMA_VERSION {
+        bail!(BlueprintError::validation(trf(
+            "error.backup_too_new",
+            &[("version", &version), ("supported", &db::SCHEMA_VERSION)]
+        )));
+    }
+    Ok(())
+}
+
+pub fn restore(conn: &mut Connection, key: Option<&str>, path: &Path) -> Result<()> {
+    verify(path, key)?;
+    let source = db::open_keyed(path, key, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    copy_database(&source, conn)?;
+    conn.execute_batch("PRAGMA foreign_keys=ON;")?;
+    db::run_migrations(conn)?;
+    Ok(())
+}
+
+// Rewrites every backup readable with `old` under `new` after the database
+// key changed, so earlier snapshots stay restorable. Backups already under
+// `new` are left alone; the names of the ones neither key opens are returned.
+pub fn rekey(dir: &Path, old: Option<&str>, new: Option<&str>) -> Result<Vec<String>> {
+    let mut unreadable = Vec::new();
+    for info in list(dir)? {
+        let path = dir.join(&info.file_name);
+        // Read-write, as the export attaches and creates the new file.
+        let source = match db::open_keyed(&path, old, OpenFlags::default()) {
+            Ok(source) => source,
+            Err(_) => {
+                if db::open_keyed(&path, new, OpenFlags::SQLITE_OPEN_READ_ONLY).is_err() {
+                    unreadable.push(info.file_name);
+                }
+                continue;
+            }
+        };
+        let tmp = path.with_extension("db.rekey");
+        let exported = db::export_to(&source, &tmp, new);
+        drop(source);
+        if let Err(e) = exported.and_then(|()| db::replace_file(&tmp, &path)) {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+    }
+    Ok(unreadable)
+}
+
+fn resolve_backup_path(dir: &Path, file_name: &str) -> Result<PathBuf> {
+    if parse_file_time(file_name).is_none() || file_name.contains(['/', '\\']) {
+        bail!(BlueprintError::validation(trf(
+            "error.backup_bad_name",
+            &[("name", &file_name)]
+        )));
+    }
+    let path = dir.join(file_name);
+    if !path.is_file() {
+        bail!(BlueprintError::not_found(trf(
+            "error.backup_not_found",
+            &[("name", &file_name)]
+        )));
+    }
+    Ok(path)
+}
+
+fn run_scheduled_backup(app: &AppHandle) -> Result<()> {
+    let dir = backups_dir(app)?;
+    let state = app.state::<DbState>();
+    if state.is_locked() {
+        return Ok(());
+    }
+    let conn = state.background_conn()?;
+    if db::get_setting(&conn, "backup_enabled").as_deref() == Some("false") {
+        return Ok(());
+    }
+    let policy = RetentionPolicy::from_settings(&conn);
+    snapshot(&conn, conn.key(), &dir)?;
+    drop(conn);
+    apply_retention(&dir, policy)?;
+    Ok(())
+}
+
+fn backup_interval(app: &AppHandle) -> Duration {
+    let hours = app
+        .try_state::<DbState>()
+        .and_then(|state| {
+            let conn = state.background_conn().ok()?;
+            db::get_setting(&conn, "backup_interval_hours")
+        })
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|h| *h > 0)
+        .unwrap_or(24);
+    Duration::from_secs(hours * 3600)
+}
+
+pub fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_scheduled_backup(&app) {
+                eprintln!("Scheduled backup failed: {}", e);
+            }
+            tokio::time::sleep(backup_interval(&app)).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, BlueprintError> {
+    let dir = backups_dir(&app)?;
+    list(&dir).map_err(BlueprintError::from)
+}
+
+#[tauri::command]
+pub fn create_backup(state: State<DbState>, app: AppHandle) -> Result<BackupInfo, BlueprintError> {
+    let dir = backups_dir(&app)?;
+    let conn = state.conn()?;
+    snapshot(&conn, conn.key(), &dir).map_err(BlueprintError::from)
+}
+
+#[tauri::command]
+pub fn restore_backup(
+    file_name: String,
+    state: State<DbState>,
+    app: AppHandle,
+) -> Result<(), BlueprintError> {
+    let dir = backups_dir(&app)?;
+    let path = resolve_backup_path(&dir, &file_name)?;
+
+    let mut conn = state.conn()?;
+    let key = conn.key().map(str::to_string);
+    snapshot(&conn, key.as_deref(), &dir)?;
+    restore(&mut conn, key.as_deref(), &path)?;
+    i18n::reload(&app, &conn);
+    Ok(())
+}
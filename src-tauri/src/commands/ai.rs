@@ -1,4 +1,7 @@
 use crate::db::DbState;
+use crate::error::BlueprintError;
+use crate::i18n::{self, trf};
+use crate::vault::VaultState;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -36,7 +39,7 @@ const SYSTEM_PROMPT: &str = r#"Ты Blueprint — персональный ас
 Например, "создай 3 клиента" → массив из 3 объектов add_client.
 
 Доступные действия:
-- "add_client": data: { name, contact?, payment_type("monthly"|"onetime"), amount?, currency?("RUB"), notes?, payment_date?(YYYY-MM-DD для разового) }
+- "add_client": data: { name, contact?, payment_type("monthly"|"weekly"|"quarterly"|"yearly"|"retainer"|"hourly"|"onetime"), amount?, currency?("RUB"), notes?, payment_date?(YYYY-MM-DD для разового), included_hours?(для retainer), hourly_rate?(для hourly и retainer) }
 - "add_service": data: { project_name, service_name, login?, url?, expires_at?(YYYY-MM-DD), cost?, currency?("USD"), notes?, category? }
 - "add_note": data: { title, content?, category? } или data: { items: [{ title, content?, category? }, ...] } или data: { by_category: { "<категория>": ["заметка 1", "заметка 2"] } }
 - "complete_note": data: { title_query }
@@ -53,6 +56,14 @@ fn get_system_prompt() -> String {
     format!("{}{}", SYSTEM_PROMPT, today)
 }
 
+fn provider_error(provider: &str, status: reqwest::StatusCode, body: String) -> BlueprintError {
+    BlueprintError::Provider {
+        provider: provider.to_string(),
+        status: status.as_u16(),
+        body,
+    }
+}
+
 fn get_setting(conn: &rusqlite::Connection, key: &str) -> String {
     conn.query_row(
         "SELECT value FROM settings WHERE key = ?1",
@@ -66,9 +77,10 @@ fn get_setting(conn: &rusqlite::Connection, key: &str) -> String {
 pub async fn chat_with_ai(
     input: ChatInput,
     state: State<'_, DbState>,
-) -> Result<ChatResponse, String> {
+    vault: State<'_, VaultState>,
+) -> Result<ChatResponse, BlueprintError> {
     let (provider, model, base_url, api_key) = {
-        let conn = state.0.lock().map_err(|e| e.to_string())?;
+        let conn = state.conn()?;
         let provider = input
             .provider
             .clone()
@@ -81,12 +93,15 @@ pub async fn chat_with_ai(
             .base_url
             .clone()
             .unwrap_or_else(|| get_setting(&conn, "ai_base_url"));
-        let api_key = get_setting(&conn, "ai_api_key");
+        let api_key = vault
+            .vault(&conn)
+            .and_then(|v| v.get(&conn, "ai_api_key"))?
+            .unwrap_or_default();
         (provider, model, base_url, api_key)
     };
 
     if api_key.is_empty() && provider != "local" {
-        return Err("API ключ не настроен. Перейди в Настройки и добавь ключ.".to_string());
+        return Err(BlueprintError::missing_key("ai_api_key"));
     }
 
     let mut messages = vec![serde_json::json!({
@@ -103,8 +118,7 @@ pub async fn chat_with_ai(
 
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(60))
-        .build()
-        .map_err(|e| e.to_string())?;
+        .build()?;
 
     let response_text = match provider.as_str() {
         "anthropic" => {
@@ -159,7 +173,7 @@ async fn call_openai_compatible(
     model: &str,
     messages: &[serde_json::Value],
     url: &str,
-) -> Result<String, String> {
+) -> Result<String, BlueprintError> {
     let body = serde_json::json!({
         "model": model,
         "messages": messages,
@@ -172,14 +186,14 @@ async fn call_openai_compatible(
         req = req.bearer_auth(api_key);
     }
 
-    let resp = req.send().await.map_err(|e| e.to_string())?;
+    let resp = req.send().await?;
     if !resp.status().is_success() {
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
-        return Err(format!("AI API ошибка {}: {}", status, text));
+        return Err(provider_error("AI", status, text));
     }
 
-    let json: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    let json: serde_json::Value = resp.json().await?;
     Ok(json["choices"][0]["message"]["content"]
         .as_str()
         .unwrap_or("")
@@ -191,7 +205,7 @@ async fn call_anthropic(
     api_key: &str,
     model: &str,
     messages: &[AiMessage],
-) -> Result<String, String> {
+) -> Result<String, BlueprintError> {
     let anthropic_messages: Vec<serde_json::Value> = messages
         .iter()
         .map(|m| serde_json::json!({ "role": m.role, "content": m.content }))
@@ -210,16 +224,15 @@ async fn call_anthropic(
         .header("anthropic-version", "2023-06-01")
         .json(&body)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API ошибка {}: {}", status, text));
+        return Err(provider_error("Anthropic", status, text));
     }
 
-    let json: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    let json: serde_json::Value = resp.json().await?;
     Ok(json["content"][0]["text"]
         .as_str()
         .unwrap_or("")
@@ -231,7 +244,7 @@ async fn call_gemini(
     api_key: &str,
     model: &str,
     messages: &[AiMessage],
-) -> Result<String, String> {
+) -> Result<String, BlueprintError> {
     let contents: Vec<serde_json::Value> = messages
         .iter()
         .map(|m| {
@@ -258,16 +271,15 @@ async fn call_gemini(
         .post(&url)
         .json(&body)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
-        return Err(format!("Gemini API ошибка {}: {}", status, text));
+        return Err(provider_error("Gemini", status, text));
     }
 
-    let json: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    let json: serde_json::Value = resp.json().await?;
     Ok(json["candidates"][0]["content"]["parts"][0]["text"]
         .as_str()
         .unwrap_or("")
@@ -278,9 +290,10 @@ async fn call_gemini(
 pub async fn transcribe_audio(
     audio_base64: String,
     state: State<'_, DbState>,
-) -> Result<String, String> {
-    let (voice_provider, api_key) = {
-        let conn = state.0.lock().map_err(|e| e.to_string())?;
+    vault: State<'_, VaultState>,
+) -> Result<String, BlueprintError> {
+    let (voice_provider, key_field, api_key) = {
+        let conn = state.conn()?;
         let provider = conn.query_row(
             "SELECT value FROM settings WHERE key = 'voice_provider'",
             [],
@@ -288,28 +301,23 @@ pub async fn transcribe_audio(
         ).unwrap_or_else(|_| "openai".to_string());
 
         let key_field = if provider == "groq" { "groq_api_key" } else { "ai_api_key" };
-        let key = conn.query_row(
-            &format!("SELECT value FROM settings WHERE key = '{}'", key_field),
-            [],
-            |row| row.get::<_, String>(0),
-        ).unwrap_or_default();
+        let key = vault
+            .vault(&conn)
+            .and_then(|v| v.get(&conn, key_field))?
+            .unwrap_or_default();
 
-        (provider, key)
+        (provider, key_field, key)
     };
 
     if api_key.is_empty() {
-        return Err(if voice_provider == "groq" {
-            "Groq API ключ не настроен. Добавьте его в Настройки → Голосовой ввод.".to_string()
-        } else {
-            "API ключ не настроен".to_string()
-        });
+        return Err(BlueprintError::missing_key(key_field));
     }
 
     let audio_bytes = base64::Engine::decode(
         &base64::engine::general_purpose::STANDARD,
         &audio_base64,
     )
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| BlueprintError::validation(trf("error.invalid_audio", &[("error", &e)])))?;
 
     let (endpoint, model) = if voice_provider == "groq" {
         (
@@ -326,28 +334,26 @@ pub async fn transcribe_audio(
     let client = reqwest::Client::new();
     let part = reqwest::multipart::Part::bytes(audio_bytes)
         .file_name("audio.webm")
-        .mime_str("audio/webm")
-        .map_err(|e| e.to_string())?;
+        .mime_str("audio/webm")?;
 
     let form = reqwest::multipart::Form::new()
         .part("file", part)
         .text("model", model)
-        .text("language", "ru");
+        .text("language", i18n::current().code());
 
     let resp = client
         .post(&endpoint)
         .bearer_auth(&api_key)
         .multipart(form)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        .await?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
-        return Err(format!("Whisper API ошибка {}: {}", status, text));
+        return Err(provider_error("Whisper", status, text));
     }
 
-    let json: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    let json: serde_json::Value = resp.json().await?;
     Ok(json["text"].as_str().unwrap_or("").to_string())
 }
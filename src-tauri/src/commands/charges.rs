@@ -0,0 +1,235 @@
+use crate::commands::fx::{normalize_currency, Converter};
+use crate::dates::parse_day;
+use crate::db::DbState;
+use crate::error::BlueprintError;
+use crate::i18n::trf;
+use chrono::Utc;
+use rusqlite::{params, Connection, Row};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use uuid::Uuid;
+
+// Money actually paid for a service, one row per charge. Renewals add one
+// automatically; anything else (setup fees, refunds as negative amounts) is
+// logged by hand.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceCharge {
+    pub id: String,
+    pub service_id: String,
+    pub charged_on: String,
+    pub amount: f64,
+    pub currency: String,
+    pub note: Option<String>,
+    pub renewal_id: Option<String>,
+    pub created_at: String,
+}
+
+impl ServiceCharge {
+    pub const COLUMNS: &'static str =
+        "id, service_id, charged_on, amount, currency, note, renewal_id, created_at";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(ServiceCharge {
+            id: row.get(0)?,
+            service_id: row.get(1)?,
+            charged_on: row.get(2)?,
+            amount: row.get(3)?,
+            currency: row.get(4)?,
+            note: row.get(5)?,
+            renewal_id: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogChargeInput {
+    pub service_id: String,
+    pub charged_on: String,
+    pub amount: f64,
+    pub currency: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ProjectSpending {
+    pub project_name: String,
+    pub currency: String,
+    pub total: f64,
+    pub charges: i64,
+}
+
+pub fn insert_charge(conn: &Connection, charge: &ServiceCharge) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO service_charges ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            ServiceCharge::COLUMNS
+        ),
+        params![
+            charge.id,
+            charge.service_id,
+            charge.charged_on,
+            charge.amount,
+            charge.currency,
+            charge.note,
+            charge.renewal_id,
+            charge.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+// Refunds are negative, so only zero and non-numbers are refused.
+pub fn log_charge(conn: &Connection, input: LogChargeInput) -> Result<ServiceCharge, BlueprintError> {
+    let charged_on = parse_day(&input.charged_on)?.format("%Y-%m-%d").to_string();
+    if !input.amount.is_finite() || input.amount == 0.0 {
+        return Err(BlueprintError::validation(trf(
+            "error.invalid_charge_amount",
+            &[("amount", &input.amount)],
+        )));
+    }
+    let service_currency: String = conn.query_row(
+        "SELECT currency FROM services WHERE id = ?1",
+        [&input.service_id],
+        |row| row.get(0),
+    )?;
+    let charge = ServiceCharge {
+        id: Uuid::new_v4().to_string(),
+        service_id: input.service_id,
+        charged_on,
+        amount: input.amount,
+        currency: match input.currency.filter(|c| !c.is_empty()) {
+            Some(currency) => normalize_currency(&currency)?,
+            None => service_currency,
+        },
+        note: input.note.filter(|n| !n.is_empty()),
+        renewal_id: None,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    insert_charge(conn, &charge)?;
+    Ok(charge)
+}
+
+// Totals are kept per currency; mixing them needs exchange rates.
+pub fn spending_summary(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+) -> Result<Vec<ProjectSpending>, BlueprintError> {
+    let from = parse_day(from)?.format("%Y-%m-%d").to_string();
+    let to = parse_day(to)?.format("%Y-%m-%d").to_string();
+    let mut stmt = conn.prepare(
+        "SELECT s.project_name, c.currency, SUM(c.amount), COUNT(*)
+         FROM service_charges c JOIN services s ON s.id = c.service_id
+         WHERE c.charged_on BETWEEN ?1 AND ?2
+         GROUP BY s.project_name, c.currency
+         ORDER BY s.project_name COLLATE NOCASE, c.currency",
+    )?;
+    let rows = stmt
+        .query_map(params![from, to], |row| {
+            Ok(ProjectSpending {
+                project_name: row.get(0)?,
+                currency: row.get(1)?,
+                total: row.get(2)?,
+                charges: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(rows)
+}
+
+#[tauri::command]
+pub fn get_service_charges(
+    service_id: String,
+    state: State<DbState>,
+) -> Result<Vec<ServiceCharge>, BlueprintError> {
+    let conn = state.conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM service_charges WHERE service_id = ?1 ORDER BY charged_on DESC, created_at DESC",
+        ServiceCharge::COLUMNS
+    ))?;
+    let charges = stmt
+        .query_map([&service_id], ServiceCharge::from_row)?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(charges)
+}
+
+#[tauri::command]
+pub fn log_service_charge(
+    input: LogChargeInput,
+    state: State<DbState>,
+) -> Result<ServiceCharge, BlueprintError> {
+    let conn = state.conn()?;
+    log_charge(&conn, input)
+}
+
+#[tauri::command]
+pub fn delete_service_charge(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    conn.execute("DELETE FROM service_charges WHERE id = ?1", [&id])?;
+    Ok(())
+}
+
+// Same totals with every charge converted at the rate of the day it was
+// charged, so each project ends up with a single line.
+pub fn spending_summary_in(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+    base: &str,
+) -> Result<Vec<ProjectSpending>, BlueprintError> {
+    parse_day(from)?;
+    parse_day(to)?;
+    let base = normalize_currency(base)?;
+    let converter = Converter::load(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT s.project_name, c.amount, c.currency, c.charged_on
+         FROM service_charges c JOIN services s ON s.id = c.service_id
+         WHERE c.charged_on BETWEEN ?1 AND ?2
+         ORDER BY s.project_name COLLATE NOCASE, s.project_name",
+    )?;
+    let rows = stmt
+        .query_map(params![from, to], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut summary: Vec<ProjectSpending> = Vec::new();
+    for (project_name, amount, currency, charged_on) in rows {
+        let amount = converter.convert(amount, &currency, &base, parse_day(&charged_on)?)?;
+        match summary.last_mut() {
+            Some(last) if last.project_name == project_name => {
+                last.total += amount;
+                last.charges += 1;
+            }
+            _ => summary.push(ProjectSpending {
+                project_name,
+                currency: base.clone(),
+                total: amount,
+                charges: 1,
+            }),
+        }
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn get_spending_summary(
+    from: String,
+    to: String,
+    base_currency: Option<String>,
+    state: State<DbState>,
+) -> Result<Vec<ProjectSpending>, BlueprintError> {
+    let conn = state.conn()?;
+    match base_currency {
+        Some(base) => spending_summary_in(&conn, &from, &to, &base),
+        None => spending_summary(&conn, &from, &to),
+    }
+}
@@ -1,24 +1,119 @@
+use crate::commands::fx::{normalize_currency, Converter};
+use crate::commands::invoices::parse_period;
+use crate::commands::schedule::PeriodKind;
+use crate::commands::time_entries::{self, Rounding};
+use crate::dates::parse_day;
 use crate::db::DbState;
-use chrono::Utc;
-use rusqlite::params;
+use crate::error::BlueprintError;
+use crate::i18n::{tr, trf};
+use chrono::{Local, NaiveDate, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Row, ToSql};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tauri::State;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// How a client is billed; stored as its lowercase name. Retainers cover
+// `included_hours` per month for `amount`, hourly clients pay `hourly_rate`
+// for whatever was worked in the month.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentType {
+    Onetime,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+    Retainer,
+    Hourly,
+}
+
+impl PaymentType {
+    pub const ALL: [PaymentType; 7] = [
+        PaymentType::Onetime,
+        PaymentType::Weekly,
+        PaymentType::Monthly,
+        PaymentType::Quarterly,
+        PaymentType::Yearly,
+        PaymentType::Retainer,
+        PaymentType::Hourly,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PaymentType::Onetime => "onetime",
+            PaymentType::Weekly => "weekly",
+            PaymentType::Monthly => "monthly",
+            PaymentType::Quarterly => "quarterly",
+            PaymentType::Yearly => "yearly",
+            PaymentType::Retainer => "retainer",
+            PaymentType::Hourly => "hourly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<PaymentType, BlueprintError> {
+        PaymentType::ALL
+            .into_iter()
+            .find(|t| t.as_str() == value.trim())
+            .ok_or_else(|| BlueprintError::validation(trf("error.invalid_payment_type", &[("value", &value)])))
+    }
+
+    // None for one-time clients, who have no recurring periods.
+    pub fn period_kind(self) -> Option<PeriodKind> {
+        match self {
+            PaymentType::Onetime => None,
+            PaymentType::Weekly => Some(PeriodKind::Week),
+            PaymentType::Monthly | PaymentType::Retainer | PaymentType::Hourly => Some(PeriodKind::Month),
+            PaymentType::Quarterly => Some(PeriodKind::Quarter),
+            PaymentType::Yearly => Some(PeriodKind::Year),
+        }
+    }
+}
+
+impl ToSql for PaymentType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for PaymentType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let value = value.as_str()?;
+        PaymentType::ALL
+            .into_iter()
+            .find(|t| t.as_str() == value)
+            .ok_or_else(|| FromSqlError::Other(format!("unknown payment type {value}").into()))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Client {
     pub id: String,
     pub name: String,
     pub contact: Option<String>,
-    pub payment_type: String,
+    pub payment_type: PaymentType,
     pub amount: Option<f64>,
     pub currency: String,
     pub notes: Option<String>,
     pub payment_day: Option<i32>,
     pub created_at: String,
+    // Days before `payment_day` to start reminding; the scheduler's default
+    // applies when unset.
+    pub payment_notify_days: Option<i32>,
+    // Billing window and pause, all YYYY-MM-DD; see `schedule::BillingTerms`.
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub pause_from: Option<String>,
+    pub pause_to: Option<String>,
+    pub included_hours: Option<f64>,
+    pub hourly_rate: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct ClientPayment {
     pub id: String,
     pub client_id: String,
@@ -27,6 +122,47 @@ pub struct ClientPayment {
     pub paid_at: Option<String>,
 }
 
+impl Client {
+    pub const COLUMNS: &'static str =
+        "id, name, contact, payment_type, amount, currency, notes, payment_day, created_at, \
+         payment_notify_days, start_date, end_date, pause_from, pause_to, included_hours, hourly_rate";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Client {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            contact: row.get(2)?,
+            payment_type: row.get(3)?,
+            amount: row.get(4)?,
+            currency: row.get(5)?,
+            notes: row.get(6)?,
+            payment_day: row.get(7)?,
+            created_at: row.get(8)?,
+            payment_notify_days: row.get(9)?,
+            start_date: row.get(10)?,
+            end_date: row.get(11)?,
+            pause_from: row.get(12)?,
+            pause_to: row.get(13)?,
+            included_hours: row.get(14)?,
+            hourly_rate: row.get(15)?,
+        })
+    }
+}
+
+impl ClientPayment {
+    pub const COLUMNS: &'static str = "id, client_id, period, paid, paid_at";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(ClientPayment {
+            id: row.get(0)?,
+            client_id: row.get(1)?,
+            period: row.get(2)?,
+            paid: row.get::<_, i32>(3)? != 0,
+            paid_at: row.get(4)?,
+        })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateClientInput {
     pub name: String,
@@ -37,6 +173,13 @@ pub struct CreateClientInput {
     pub notes: Option<String>,
     pub payment_date: Option<String>,
     pub payment_day: Option<i32>,
+    pub payment_notify_days: Option<i32>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub pause_from: Option<String>,
+    pub pause_to: Option<String>,
+    pub included_hours: Option<f64>,
+    pub hourly_rate: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -49,59 +192,136 @@ pub struct UpdateClientInput {
     pub currency: Option<String>,
     pub notes: Option<String>,
     pub payment_day: Option<i32>,
+    pub payment_notify_days: Option<i32>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub pause_from: Option<String>,
+    pub pause_to: Option<String>,
+    pub included_hours: Option<f64>,
+    pub hourly_rate: Option<f64>,
 }
 
 #[tauri::command]
-pub fn get_clients(state: State<DbState>) -> Result<Vec<Client>, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn get_clients(state: State<DbState>) -> Result<Vec<Client>, BlueprintError> {
+    let conn = state.conn()?;
     let mut stmt = conn
-        .prepare(
-            "SELECT id, name, contact, payment_type, amount, currency, notes, payment_day, created_at \
-             FROM clients ORDER BY name COLLATE NOCASE",
-        )
-        .map_err(|e| e.to_string())?;
+        .prepare(&format!(
+            "SELECT {} FROM clients ORDER BY name COLLATE NOCASE",
+            Client::COLUMNS
+        ))?;
     let clients = stmt
-        .query_map([], |row| {
-            Ok(Client {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                contact: row.get(2)?,
-                payment_type: row.get(3)?,
-                amount: row.get(4)?,
-                currency: row.get(5)?,
-                notes: row.get(6)?,
-                payment_day: row.get(7)?,
-                created_at: row.get(8)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
+        .query_map([], Client::from_row)?
         .filter_map(|r| r.ok())
         .collect();
     Ok(clients)
 }
 
+fn day_or_none(value: Option<String>) -> Result<Option<NaiveDate>, BlueprintError> {
+    value.filter(|v| !v.is_empty()).map(|v| parse_day(&v)).transpose()
+}
+
+fn ordered(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Result<(), BlueprintError> {
+    match (from, to) {
+        (Some(from), Some(to)) if from > to => Err(BlueprintError::validation(trf(
+            "error.invalid_range",
+            &[("from", &from), ("to", &to)],
+        ))),
+        _ => Ok(()),
+    }
+}
+
+type Terms = [Option<String>; 4];
+
+// Normalizes start, end, pause start and pause end; a pause needs a start.
+fn billing_terms(terms: Terms) -> Result<Terms, BlueprintError> {
+    let [start, end, pause_from, pause_to] = terms.map(day_or_none);
+    let (start, end, pause_from, pause_to) = (start?, end?, pause_from?, pause_to?);
+    ordered(start, end)?;
+    ordered(pause_from, pause_to)?;
+    if pause_from.is_none() && pause_to.is_some() {
+        return Err(BlueprintError::validation(tr("error.pause_without_start")));
+    }
+    Ok([start, end, pause_from, pause_to].map(|d| d.map(|d| d.format("%Y-%m-%d").to_string())))
+}
+
+struct BillingModel {
+    payment_type: PaymentType,
+    payment_day: Option<i32>,
+    included_hours: Option<f64>,
+    hourly_rate: Option<f64>,
+}
+
+// Keeps only the fields the model uses: a payment day for recurring clients
+// (a weekday for weekly ones), included hours for retainers, an hourly rate
+// for hourly clients and retainer overtime.
+fn billing_model(
+    payment_type: &str,
+    payment_day: Option<i32>,
+    included_hours: Option<f64>,
+    hourly_rate: Option<f64>,
+) -> Result<BillingModel, BlueprintError> {
+    let payment_type = PaymentType::parse(payment_type)?;
+    let max_day = match payment_type {
+        PaymentType::Onetime => 0,
+        PaymentType::Weekly => 7,
+        _ => 31,
+    };
+    let payment_day = payment_day.filter(|_| max_day > 0);
+    if let Some(day) = payment_day.filter(|d| !(1..=max_day).contains(d)) {
+        return Err(BlueprintError::validation(trf("error.invalid_payment_day", &[("day", &day)])));
+    }
+    let positive = |value: Option<f64>, key: &str| match value {
+        Some(v) if !v.is_finite() || v <= 0.0 => {
+            Err(BlueprintError::validation(trf(key, &[("value", &v)])))
+        }
+        _ => Ok(value),
+    };
+    let included_hours = match payment_type {
+        PaymentType::Retainer => positive(included_hours, "error.invalid_hours")?,
+        _ => None,
+    };
+    let hourly_rate = match payment_type {
+        PaymentType::Retainer | PaymentType::Hourly => positive(hourly_rate, "error.invalid_hourly_rate")?,
+        _ => None,
+    };
+    if payment_type == PaymentType::Retainer && included_hours.is_none() {
+        return Err(BlueprintError::validation(tr("error.retainer_needs_hours")));
+    }
+    if payment_type == PaymentType::Hourly && hourly_rate.is_none() {
+        return Err(BlueprintError::validation(tr("error.hourly_needs_rate")));
+    }
+    Ok(BillingModel {
+        payment_type,
+        payment_day,
+        included_hours,
+        hourly_rate,
+    })
+}
+
 #[tauri::command]
-pub fn create_client(input: CreateClientInput, state: State<DbState>) -> Result<Client, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn create_client(input: CreateClientInput, state: State<DbState>) -> Result<Client, BlueprintError> {
+    let conn = state.conn()?;
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
-    let currency = input.currency.clone().unwrap_or_else(|| "USD".to_string());
+    let currency = normalize_currency(input.currency.as_deref().unwrap_or("USD"))?;
+    let [start_date, end_date, pause_from, pause_to] =
+        billing_terms([input.start_date, input.end_date, input.pause_from, input.pause_to])?;
+    let model = billing_model(&input.payment_type, input.payment_day, input.included_hours, input.hourly_rate)?;
 
     conn.execute(
-        "INSERT INTO clients (id, name, contact, payment_type, amount, currency, notes, payment_day, created_at) \
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-        params![id, input.name, input.contact, input.payment_type, input.amount, currency, input.notes, input.payment_day, now],
-    )
-    .map_err(|e| e.to_string())?;
+        "INSERT INTO clients (id, name, contact, payment_type, amount, currency, notes, payment_day, created_at, \
+         payment_notify_days, start_date, end_date, pause_from, pause_to, included_hours, hourly_rate) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![id, input.name, input.contact, model.payment_type, input.amount, currency, input.notes, model.payment_day, now, input.payment_notify_days, start_date, end_date, pause_from, pause_to, model.included_hours, model.hourly_rate],
+    )?;
 
-    if input.payment_type == "onetime" {
+    if model.payment_type == PaymentType::Onetime {
         if let Some(date) = &input.payment_date {
             let pid = Uuid::new_v4().to_string();
             conn.execute(
                 "INSERT INTO client_payments (id, client_id, period, paid) VALUES (?1, ?2, ?3, 0)",
                 params![pid, id, date],
-            )
-            .map_err(|e| e.to_string())?;
+            )?;
         }
     }
 
@@ -109,71 +329,118 @@ pub fn create_client(input: CreateClientInput, state: State<DbState>) -> Result<
         id,
         name: input.name,
         contact: input.contact,
-        payment_type: input.payment_type,
+        payment_type: model.payment_type,
         amount: input.amount,
         currency,
         notes: input.notes,
-        payment_day: input.payment_day,
+        payment_day: model.payment_day,
         created_at: now,
+        payment_notify_days: input.payment_notify_days,
+        start_date,
+        end_date,
+        pause_from,
+        pause_to,
+        included_hours: model.included_hours,
+        hourly_rate: model.hourly_rate,
     })
 }
 
 #[tauri::command]
-pub fn update_client(input: UpdateClientInput, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
-    let currency = input.currency.unwrap_or_else(|| "USD".to_string());
+pub fn update_client(input: UpdateClientInput, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    let currency = normalize_currency(input.currency.as_deref().unwrap_or("USD"))?;
+    let [start_date, end_date, pause_from, pause_to] =
+        billing_terms([input.start_date, input.end_date, input.pause_from, input.pause_to])?;
+    let model = billing_model(&input.payment_type, input.payment_day, input.included_hours, input.hourly_rate)?;
     conn.execute(
-        "UPDATE clients SET name=?1, contact=?2, payment_type=?3, amount=?4, currency=?5, notes=?6, payment_day=?7 WHERE id=?8",
-        params![input.name, input.contact, input.payment_type, input.amount, currency, input.notes, input.payment_day, input.id],
-    )
-    .map_err(|e| e.to_string())?;
+        "UPDATE clients SET name=?1, contact=?2, payment_type=?3, amount=?4, currency=?5, notes=?6, payment_day=?7, \
+         payment_notify_days=?8, start_date=?9, end_date=?10, pause_from=?11, pause_to=?12, included_hours=?13, \
+         hourly_rate=?14 WHERE id=?15",
+        params![input.name, input.contact, model.payment_type, input.amount, currency, input.notes, model.payment_day, input.payment_notify_days, start_date, end_date, pause_from, pause_to, model.included_hours, model.hourly_rate, input.id],
+    )?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_client(id: String, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
-    conn.execute("DELETE FROM clients WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+pub fn delete_client(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    conn.execute("DELETE FROM clients WHERE id = ?1", [&id])?;
     Ok(())
 }
 
+// Balance of one period in the client's currency. A period marked paid by
+// hand counts as settled whatever was recorded; `outstanding` is None when
+// the client has no amount or a transaction could not be converted.
+#[derive(Debug, Serialize, Clone)]
+pub struct PaymentPeriod {
+    #[serde(flatten)]
+    pub payment: ClientPayment,
+    pub currency: String,
+    pub expected: Option<f64>,
+    pub received: f64,
+    pub outstanding: Option<f64>,
+    pub transactions: Vec<PaymentTransaction>,
+}
+
+pub fn payment_periods(conn: &Connection, client_id: &str) -> Result<Vec<PaymentPeriod>, BlueprintError> {
+    let client = conn.query_row(
+        &format!("SELECT {} FROM clients WHERE id = ?1", Client::COLUMNS),
+        [client_id],
+        Client::from_row,
+    )?;
+    let currency = client.currency.clone();
+    let hours = time_entries::billable_hours(conn, &client, Rounding::load(conn))?;
+    let fx = Converter::load(conn)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM client_payments WHERE client_id = ?1 ORDER BY period DESC",
+        ClientPayment::COLUMNS
+    ))?;
+    let payments = stmt
+        .query_map([client_id], ClientPayment::from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM payment_transactions WHERE payment_id = ?1 ORDER BY paid_on, created_at",
+        PaymentTransaction::COLUMNS
+    ))?;
+    let mut periods = Vec::with_capacity(payments.len());
+    for payment in payments {
+        let transactions = stmt
+            .query_map([&payment.id], PaymentTransaction::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let received = received_total(&fx, &transactions, &currency);
+        let expected = time_entries::expected_amount(&client, hours.get(&payment.period));
+        let outstanding = match (payment.paid, expected, received) {
+            (true, _, _) => Some(0.0),
+            (false, Some(expected), Some(received)) => Some((expected - received).max(0.0)),
+            _ => None,
+        };
+        periods.push(PaymentPeriod {
+            payment,
+            currency: currency.clone(),
+            expected,
+            received: received.unwrap_or_default(),
+            outstanding,
+            transactions,
+        });
+    }
+    Ok(periods)
+}
+
 #[tauri::command]
 pub fn get_client_payments(
     client_id: String,
     state: State<DbState>,
-) -> Result<Vec<ClientPayment>, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare(
-            "SELECT id, client_id, period, paid, paid_at FROM client_payments \
-             WHERE client_id = ?1 ORDER BY period DESC",
-        )
-        .map_err(|e| e.to_string())?;
-    let payments = stmt
-        .query_map([&client_id], |row| {
-            Ok(ClientPayment {
-                id: row.get(0)?,
-                client_id: row.get(1)?,
-                period: row.get(2)?,
-                paid: row.get::<_, i32>(3)? != 0,
-                paid_at: row.get(4)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
-    Ok(payments)
+) -> Result<Vec<PaymentPeriod>, BlueprintError> {
+    let conn = state.conn()?;
+    payment_periods(&conn, &client_id)
 }
 
-#[tauri::command]
-pub fn toggle_payment(
-    client_id: String,
-    period: String,
+pub fn set_payment(
+    conn: &Connection,
+    client_id: &str,
+    period: &str,
     paid: bool,
-    state: State<DbState>,
-) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+) -> Result<(), BlueprintError> {
     let now = if paid {
         Some(Utc::now().to_rfc3339())
     } else {
@@ -183,7 +450,278 @@ pub fn toggle_payment(
         "INSERT INTO client_payments (id, client_id, period, paid, paid_at) VALUES (?1, ?2, ?3, ?4, ?5) \
          ON CONFLICT(client_id, period) DO UPDATE SET paid = excluded.paid, paid_at = excluded.paid_at",
         params![Uuid::new_v4().to_string(), client_id, period, paid as i32, now],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
     Ok(())
 }
+
+#[tauri::command]
+pub fn toggle_payment(
+    client_id: String,
+    period: String,
+    paid: bool,
+    state: State<DbState>,
+) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    set_payment(&conn, &client_id, &period, paid)
+}
+
+// Money actually received for a period; several may add up to (or exceed)
+// the client's amount, possibly in another currency.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PaymentTransaction {
+    pub id: String,
+    pub payment_id: String,
+    pub paid_on: String,
+    pub amount: f64,
+    pub currency: String,
+    pub method: Option<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+impl PaymentTransaction {
+    pub const COLUMNS: &'static str =
+        "id, payment_id, paid_on, amount, currency, method, note, created_at";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(PaymentTransaction {
+            id: row.get(0)?,
+            payment_id: row.get(1)?,
+            paid_on: row.get(2)?,
+            amount: row.get(3)?,
+            currency: row.get(4)?,
+            method: row.get(5)?,
+            note: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordPaymentInput {
+    pub client_id: String,
+    pub period: String,
+    pub paid_on: Option<String>,
+    pub amount: f64,
+    pub currency: Option<String>,
+    pub method: Option<String>,
+    pub note: Option<String>,
+}
+
+// Sum in `currency`, or None if some transaction has no usable rate.
+fn received_total(fx: &Converter, transactions: &[PaymentTransaction], currency: &str) -> Option<f64> {
+    transactions
+        .iter()
+        .map(|t| {
+            let on = NaiveDate::parse_from_str(&t.paid_on, "%Y-%m-%d").ok()?;
+            fx.convert(t.amount, &t.currency, currency, on).ok()
+        })
+        .sum()
+}
+
+// Received amount per (client, period) in each client's currency, for
+// reports that walk every client at once. None when a transaction has no rate
+// into the client's currency, as in `payment_periods`.
+pub fn received_by_period(
+    conn: &Connection,
+    fx: &Converter,
+) -> Result<HashMap<(String, String), Option<f64>>, BlueprintError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT p.client_id, p.period, c.currency, {}
+         FROM payment_transactions t
+         JOIN client_payments p ON p.id = t.payment_id
+         JOIN clients c ON c.id = p.client_id",
+        PaymentTransaction::COLUMNS
+            .split(", ")
+            .map(|c| format!("t.{}", c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                PaymentTransaction {
+                    id: row.get(3)?,
+                    payment_id: row.get(4)?,
+                    paid_on: row.get(5)?,
+                    amount: row.get(6)?,
+                    currency: row.get(7)?,
+                    method: row.get(8)?,
+                    note: row.get(9)?,
+                    created_at: row.get(10)?,
+                },
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut received = HashMap::new();
+    for (client_id, period, currency, transaction) in rows {
+        let on = parse_day(&transaction.paid_on)?;
+        let amount = fx.rate(&transaction.currency, &currency, on).map(|rate| transaction.amount * rate);
+        let total = received.entry((client_id, period)).or_insert(Some(0.0));
+        *total = total.zip(amount).map(|(total, amount)| total + amount);
+    }
+    Ok(received)
+}
+
+// Whether the recorded transactions cover the period, with the day of the
+// last one. None when the period is gone or a transaction has no rate into
+// the client's currency. Without an amount any payment covers it.
+fn coverage(conn: &Connection, payment_id: &str) -> Result<Option<(bool, Option<String>)>, BlueprintError> {
+    let (client_id, period): (String, String) = conn.query_row(
+        "SELECT client_id, period FROM client_payments WHERE id = ?1",
+        [payment_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let Some(period) = payment_periods(conn, &client_id)?
+        .into_iter()
+        .find(|p| p.payment.period == period)
+    else {
+        return Ok(None);
+    };
+    let Some(received) = received_total(&Converter::load(conn)?, &period.transactions, &period.currency) else {
+        return Ok(None);
+    };
+    let last_paid = period.transactions.iter().map(|t| t.paid_on.clone()).max();
+    let covered = match (period.expected, period.transactions.is_empty()) {
+        (_, true) => false,
+        (Some(expected), false) => received + 0.005 >= expected,
+        (None, false) => true,
+    };
+    Ok(Some((covered, last_paid)))
+}
+
+pub fn covered_by_transactions(conn: &Connection, client_id: &str, period: &str) -> Result<bool, BlueprintError> {
+    let payment_id: Option<String> = conn
+        .query_row(
+            "SELECT id FROM client_payments WHERE client_id = ?1 AND period = ?2",
+            params![client_id, period],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match payment_id {
+        Some(payment_id) => Ok(matches!(coverage(conn, &payment_id)?, Some((true, _)))),
+        None => Ok(false),
+    }
+}
+
+// Marks the period paid once the transactions cover it. The flag is only
+// cleared when it was the transactions that covered it before (`was_covered`),
+// so a period marked paid by hand stays paid.
+fn settle(conn: &Connection, payment_id: &str, was_covered: bool) -> Result<(), BlueprintError> {
+    match coverage(conn, payment_id)? {
+        Some((true, last_paid)) => {
+            conn.execute(
+                "UPDATE client_payments SET paid = 1, paid_at = ?1 WHERE id = ?2 AND paid = 0",
+                params![last_paid, payment_id],
+            )?;
+        }
+        Some((false, _)) if was_covered => {
+            conn.execute(
+                "UPDATE client_payments SET paid = 0, paid_at = NULL WHERE id = ?1",
+                [payment_id],
+            )?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+pub fn record_payment(
+    conn: &mut Connection,
+    input: RecordPaymentInput,
+) -> Result<PaymentTransaction, BlueprintError> {
+    let client_currency: String = conn.query_row(
+        "SELECT currency FROM clients WHERE id = ?1",
+        [&input.client_id],
+        |row| row.get(0),
+    )?;
+    let paid_on = match input.paid_on.filter(|d| !d.is_empty()) {
+        Some(day) => parse_day(&day)?.format("%Y-%m-%d").to_string(),
+        None => Local::now().format("%Y-%m-%d").to_string(),
+    };
+    parse_period(&input.period)?;
+    if !input.amount.is_finite() || input.amount <= 0.0 {
+        return Err(BlueprintError::validation(trf(
+            "error.invalid_amount",
+            &[("amount", &input.amount)],
+        )));
+    }
+    let currency = match input.currency.filter(|c| !c.is_empty()) {
+        Some(currency) => normalize_currency(&currency)?,
+        None => client_currency,
+    };
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO client_payments (id, client_id, period, paid) VALUES (?1, ?2, ?3, 0)
+         ON CONFLICT(client_id, period) DO NOTHING",
+        params![Uuid::new_v4().to_string(), input.client_id, input.period],
+    )?;
+    let payment_id: String = tx.query_row(
+        "SELECT id FROM client_payments WHERE client_id = ?1 AND period = ?2",
+        params![input.client_id, input.period],
+        |row| row.get(0),
+    )?;
+    let transaction = PaymentTransaction {
+        id: Uuid::new_v4().to_string(),
+        payment_id,
+        paid_on,
+        amount: input.amount,
+        currency,
+        method: input.method.map(|m| m.trim().to_string()).filter(|m| !m.is_empty()),
+        note: input.note.filter(|n| !n.is_empty()),
+        created_at: Utc::now().to_rfc3339(),
+    };
+    tx.execute(
+        &format!(
+            "INSERT INTO payment_transactions ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            PaymentTransaction::COLUMNS
+        ),
+        params![
+            transaction.id,
+            transaction.payment_id,
+            transaction.paid_on,
+            transaction.amount,
+            transaction.currency,
+            transaction.method,
+            transaction.note,
+            transaction.created_at
+        ],
+    )?;
+    settle(&tx, &transaction.payment_id, false)?;
+    tx.commit()?;
+    Ok(transaction)
+}
+
+pub fn delete_payment(conn: &mut Connection, id: &str) -> Result<(), BlueprintError> {
+    let tx = conn.transaction()?;
+    let payment_id: String = tx.query_row(
+        "SELECT payment_id FROM payment_transactions WHERE id = ?1",
+        [id],
+        |row| row.get(0),
+    )?;
+    let was_covered = matches!(coverage(&tx, &payment_id)?, Some((true, _)));
+    tx.execute("DELETE FROM payment_transactions WHERE id = ?1", [id])?;
+    settle(&tx, &payment_id, was_covered)?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn record_payment_transaction(
+    input: RecordPaymentInput,
+    state: State<DbState>,
+) -> Result<PaymentTransaction, BlueprintError> {
+    let mut conn = state.conn()?;
+    record_payment(&mut conn, input)
+}
+
+#[tauri::command]
+pub fn delete_payment_transaction(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let mut conn = state.conn()?;
+    delete_payment(&mut conn, &id)
+}
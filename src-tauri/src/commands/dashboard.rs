@@ -0,0 +1,426 @@
+use crate::commands::clients::{received_by_period, Client};
+use crate::commands::fx::{normalize_currency, Converter};
+use crate::commands::schedule::{months, BillingTerms};
+use crate::commands::services::{self, BillingCycle};
+use crate::commands::time_entries::{self, Rounding};
+use crate::dates::parse_day;
+use crate::db::{self, DbState};
+use crate::error::BlueprintError;
+use crate::i18n::trf;
+use crate::scheduler::due_date;
+use chrono::{Datelike, Days, Local, NaiveDate};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::State;
+
+const UPCOMING_SHORT_DAYS: u64 = 30;
+const UPCOMING_LONG_DAYS: u64 = 90;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DashboardRange {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MonthlyIncome {
+    pub period: String,
+    pub expected: f64,
+    pub received: f64,
+}
+
+// Amounts are what is still owed once partial payments are taken off.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OverdueReceivable {
+    pub client_id: String,
+    pub client_name: String,
+    pub period: String,
+    pub due_date: String,
+    pub days_overdue: i64,
+    pub amount: f64,
+    pub original_amount: f64,
+    pub original_currency: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UpcomingCost {
+    pub service_id: String,
+    pub project_name: String,
+    pub service_name: String,
+    pub due_date: String,
+    pub amount: f64,
+    pub original_amount: f64,
+    pub original_currency: String,
+}
+
+// `name` is None for services without a category.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SpendingGroup {
+    pub name: Option<String>,
+    pub total: f64,
+    pub charges: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnconvertedKind {
+    Receivable,
+    Upcoming,
+    Charge,
+}
+
+// An amount with no rate into the base currency. It is listed here instead
+// of failing the whole dashboard and is left out of every total.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UnconvertedAmount {
+    pub kind: UnconvertedKind,
+    pub id: String,
+    pub name: String,
+    pub date: String,
+    pub amount: f64,
+    pub currency: String,
+}
+
+// Every amount is in `base_currency`. Past amounts are converted at the rate
+// of the day they were due or charged, upcoming ones at today's rate.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Dashboard {
+    pub base_currency: String,
+    pub from: String,
+    pub to: String,
+    pub income: Vec<MonthlyIncome>,
+    pub overdue: Vec<OverdueReceivable>,
+    pub overdue_total: f64,
+    pub upcoming: Vec<UpcomingCost>,
+    pub upcoming_30_days: f64,
+    pub upcoming_90_days: f64,
+    pub spending_by_project: Vec<SpendingGroup>,
+    pub spending_by_category: Vec<SpendingGroup>,
+    pub unconverted: Vec<UnconvertedAmount>,
+}
+
+struct Receivable {
+    client_id: String,
+    client_name: String,
+    period: String,
+    due: NaiveDate,
+    amount: f64,
+    currency: String,
+    // In `currency`; a period marked paid counts as fully received. None
+    // when a payment could not be converted into `currency`.
+    received: Option<f64>,
+}
+
+// One entry per billing period from `from` up to the end of `to`'s month:
+// every expected period for recurring clients (see `BillingTerms`), the
+// recorded payment date for one-time clients. Periods with nothing to expect
+// (no amount, or no tracked hours for hourly clients) are left out.
+fn receivables(
+    conn: &Connection,
+    fx: &Converter,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Receivable>, BlueprintError> {
+    let mut stmt = conn.prepare("SELECT client_id, period FROM client_payments WHERE paid = 1")?;
+    let paid: HashSet<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let partial = received_by_period(conn, fx)?;
+    let received = |client_id: &str, period: &str, amount: f64| {
+        let key = (client_id.to_string(), period.to_string());
+        if paid.contains(&key) {
+            Some(amount)
+        } else {
+            partial.get(&key).copied().unwrap_or(Some(0.0)).map(|r| r.min(amount))
+        }
+    };
+
+    let mut receivables = Vec::new();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM clients WHERE payment_type != 'onetime'",
+        Client::COLUMNS
+    ))?;
+    let clients = stmt
+        .query_map([], Client::from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let rounding = Rounding::load(conn);
+    for client in clients {
+        let hours = time_entries::billable_hours(conn, &client, rounding)?;
+        for expected in BillingTerms::of(&client).expected_periods(from, to) {
+            let Some(amount) = time_entries::expected_amount(&client, hours.get(&expected.period)) else {
+                continue;
+            };
+            receivables.push(Receivable {
+                received: received(&client.id, &expected.period, amount),
+                client_id: client.id.clone(),
+                client_name: client.name.clone(),
+                period: expected.period,
+                due: expected.due,
+                amount,
+                currency: client.currency.clone(),
+            });
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.name, c.amount, c.currency, p.period
+         FROM client_payments p JOIN clients c ON c.id = p.client_id
+         WHERE c.payment_type = 'onetime' AND c.amount IS NOT NULL",
+    )?;
+    let payments = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let first = from.with_day(1).unwrap_or(from);
+    for (id, name, amount, currency, period) in payments {
+        let Ok(due) = NaiveDate::parse_from_str(&period, "%Y-%m-%d") else {
+            continue;
+        };
+        if due < first || due > month_end(to) {
+            continue;
+        }
+        receivables.push(Receivable {
+            received: received(&id, &period, amount),
+            client_id: id,
+            client_name: name,
+            period,
+            due,
+            amount,
+            currency,
+        });
+    }
+    Ok(receivables)
+}
+
+fn month_end(date: NaiveDate) -> NaiveDate {
+    due_date(date.year(), date.month(), 31).unwrap_or(date)
+}
+
+fn group_spending(groups: HashMap<Option<String>, (f64, i64)>) -> Vec<SpendingGroup> {
+    let mut groups: Vec<SpendingGroup> = groups
+        .into_iter()
+        .map(|(name, (total, charges))| SpendingGroup {
+            name,
+            total,
+            charges,
+        })
+        .collect();
+    groups.sort_by(|a, b| b.total.total_cmp(&a.total).then_with(|| a.name.cmp(&b.name)));
+    groups
+}
+
+pub fn build_dashboard(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+    base_currency: &str,
+    today: NaiveDate,
+) -> Result<Dashboard, BlueprintError> {
+    if from > to {
+        return Err(BlueprintError::validation(trf(
+            "error.invalid_range",
+            &[("from", &from), ("to", &to)],
+        )));
+    }
+    let base = normalize_currency(base_currency)?;
+    let fx = Converter::load(conn)?;
+
+    let mut income: BTreeMap<String, MonthlyIncome> = months(from, to)
+        .map(|m| {
+            let period = m.format("%Y-%m").to_string();
+            (
+                period.clone(),
+                MonthlyIncome {
+                    period,
+                    expected: 0.0,
+                    received: 0.0,
+                },
+            )
+        })
+        .collect();
+    let mut overdue = Vec::new();
+    let mut unconverted = Vec::new();
+    for receivable in receivables(conn, &fx, from, to)? {
+        let converted = receivable.received.and_then(|received| {
+            let rate = fx.rate(&receivable.currency, &base, receivable.due)?;
+            Some((receivable.amount * rate, received * rate, received))
+        });
+        let Some((expected, received, original_received)) = converted else {
+            unconverted.push(UnconvertedAmount {
+                kind: UnconvertedKind::Receivable,
+                id: receivable.client_id,
+                name: receivable.client_name,
+                date: receivable.due.format("%Y-%m-%d").to_string(),
+                amount: receivable.amount,
+                currency: receivable.currency,
+            });
+            continue;
+        };
+        if let Some(month) = income.get_mut(&receivable.due.format("%Y-%m").to_string()) {
+            month.expected += expected;
+            month.received += received;
+        }
+        let outstanding = receivable.amount - original_received;
+        if outstanding > 0.005 && receivable.due < today && receivable.due >= from && receivable.due <= to {
+            overdue.push(OverdueReceivable {
+                client_id: receivable.client_id,
+                client_name: receivable.client_name,
+                period: receivable.period,
+                due_date: receivable.due.format("%Y-%m-%d").to_string(),
+                days_overdue: (today - receivable.due).num_days(),
+                amount: expected - received,
+                original_amount: outstanding,
+                original_currency: receivable.currency,
+            });
+        }
+    }
+    overdue.sort_by(|a, b| a.due_date.cmp(&b.due_date).then_with(|| a.client_name.cmp(&b.client_name)));
+
+    // Services with a billing cycle come due again within the window, so each
+    // occurrence is listed.
+    let short = today + Days::new(UPCOMING_SHORT_DAYS);
+    let horizon = today + Days::new(UPCOMING_LONG_DAYS);
+    let mut stmt = conn.prepare(
+        "SELECT id, project_name, service_name, expires_at, cost, currency, billing_cycle, billing_cycle_days
+         FROM services WHERE cost IS NOT NULL AND expires_at >= ?1",
+    )?;
+    let services = stmt
+        .query_map([today.format("%Y-%m-%d").to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, f64>(4)?,
+                row.get::<_, String>(5)?,
+                BillingCycle::from_columns(
+                    row.get::<_, Option<String>>(6)?.as_deref(),
+                    row.get(7)?,
+                ),
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut upcoming = Vec::new();
+    for (id, project_name, service_name, expires_at, cost, currency, cycle) in services {
+        let Ok(mut due) = NaiveDate::parse_from_str(&expires_at, "%Y-%m-%d") else {
+            continue;
+        };
+        let anchor = services::renewal_anchor(conn, &id, due)?;
+        let Some(rate) = fx.rate(&currency, &base, today) else {
+            unconverted.push(UnconvertedAmount {
+                kind: UnconvertedKind::Upcoming,
+                id,
+                name: service_name,
+                date: expires_at,
+                amount: cost,
+                currency,
+            });
+            continue;
+        };
+        let amount = cost * rate;
+        while due <= horizon {
+            upcoming.push(UpcomingCost {
+                service_id: id.clone(),
+                project_name: project_name.clone(),
+                service_name: service_name.clone(),
+                due_date: due.format("%Y-%m-%d").to_string(),
+                amount,
+                original_amount: cost,
+                original_currency: currency.clone(),
+            });
+            match cycle.and_then(|c| c.advance(anchor, due)) {
+                Some(next) => due = next,
+                None => break,
+            }
+        }
+    }
+    upcoming.sort_by(|a, b| a.due_date.cmp(&b.due_date).then_with(|| a.service_name.cmp(&b.service_name)));
+    let short = short.format("%Y-%m-%d").to_string();
+    let upcoming_30_days = upcoming
+        .iter()
+        .filter(|c| c.due_date <= short)
+        .map(|c| c.amount)
+        .sum();
+
+    let mut stmt = conn.prepare(
+        "SELECT s.project_name, NULLIF(TRIM(s.category), ''), c.amount, c.currency, c.charged_on,
+                s.id, s.service_name
+         FROM service_charges c JOIN services s ON s.id = c.service_id
+         WHERE c.charged_on BETWEEN ?1 AND ?2",
+    )?;
+    let charges = stmt
+        .query_map(
+            [from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string()],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, f64>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, String>(6)?,
+                ))
+            },
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut by_project: HashMap<Option<String>, (f64, i64)> = HashMap::new();
+    let mut by_category: HashMap<Option<String>, (f64, i64)> = HashMap::new();
+    for (project_name, category, amount, currency, charged_on, service_id, service_name) in charges {
+        let Some(rate) = fx.rate(&currency, &base, parse_day(&charged_on)?) else {
+            unconverted.push(UnconvertedAmount {
+                kind: UnconvertedKind::Charge,
+                id: service_id,
+                name: service_name,
+                date: charged_on,
+                amount,
+                currency,
+            });
+            continue;
+        };
+        let amount = amount * rate;
+        for (groups, key) in [(&mut by_project, Some(project_name)), (&mut by_category, category)] {
+            let entry = groups.entry(key).or_default();
+            entry.0 += amount;
+            entry.1 += 1;
+        }
+    }
+
+    Ok(Dashboard {
+        base_currency: base,
+        from: from.format("%Y-%m-%d").to_string(),
+        to: to.format("%Y-%m-%d").to_string(),
+        income: income.into_values().collect(),
+        overdue_total: overdue.iter().map(|o| o.amount).sum(),
+        overdue,
+        upcoming_30_days,
+        upcoming_90_days: upcoming.iter().map(|c| c.amount).sum(),
+        upcoming,
+        spending_by_project: group_spending(by_project),
+        spending_by_category: group_spending(by_category),
+        unconverted,
+    })
+}
+
+#[tauri::command]
+pub fn get_dashboard(
+    range: DashboardRange,
+    base_currency: Option<String>,
+    state: State<DbState>,
+) -> Result<Dashboard, BlueprintError> {
+    let from = parse_day(&range.from)?;
+    let to = parse_day(&range.to)?;
+    let conn = state.conn()?;
+    let base = base_currency
+        .or_else(|| db::get_setting(&conn, "base_currency"))
+        .unwrap_or_else(|| "RUB".to_string());
+    build_dashboard(&conn, from, to, &base, Local::now().date_naive())
+}
@@ -0,0 +1,72 @@
+use crate::backup;
+use crate::db::DbState;
+use crate::error::BlueprintError;
+use crate::i18n;
+use crate::vault;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseStatus {
+    pub encrypted: bool,
+    pub locked: bool,
+}
+
+#[tauri::command]
+pub fn get_database_status(state: State<DbState>) -> DatabaseStatus {
+    DatabaseStatus {
+        encrypted: state.is_encrypted(),
+        locked: state.is_locked(),
+    }
+}
+
+#[tauri::command]
+pub fn unlock_database(
+    passphrase: String,
+    state: State<DbState>,
+    app: AppHandle,
+) -> Result<(), BlueprintError> {
+    state.unlock(&passphrase)?;
+    i18n::reload(&app, &*state.background_conn()?);
+    if let Err(e) = vault::prepare(&app) {
+        eprintln!("Failed to prepare secret vault: {}", e);
+    }
+    let _ = app.emit("database-unlocked", ());
+    Ok(())
+}
+
+// The key-changing commands re-key existing backups as well and return the
+// names of any that could not be, since those can no longer be restored.
+#[tauri::command]
+pub fn enable_database_encryption(
+    passphrase: String,
+    state: State<DbState>,
+    app: AppHandle,
+) -> Result<Vec<String>, BlueprintError> {
+    let dir = backup::backups_dir(&app)?;
+    state.enable_encryption(&passphrase)?;
+    Ok(backup::rekey(&dir, None, Some(&passphrase))?)
+}
+
+#[tauri::command]
+pub fn disable_database_encryption(
+    passphrase: String,
+    state: State<DbState>,
+    app: AppHandle,
+) -> Result<Vec<String>, BlueprintError> {
+    let dir = backup::backups_dir(&app)?;
+    state.disable_encryption(&passphrase)?;
+    Ok(backup::rekey(&dir, Some(&passphrase), None)?)
+}
+
+#[tauri::command]
+pub fn change_database_passphrase(
+    current: String,
+    new: String,
+    state: State<DbState>,
+    app: AppHandle,
+) -> Result<Vec<String>, BlueprintError> {
+    let dir = backup::backups_dir(&app)?;
+    state.change_passphrase(&current, &new)?;
+    Ok(backup::rekey(&dir, Some(&current), Some(&new))?)
+}
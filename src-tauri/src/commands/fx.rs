@@ -0,0 +1,316 @@
+use crate::db::DbState;
+use crate::error::BlueprintError;
+use crate::i18n::trf;
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, Connection, Row};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+// Active ISO 4217 codes. Funds and precious metals are left out on purpose.
+pub const CURRENCIES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT",
+    "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD",
+    "CDF", "CHF", "CLP", "CNY", "COP", "CRC", "CUP", "CVE", "CZK", "DJF", "DKK", "DOP", "DZD",
+    "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS", "GIP", "GMD", "GNF", "GTQ",
+    "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD", "JOD",
+    "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR",
+    "LRD", "LSL", "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR",
+    "MWK", "MXN", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN",
+    "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD", "RUB", "RWF", "SAR", "SBD", "SCR",
+    "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN", "SVC", "SYP", "SZL", "THB",
+    "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU", "UZS",
+    "VES", "VND", "VUV", "WST", "XAF", "XCD", "XOF", "XPF", "YER", "ZAR", "ZMW", "ZWG",
+];
+
+// Trims and upper-cases, then checks the code against ISO 4217.
+pub fn normalize_currency(code: &str) -> Result<String, BlueprintError> {
+    let code = code.trim().to_ascii_uppercase();
+    if CURRENCIES.contains(&code.as_str()) {
+        Ok(code)
+    } else {
+        Err(BlueprintError::validation(trf(
+            "error.unknown_currency",
+            &[("code", &code)],
+        )))
+    }
+}
+
+// One `base` is worth `rate` of `quote` on `date`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FxRate {
+    pub id: String,
+    pub base: String,
+    pub quote: String,
+    pub date: String,
+    pub rate: f64,
+}
+
+impl FxRate {
+    pub const COLUMNS: &'static str = "id, base, quote, date, rate";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(FxRate {
+            id: row.get(0)?,
+            base: row.get(1)?,
+            quote: row.get(2)?,
+            date: row.get(3)?,
+            rate: row.get(4)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FxRateInput {
+    pub base: String,
+    pub quote: String,
+    pub date: String,
+    pub rate: f64,
+}
+
+impl FxRateInput {
+    fn validated(self) -> Result<FxRateInput, BlueprintError> {
+        let base = normalize_currency(&self.base)?;
+        let quote = normalize_currency(&self.quote)?;
+        NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").map_err(|_| {
+            BlueprintError::validation(trf("error.invalid_date", &[("date", &self.date)]))
+        })?;
+        if base == quote || !self.rate.is_finite() || self.rate <= 0.0 {
+            return Err(BlueprintError::validation(trf(
+                "error.invalid_fx_rate",
+                &[("base", &base), ("quote", &quote), ("rate", &self.rate)],
+            )));
+        }
+        Ok(FxRateInput {
+            base,
+            quote,
+            date: self.date,
+            rate: self.rate,
+        })
+    }
+}
+
+// The id is derived from the pair and day, so setting a rate again replaces
+// the earlier one and the same rate from another profile merges cleanly.
+pub fn set_rate(conn: &Connection, input: FxRateInput) -> Result<FxRate, BlueprintError> {
+    let input = input.validated()?;
+    conn.execute(
+        "INSERT INTO fx_rates (id, base, quote, date, rate) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(base, quote, date) DO UPDATE SET rate = excluded.rate",
+        params![
+            format!("{}-{}-{}", input.base, input.quote, input.date),
+            input.base,
+            input.quote,
+            input.date,
+            input.rate
+        ],
+    )?;
+    Ok(conn.query_row(
+        &format!(
+            "SELECT {} FROM fx_rates WHERE base = ?1 AND quote = ?2 AND date = ?3",
+            FxRate::COLUMNS
+        ),
+        params![input.base, input.quote, input.date],
+        FxRate::from_row,
+    )?)
+}
+
+// Accepts a JSON array of rates or CSV lines `base,quote,date,rate` (`;` works
+// too, a header line is skipped).
+pub fn parse_rates(content: &str) -> Result<Vec<FxRateInput>, BlueprintError> {
+    let trimmed = content.trim_start();
+    if trimmed.starts_with('[') {
+        return serde_json::from_str(trimmed).map_err(|e| {
+            BlueprintError::validation(trf(
+                "error.invalid_fx_import",
+                &[("line", &e.line()), ("error", &e)],
+            ))
+        });
+    }
+    let mut rates = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let fields: Vec<&str> = line.split([',', ';']).map(str::trim).collect();
+        if line.trim().is_empty() || (idx == 0 && fields[0].eq_ignore_ascii_case("base")) {
+            continue;
+        }
+        let invalid = |error: &dyn std::fmt::Display| {
+            BlueprintError::validation(trf(
+                "error.invalid_fx_import",
+                &[("line", &(idx + 1)), ("error", error)],
+            ))
+        };
+        let [base, quote, date, rate] = fields[..] else {
+            return Err(invalid(&line));
+        };
+        rates.push(FxRateInput {
+            base: base.to_string(),
+            quote: quote.to_string(),
+            date: date.to_string(),
+            rate: rate.parse().map_err(|e| invalid(&e))?,
+        });
+    }
+    Ok(rates)
+}
+
+// All or nothing: one bad line rejects the whole file.
+pub fn import_rates(conn: &mut Connection, content: &str) -> Result<usize, BlueprintError> {
+    let rates = parse_rates(content)?
+        .into_iter()
+        .map(FxRateInput::validated)
+        .collect::<Result<Vec<_>, _>>()?;
+    let tx = conn.transaction()?;
+    for rate in &rates {
+        set_rate(&tx, rate.clone())?;
+    }
+    tx.commit()?;
+    Ok(rates.len())
+}
+
+// All rates in memory, for reports that convert many amounts at once. Uses
+// the latest rate on or before the requested day, inverts pairs when only the
+// opposite direction is known and otherwise goes through one intermediate
+// currency.
+pub struct Converter {
+    rates: HashMap<(String, String), Vec<(String, f64)>>,
+}
+
+impl Converter {
+    pub fn load(conn: &Connection) -> Result<Converter, BlueprintError> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM fx_rates ORDER BY date",
+            FxRate::COLUMNS
+        ))?;
+        let mut rates: HashMap<(String, String), Vec<(String, f64)>> = HashMap::new();
+        for rate in stmt.query_map([], FxRate::from_row)?.filter_map(|r| r.ok()) {
+            rates
+                .entry((rate.base.clone(), rate.quote.clone()))
+                .or_default()
+                .push((rate.date.clone(), rate.rate));
+            rates
+                .entry((rate.quote, rate.base))
+                .or_default()
+                .push((rate.date, 1.0 / rate.rate));
+        }
+        for history in rates.values_mut() {
+            history.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        Ok(Converter { rates })
+    }
+
+    fn direct(&self, from: &str, to: &str, on: &str) -> Option<f64> {
+        let history = self.rates.get(&(from.to_string(), to.to_string()))?;
+        history
+            .iter()
+            .rev()
+            .find(|(date, _)| date.as_str() <= on)
+            .map(|(_, rate)| *rate)
+    }
+
+    pub fn rate(&self, from: &str, to: &str, on: NaiveDate) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        let on = on.format("%Y-%m-%d").to_string();
+        if let Some(rate) = self.direct(from, to, &on) {
+            return Some(rate);
+        }
+        let pivots: HashSet<&str> = self
+            .rates
+            .keys()
+            .filter(|(base, _)| base == from)
+            .map(|(_, quote)| quote.as_str())
+            .collect();
+        pivots.into_iter().find_map(|pivot| {
+            Some(self.direct(from, pivot, &on)? * self.direct(pivot, to, &on)?)
+        })
+    }
+
+    pub fn convert(
+        &self,
+        amount: f64,
+        from: &str,
+        to: &str,
+        on: NaiveDate,
+    ) -> Result<f64, BlueprintError> {
+        self.rate(from, to, on).map(|rate| amount * rate).ok_or_else(|| {
+            BlueprintError::validation(trf(
+                "error.no_fx_rate",
+                &[("from", &from), ("to", &to), ("date", &on)],
+            ))
+        })
+    }
+}
+
+#[tauri::command]
+pub fn get_fx_rates(state: State<DbState>) -> Result<Vec<FxRate>, BlueprintError> {
+    let conn = state.conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM fx_rates ORDER BY date DESC, base, quote",
+        FxRate::COLUMNS
+    ))?;
+    let rates = stmt
+        .query_map([], FxRate::from_row)?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(rates)
+}
+
+#[tauri::command]
+pub fn set_fx_rate(
+    base: String,
+    quote: String,
+    date: Option<String>,
+    rate: f64,
+    state: State<DbState>,
+) -> Result<FxRate, BlueprintError> {
+    let conn = state.conn()?;
+    let date = date.unwrap_or_else(|| Local::now().format("%Y-%m-%d").to_string());
+    set_rate(
+        &conn,
+        FxRateInput {
+            base,
+            quote,
+            date,
+            rate,
+        },
+    )
+}
+
+#[tauri::command]
+pub fn delete_fx_rate(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    conn.execute("DELETE FROM fx_rates WHERE id = ?1", [&id])?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn import_fx_rates(content: String, state: State<DbState>) -> Result<usize, BlueprintError> {
+    let mut conn = state.conn()?;
+    import_rates(&mut conn, &content)
+}
+
+#[tauri::command]
+pub fn convert_amount(
+    amount: f64,
+    from: String,
+    to: String,
+    date: Option<String>,
+    state: State<DbState>,
+) -> Result<f64, BlueprintError> {
+    let from = normalize_currency(&from)?;
+    let to = normalize_currency(&to)?;
+    let on = match date {
+        Some(date) => NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|_| BlueprintError::validation(trf("error.invalid_date", &[("date", &date)])))?,
+        None => Local::now().date_naive(),
+    };
+    let conn = state.conn()?;
+    Converter::load(&conn)?.convert(amount, &from, &to, on)
+}
+
+#[tauri::command]
+pub fn get_currencies() -> Vec<&'static str> {
+    CURRENCIES.to_vec()
+}
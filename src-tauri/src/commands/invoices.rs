@@ -0,0 +1,737 @@
+use crate::commands::clients::{self, Client, PaymentType};
+use crate::commands::fx::normalize_currency;
+use crate::commands::schedule::Period;
+use crate::commands::time_entries::{self, Rounding};
+use crate::dates::parse_day;
+use crate::db::{self, DbState};
+use crate::error::BlueprintError;
+use crate::i18n::{tr, trf};
+use crate::pdf::{PdfDocument, TrueTypeFont, A4_HEIGHT, A4_WIDTH};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{Datelike, Local, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+use uuid::Uuid;
+
+pub const STATUS_ISSUED: &str = "issued";
+pub const STATUS_PAID: &str = "paid";
+
+const DEFAULT_PREFIX: &str = "INV-{year}-";
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Invoice {
+    pub id: String,
+    pub number: String,
+    pub client_id: String,
+    pub period: Option<String>,
+    pub issued_on: String,
+    pub due_on: Option<String>,
+    pub currency: String,
+    pub status: String,
+    pub notes: Option<String>,
+    pub paid_at: Option<String>,
+    pub created_at: String,
+}
+
+impl Invoice {
+    pub const COLUMNS: &'static str =
+        "id, number, client_id, period, issued_on, due_on, currency, status, notes, paid_at, created_at";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Invoice {
+            id: row.get(0)?,
+            number: row.get(1)?,
+            client_id: row.get(2)?,
+            period: row.get(3)?,
+            issued_on: row.get(4)?,
+            due_on: row.get(5)?,
+            currency: row.get(6)?,
+            status: row.get(7)?,
+            notes: row.get(8)?,
+            paid_at: row.get(9)?,
+            created_at: row.get(10)?,
+        })
+    }
+}
+
+// `period` is the client payment period the line bills for; paying the
+// invoice marks each of them paid.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InvoiceItem {
+    pub id: String,
+    pub invoice_id: String,
+    pub position: i32,
+    pub description: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub period: Option<String>,
+}
+
+impl InvoiceItem {
+    pub const COLUMNS: &'static str =
+        "id, invoice_id, position, description, quantity, unit_price, period";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(InvoiceItem {
+            id: row.get(0)?,
+            invoice_id: row.get(1)?,
+            position: row.get(2)?,
+            description: row.get(3)?,
+            quantity: row.get(4)?,
+            unit_price: row.get(5)?,
+            period: row.get(6)?,
+        })
+    }
+
+    pub fn amount(&self) -> f64 {
+        self.quantity * self.unit_price
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InvoiceDetails {
+    #[serde(flatten)]
+    pub invoice: Invoice,
+    pub client_name: String,
+    pub items: Vec<InvoiceItem>,
+    pub total: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvoiceItemInput {
+    pub description: String,
+    pub quantity: Option<f64>,
+    pub unit_price: f64,
+    pub period: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInvoiceInput {
+    pub client_id: String,
+    pub period: Option<String>,
+    pub issued_on: Option<String>,
+    pub due_on: Option<String>,
+    pub currency: Option<String>,
+    pub notes: Option<String>,
+    pub items: Option<Vec<InvoiceItemInput>>,
+}
+
+// Our side of the invoice, kept in settings as `company_*`.
+#[derive(Debug, Clone, Default)]
+pub struct CompanyDetails {
+    pub name: String,
+    pub details: String,
+    pub bank: String,
+}
+
+impl CompanyDetails {
+    pub fn load(conn: &Connection) -> CompanyDetails {
+        let get = |key| db::get_setting(conn, key).unwrap_or_default();
+        CompanyDetails {
+            name: get("company_name"),
+            details: get("company_details"),
+            bank: get("company_bank"),
+        }
+    }
+}
+
+pub(crate) fn parse_period(value: &str) -> Result<(), BlueprintError> {
+    if Period::parse(value).is_some() {
+        Ok(())
+    } else {
+        Err(BlueprintError::validation(trf("error.invalid_period", &[("period", &value)])))
+    }
+}
+
+// Takes the next number from the `invoice_next_number` setting; `{year}` in
+// `invoice_prefix` becomes the year of issue. Numbers already taken (say, by
+// an imported profile) are skipped.
+fn next_number(conn: &Connection, issued_on: NaiveDate) -> Result<String, BlueprintError> {
+    let prefix = db::get_setting(conn, "invoice_prefix").unwrap_or_else(|| DEFAULT_PREFIX.to_string());
+    let prefix = prefix.replace("{year}", &issued_on.year().to_string());
+    let mut next: u64 = db::get_setting(conn, "invoice_next_number")
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(1);
+    loop {
+        let number = format!("{}{:04}", prefix, next);
+        next += 1;
+        let taken = conn
+            .query_row("SELECT 1 FROM invoices WHERE number = ?1", [&number], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !taken {
+            db::set_setting(conn, "invoice_next_number", &next.to_string())?;
+            return Ok(number);
+        }
+    }
+}
+
+fn load_client(conn: &Connection, id: &str) -> Result<Client, BlueprintError> {
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM clients WHERE id = ?1", Client::COLUMNS),
+        [id],
+        Client::from_row,
+    )?)
+}
+
+pub fn load(conn: &Connection, id: &str) -> Result<InvoiceDetails, BlueprintError> {
+    let invoice = conn.query_row(
+        &format!("SELECT {} FROM invoices WHERE id = ?1", Invoice::COLUMNS),
+        [id],
+        Invoice::from_row,
+    )?;
+    let client_name = conn.query_row(
+        "SELECT name FROM clients WHERE id = ?1",
+        [&invoice.client_id],
+        |row| row.get(0),
+    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM invoice_items WHERE invoice_id = ?1 ORDER BY position",
+        InvoiceItem::COLUMNS
+    ))?;
+    let items: Vec<InvoiceItem> = stmt
+        .query_map([id], InvoiceItem::from_row)?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(InvoiceDetails {
+        total: items.iter().map(InvoiceItem::amount).sum(),
+        invoice,
+        client_name,
+        items,
+    })
+}
+
+pub fn list(conn: &Connection, client_id: Option<&str>) -> Result<Vec<InvoiceDetails>, BlueprintError> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM invoices WHERE ?1 IS NULL OR client_id = ?1 ORDER BY issued_on DESC, number DESC",
+    )?;
+    let ids = stmt
+        .query_map([client_id], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    ids.iter().map(|id| load(conn, id)).collect()
+}
+
+// Without items the invoice bills the client's regular amount for `period`.
+fn hours_item(description: String, minutes: i64, rate: f64, period: &str) -> InvoiceItemInput {
+    InvoiceItemInput {
+        description,
+        quantity: Some((minutes as f64 / 60.0 * 100.0).round() / 100.0),
+        unit_price: rate,
+        period: Some(period.to_string()),
+    }
+}
+
+// Without explicit items the invoice bills the client's amount. For hourly
+// clients that is the period's unbilled tracked time. A retainer's fee is
+// billed once per period, and each invoice adds the overtime among the hours
+// not yet billed. Returns the time entries it covers.
+fn default_items(
+    conn: &Connection,
+    client: &Client,
+    period: Option<&str>,
+) -> Result<(Vec<InvoiceItemInput>, Vec<String>), BlueprintError> {
+    let hours = match (period, client.payment_type) {
+        (Some(period), PaymentType::Hourly | PaymentType::Retainer) => {
+            time_entries::billable_hours(conn, client, Rounding::load(conn))?.remove(period)
+        }
+        _ => None,
+    };
+    let fee_billed = match (period, client.payment_type) {
+        (Some(period), PaymentType::Retainer) => conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM invoices WHERE client_id = ?1 AND period = ?2)",
+            params![client.id, period],
+            |row| row.get(0),
+        )?,
+        _ => false,
+    };
+    let mut items = Vec::new();
+    if client.payment_type != PaymentType::Hourly && !fee_billed {
+        items.push(InvoiceItemInput {
+            description: match period {
+                Some(period) => trf("invoice.default_item", &[("period", &period)]),
+                None => tr("invoice.default_item_no_period").to_string(),
+            },
+            quantity: Some(1.0),
+            unit_price: client
+                .amount
+                .ok_or_else(|| BlueprintError::validation(tr("error.invoice_empty")))?,
+            period: None,
+        });
+    }
+    let (Some(period), Some(hours), Some(rate)) = (period, hours, client.hourly_rate) else {
+        return if items.is_empty() {
+            Err(BlueprintError::validation(tr("error.invoice_empty")))
+        } else {
+            Ok((items, Vec::new()))
+        };
+    };
+    let minutes = match client.payment_type {
+        PaymentType::Hourly => hours.unbilled_minutes,
+        // Earlier invoices already covered the overtime among billed hours.
+        _ => {
+            let included = (client.included_hours.unwrap_or(0.0) * 60.0).round() as i64;
+            hours.unbilled_minutes.min((hours.minutes - included).max(0))
+        }
+    };
+    if minutes > 0 {
+        let key = match client.payment_type {
+            PaymentType::Hourly => "invoice.hours_item",
+            _ => "invoice.overtime_item",
+        };
+        items.push(hours_item(trf(key, &[("period", &period)]), minutes, rate, period));
+    }
+    if items.is_empty() {
+        return Err(BlueprintError::validation(tr("error.invoice_empty")));
+    }
+    Ok((items, hours.unbilled_ids))
+}
+
+pub fn create(conn: &mut Connection, input: CreateInvoiceInput) -> Result<InvoiceDetails, BlueprintError> {
+    let client = load_client(conn, &input.client_id)?;
+    let issued_on = match &input.issued_on {
+        Some(day) => parse_day(day)?,
+        None => Local::now().date_naive(),
+    };
+    let due_on = input.due_on.as_deref().map(parse_day).transpose()?;
+    if let Some(due_on) = due_on.filter(|due_on| *due_on < issued_on) {
+        return Err(BlueprintError::validation(trf(
+            "error.invoice_due_before_issue",
+            &[("due", &due_on), ("issued", &issued_on)],
+        )));
+    }
+    let period = input.period.filter(|p| !p.is_empty());
+    if let Some(period) = &period {
+        parse_period(period)?;
+    }
+    let currency = normalize_currency(input.currency.as_deref().unwrap_or(&client.currency))?;
+
+    let (items, tracked) = match input.items.filter(|items| !items.is_empty()) {
+        Some(items) => (items, Vec::new()),
+        None => default_items(conn, &client, period.as_deref())?,
+    };
+    for item in &items {
+        if let Some(period) = &item.period {
+            parse_period(period)?;
+        }
+        if item.description.trim().is_empty() {
+            return Err(BlueprintError::validation(tr("error.invoice_item_no_description")));
+        }
+        if !item.unit_price.is_finite() {
+            return Err(BlueprintError::validation(trf(
+                "error.invoice_item_bad_price",
+                &[("price", &item.unit_price)],
+            )));
+        }
+        let quantity = item.quantity.unwrap_or(1.0);
+        if !(quantity.is_finite() && quantity > 0.0) {
+            return Err(BlueprintError::validation(trf(
+                "error.invoice_item_bad_quantity",
+                &[("quantity", &quantity)],
+            )));
+        }
+    }
+
+    let tx = conn.transaction()?;
+    let id = Uuid::new_v4().to_string();
+    let number = next_number(&tx, issued_on)?;
+    tx.execute(
+        "INSERT INTO invoices (id, number, client_id, period, issued_on, due_on, currency, status, notes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            id,
+            number,
+            client.id,
+            period,
+            issued_on.format("%Y-%m-%d").to_string(),
+            due_on.map(|day| day.format("%Y-%m-%d").to_string()),
+            currency,
+            STATUS_ISSUED,
+            input.notes.filter(|n| !n.is_empty()),
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    for (position, item) in items.into_iter().enumerate() {
+        tx.execute(
+            "INSERT INTO invoice_items (id, invoice_id, position, description, quantity, unit_price, period)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                Uuid::new_v4().to_string(),
+                id,
+                position as i32,
+                item.description.trim(),
+                item.quantity.unwrap_or(1.0),
+                item.unit_price,
+                item.period.or_else(|| period.clone())
+            ],
+        )?;
+    }
+    for entry_id in tracked {
+        tx.execute(
+            "UPDATE time_entries SET invoice_id = ?1 WHERE id = ?2",
+            params![id, entry_id],
+        )?;
+    }
+    tx.commit()?;
+    load(conn, &id)
+}
+
+// Goes through the same upsert as `toggle_payment`, so the client's payment
+// grid follows the invoice both ways. Reopening leaves alone the periods that
+// another paid invoice or the recorded transactions still cover.
+pub fn set_paid(conn: &mut Connection, id: &str, paid: bool) -> Result<InvoiceDetails, BlueprintError> {
+    let details = load(conn, id)?;
+    let mut periods: Vec<&str> = details
+        .items
+        .iter()
+        .filter_map(|item| item.period.as_deref())
+        .chain(details.invoice.period.as_deref())
+        .collect();
+    periods.sort_unstable();
+    periods.dedup();
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        "UPDATE invoices SET status = ?1, paid_at = ?2 WHERE id = ?3",
+        params![
+            if paid { STATUS_PAID } else { STATUS_ISSUED },
+            paid.then(|| Utc::now().to_rfc3339()),
+            id
+        ],
+    )?;
+    let client_id = &details.invoice.client_id;
+    for period in periods {
+        let still_covered = !paid
+            && (paid_elsewhere(&tx, client_id, period, id)?
+                || clients::covered_by_transactions(&tx, client_id, period)?);
+        if still_covered {
+            continue;
+        }
+        clients::set_payment(&tx, client_id, period, paid)?;
+    }
+    tx.commit()?;
+    load(conn, id)
+}
+
+fn paid_elsewhere(conn: &Connection, client_id: &str, period: &str, invoice_id: &str) -> Result<bool, BlueprintError> {
+    Ok(conn.query_row(
+        "SELECT EXISTS (
+             SELECT 1 FROM invoices i
+             WHERE i.client_id = ?1 AND i.id != ?3 AND i.status = ?4
+               AND (i.period = ?2
+                    OR EXISTS (SELECT 1 FROM invoice_items t WHERE t.invoice_id = i.id AND t.period = ?2))
+         )",
+        params![client_id, period, invoice_id, STATUS_PAID],
+        |row| row.get(0),
+    )?)
+}
+
+fn money(amount: f64, currency: &str) -> String {
+    format!("{:.2} {}", amount, currency)
+}
+
+fn quantity(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_lines(text: &str) -> String {
+    text.lines().map(escape).collect::<Vec<_>>().join("<br>")
+}
+
+pub fn render_html(conn: &Connection, id: &str) -> Result<String, BlueprintError> {
+    let details = load(conn, id)?;
+    let client = load_client(conn, &details.invoice.client_id)?;
+    let company = CompanyDetails::load(conn);
+    let invoice = &details.invoice;
+
+    let rows: String = details
+        .items
+        .iter()
+        .map(|item| {
+            format!(
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape(&item.description),
+                quantity(item.quantity),
+                money(item.unit_price, &invoice.currency),
+                money(item.amount(), &invoice.currency)
+            )
+        })
+        .collect();
+    let due = invoice
+        .due_on
+        .as_ref()
+        .map(|due| format!("<div>{}: {}</div>", tr("invoice.due"), escape(due)))
+        .unwrap_or_default();
+    let notes = invoice
+        .notes
+        .as_ref()
+        .map(|notes| format!("<p class=\"notes\">{}</p>", escape_lines(notes)))
+        .unwrap_or_default();
+    let bank = if company.bank.is_empty() {
+        String::new()
+    } else {
+        format!("<p class=\"bank\">{}</p>", escape_lines(&company.bank))
+    };
+
+    Ok(format!(
+        "<!DOCTYPE html>
+<html lang=\"{lang}\"><head><meta charset=\"utf-8\"><title>{title}</title>
+<style>
+body {{ font-family: -apple-system, 'Segoe UI', Arial, sans-serif; color: #111; margin: 40px; font-size: 14px; }}
+h1 {{ font-size: 24px; margin: 0 0 24px; }}
+.parties {{ display: flex; justify-content: space-between; gap: 32px; margin-bottom: 24px; }}
+.label {{ color: #666; font-size: 12px; text-transform: uppercase; }}
+table {{ width: 100%; border-collapse: collapse; }}
+th, td {{ padding: 8px 4px; border-bottom: 1px solid #ddd; text-align: left; }}
+.num {{ text-align: right; white-space: nowrap; }}
+.total td {{ font-weight: 700; border-bottom: none; }}
+.notes, .bank {{ margin-top: 24px; color: #444; }}
+</style></head><body>
+<h1>{title}</h1>
+<div class=\"parties\">
+<div><div class=\"label\">{from_label}</div><strong>{company_name}</strong><div>{company_details}</div></div>
+<div><div class=\"label\">{to_label}</div><strong>{client_name}</strong><div>{client_contact}</div></div>
+<div><div>{issued_label}: {issued_on}</div>{due}</div>
+</div>
+<table>
+<thead><tr><th>{description}</th><th class=\"num\">{qty}</th><th class=\"num\">{price}</th><th class=\"num\">{amount}</th></tr></thead>
+<tbody>{rows}</tbody>
+<tfoot><tr class=\"total\"><td colspan=\"3\">{total_label}</td><td class=\"num\">{total}</td></tr></tfoot>
+</table>
+{notes}{bank}
+</body></html>
+",
+        lang = crate::i18n::current().code(),
+        title = escape(&trf("invoice.title", &[("number", &invoice.number)])),
+        from_label = tr("invoice.from"),
+        company_name = escape(&company.name),
+        company_details = escape_lines(&company.details),
+        to_label = tr("invoice.bill_to"),
+        client_name = escape(&client.name),
+        client_contact = escape_lines(client.contact.as_deref().unwrap_or_default()),
+        issued_label = tr("invoice.issued"),
+        issued_on = escape(&invoice.issued_on),
+        description = tr("invoice.description"),
+        qty = tr("invoice.quantity"),
+        price = tr("invoice.price"),
+        amount = tr("invoice.amount"),
+        total_label = tr("invoice.total"),
+        total = money(details.total, &invoice.currency),
+    ))
+}
+
+const MARGIN: f32 = 50.0;
+const BOTTOM: f32 = 70.0;
+
+// Greedy word wrap to `width` points.
+fn wrap(doc: &PdfDocument, text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty() && doc.text_width(&candidate, size) > width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+// Uses the configured `invoice_font` or the first system font that has every
+// character of the invoice; Helvetica otherwise.
+pub fn pick_font(conn: &Connection, sample: &str) -> Option<TrueTypeFont> {
+    if let Some(path) = db::get_setting(conn, "invoice_font").filter(|p| !p.is_empty()) {
+        if let Some(font) = TrueTypeFont::load(Path::new(&path)) {
+            return Some(font);
+        }
+    }
+    if sample.is_ascii() {
+        return None;
+    }
+    crate::pdf::SYSTEM_FONTS
+        .iter()
+        .filter_map(|path| TrueTypeFont::load(Path::new(path)))
+        .find(|font| font.covers(sample))
+}
+
+pub fn render_pdf(
+    conn: &Connection,
+    id: &str,
+    font: Option<TrueTypeFont>,
+) -> Result<Vec<u8>, BlueprintError> {
+    let details = load(conn, id)?;
+    let client = load_client(conn, &details.invoice.client_id)?;
+    let company = CompanyDetails::load(conn);
+    let invoice = &details.invoice;
+    let mut doc = PdfDocument::new(font);
+    let right = A4_WIDTH - MARGIN;
+    let mut y = A4_HEIGHT - MARGIN - 10.0;
+
+    doc.text(MARGIN, y, 20.0, &trf("invoice.title", &[("number", &invoice.number)]));
+    doc.text_right(right, y + 6.0, 10.0, &format!("{}: {}", tr("invoice.issued"), invoice.issued_on));
+    if let Some(due) = &invoice.due_on {
+        doc.text_right(right, y - 8.0, 10.0, &format!("{}: {}", tr("invoice.due"), due));
+    }
+    y -= 40.0;
+
+    let column = (A4_WIDTH - 2.0 * MARGIN) / 2.0;
+    let mut parties_y = y;
+    for (x, label, name, body) in [
+        (MARGIN, tr("invoice.from"), company.name.as_str(), company.details.as_str()),
+        (
+            MARGIN + column,
+            tr("invoice.bill_to"),
+            client.name.as_str(),
+            client.contact.as_deref().unwrap_or_default(),
+        ),
+    ] {
+        let mut line_y = y;
+        doc.text(x, line_y, 8.0, &label.to_uppercase());
+        line_y -= 14.0;
+        doc.text(x, line_y, 11.0, name);
+        for line in wrap(&doc, body, 9.0, column - 10.0) {
+            line_y -= 12.0;
+            doc.text(x, line_y, 9.0, &line);
+        }
+        parties_y = parties_y.min(line_y);
+    }
+    y = parties_y - 30.0;
+
+    let qty_x = right - 200.0;
+    let price_x = right - 100.0;
+    let description_width = qty_x - MARGIN - 60.0;
+    let header = |doc: &mut PdfDocument, y: f32| {
+        doc.text(MARGIN, y, 9.0, tr("invoice.description"));
+        doc.text_right(qty_x, y, 9.0, tr("invoice.quantity"));
+        doc.text_right(price_x, y, 9.0, tr("invoice.price"));
+        doc.text_right(right, y, 9.0, tr("invoice.amount"));
+        doc.line(MARGIN, y - 6.0, right, y - 6.0);
+    };
+    header(&mut doc, y);
+    y -= 22.0;
+    for item in &details.items {
+        let lines = wrap(&doc, &item.description, 10.0, description_width);
+        if y - 12.0 * (lines.len() as f32) < BOTTOM {
+            doc.new_page();
+            y = A4_HEIGHT - MARGIN;
+            header(&mut doc, y);
+            y -= 22.0;
+        }
+        doc.text_right(qty_x, y, 10.0, &quantity(item.quantity));
+        doc.text_right(price_x, y, 10.0, &money(item.unit_price, &invoice.currency));
+        doc.text_right(right, y, 10.0, &money(item.amount(), &invoice.currency));
+        for line in &lines {
+            doc.text(MARGIN, y, 10.0, line);
+            y -= 12.0;
+        }
+        y -= 6.0;
+    }
+    doc.line(MARGIN, y + 8.0, right, y + 8.0);
+    y -= 10.0;
+    doc.text(MARGIN, y, 12.0, tr("invoice.total"));
+    doc.text_right(right, y, 12.0, &money(details.total, &invoice.currency));
+    y -= 30.0;
+
+    let footer = [invoice.notes.as_deref().unwrap_or_default(), company.bank.as_str()];
+    let footer: Vec<String> = footer
+        .iter()
+        .flat_map(|text| wrap(&doc, text, 9.0, right - MARGIN))
+        .collect();
+    for line in footer {
+        if y < BOTTOM {
+            doc.new_page();
+            y = A4_HEIGHT - MARGIN;
+        }
+        doc.text(MARGIN, y, 9.0, &line);
+        y -= 12.0;
+    }
+    Ok(doc.render())
+}
+
+fn pdf_sample(conn: &Connection, id: &str) -> Result<String, BlueprintError> {
+    let details = load(conn, id)?;
+    let company = CompanyDetails::load(conn);
+    let mut sample = format!(
+        "{}{}{}{}{}",
+        details.client_name,
+        details.invoice.notes.unwrap_or_default(),
+        company.name,
+        company.details,
+        company.bank
+    );
+    for item in details.items {
+        sample.push_str(&item.description);
+    }
+    for key in ["invoice.title", "invoice.from", "invoice.bill_to", "invoice.total"] {
+        sample.push_str(tr(key));
+    }
+    Ok(sample)
+}
+
+#[tauri::command]
+pub fn get_invoices(
+    client_id: Option<String>,
+    state: State<DbState>,
+) -> Result<Vec<InvoiceDetails>, BlueprintError> {
+    let conn = state.conn()?;
+    list(&conn, client_id.as_deref())
+}
+
+#[tauri::command]
+pub fn create_invoice(
+    input: CreateInvoiceInput,
+    state: State<DbState>,
+) -> Result<InvoiceDetails, BlueprintError> {
+    let mut conn = state.conn()?;
+    create(&mut conn, input)
+}
+
+#[tauri::command]
+pub fn mark_invoice_paid(
+    id: String,
+    paid: bool,
+    state: State<DbState>,
+) -> Result<InvoiceDetails, BlueprintError> {
+    let mut conn = state.conn()?;
+    set_paid(&mut conn, &id, paid)
+}
+
+#[tauri::command]
+pub fn delete_invoice(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    conn.execute("DELETE FROM invoices WHERE id = ?1", [&id])?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn render_invoice_html(id: String, state: State<DbState>) -> Result<String, BlueprintError> {
+    let conn = state.conn()?;
+    render_html(&conn, &id)
+}
+
+// Base64, so the webview can turn it into a download.
+#[tauri::command]
+pub fn render_invoice_pdf(id: String, state: State<DbState>) -> Result<String, BlueprintError> {
+    let conn = state.conn()?;
+    let font = pick_font(&conn, &pdf_sample(&conn, &id)?);
+    Ok(STANDARD.encode(render_pdf(&conn, &id, font)?))
+}
@@ -1,6 +1,14 @@
 pub mod ai;
+pub mod charges;
 pub mod clients;
+pub mod dashboard;
+pub mod database;
+pub mod fx;
+pub mod invoices;
 pub mod notes;
 pub mod notifications;
+pub mod profile;
+pub mod schedule;
 pub mod services;
 pub mod settings;
+pub mod time_entries;
@@ -1,11 +1,14 @@
 use crate::db::DbState;
+use crate::error::BlueprintError;
 use chrono::Utc;
-use rusqlite::params;
+use rusqlite::{params, Row};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Note {
     pub id: String,
     pub title: String,
@@ -15,20 +18,54 @@ pub struct Note {
     pub sort_order: i32,
     pub created_at: String,
     pub updated_at: String,
+    pub remind_at: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Category {
     pub id: String,
     pub name: String,
     pub color: String,
 }
 
+impl Note {
+    pub const COLUMNS: &'static str =
+        "id, title, content, category_id, completed, sort_order, created_at, updated_at, remind_at";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Note {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            content: row.get(2)?,
+            category_id: row.get(3)?,
+            completed: row.get::<_, i32>(4)? != 0,
+            sort_order: row.get(5)?,
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+            remind_at: row.get(8)?,
+        })
+    }
+}
+
+impl Category {
+    pub const COLUMNS: &'static str = "id, name, color";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Category {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            color: row.get(2)?,
+        })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateNoteInput {
     pub title: String,
     pub content: Option<String>,
     pub category_id: Option<String>,
+    pub remind_at: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,6 +74,7 @@ pub struct UpdateNoteInput {
     pub title: String,
     pub content: Option<String>,
     pub category_id: Option<String>,
+    pub remind_at: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,53 +94,27 @@ pub struct UpdateCategoryInput {
 pub fn get_notes(
     category_id: Option<String>,
     state: State<DbState>,
-) -> Result<Vec<Note>, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+) -> Result<Vec<Note>, BlueprintError> {
+    let conn = state.conn()?;
     let notes: Vec<Note> = if let Some(cid) = category_id {
         let mut stmt = conn
-            .prepare(
-                "SELECT id, title, content, category_id, completed, sort_order, created_at, updated_at \
-                 FROM notes WHERE category_id = ?1 ORDER BY completed ASC, sort_order ASC, created_at DESC",
-            )
-            .map_err(|e| e.to_string())?;
+            .prepare(&format!(
+                "SELECT {} FROM notes WHERE category_id = ?1 ORDER BY completed ASC, sort_order ASC, created_at DESC",
+                Note::COLUMNS
+            ))?;
         let rows: Vec<Note> = stmt
-            .query_map([&cid], |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    content: row.get(2)?,
-                    category_id: row.get(3)?,
-                    completed: row.get::<_, i32>(4)? != 0,
-                    sort_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            })
-            .map_err(|e| e.to_string())?
+            .query_map([&cid], Note::from_row)?
             .filter_map(|r| r.ok())
             .collect();
         rows
     } else {
         let mut stmt = conn
-            .prepare(
-                "SELECT id, title, content, category_id, completed, sort_order, created_at, updated_at \
-                 FROM notes ORDER BY completed ASC, sort_order ASC, created_at DESC",
-            )
-            .map_err(|e| e.to_string())?;
+            .prepare(&format!(
+                "SELECT {} FROM notes ORDER BY completed ASC, sort_order ASC, created_at DESC",
+                Note::COLUMNS
+            ))?;
         let rows: Vec<Note> = stmt
-            .query_map([], |row| {
-                Ok(Note {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    content: row.get(2)?,
-                    category_id: row.get(3)?,
-                    completed: row.get::<_, i32>(4)? != 0,
-                    sort_order: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            })
-            .map_err(|e| e.to_string())?
+            .query_map([], Note::from_row)?
             .filter_map(|r| r.ok())
             .collect();
         rows
@@ -111,16 +123,15 @@ pub fn get_notes(
 }
 
 #[tauri::command]
-pub fn create_note(input: CreateNoteInput, state: State<DbState>) -> Result<Note, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn create_note(input: CreateNoteInput, state: State<DbState>) -> Result<Note, BlueprintError> {
+    let conn = state.conn()?;
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     conn.execute(
-        "INSERT INTO notes (id, title, content, category_id, completed, sort_order, created_at, updated_at) \
-         VALUES (?1, ?2, ?3, ?4, 0, 0, ?5, ?5)",
-        params![id, input.title, input.content, input.category_id, now],
-    )
-    .map_err(|e| e.to_string())?;
+        "INSERT INTO notes (id, title, content, category_id, completed, sort_order, created_at, updated_at, remind_at) \
+         VALUES (?1, ?2, ?3, ?4, 0, 0, ?5, ?5, ?6)",
+        params![id, input.title, input.content, input.category_id, now, input.remind_at],
+    )?;
     Ok(Note {
         id,
         title: input.title,
@@ -130,56 +141,49 @@ pub fn create_note(input: CreateNoteInput, state: State<DbState>) -> Result<Note
         sort_order: 0,
         created_at: now.clone(),
         updated_at: now,
+        remind_at: input.remind_at,
     })
 }
 
 #[tauri::command]
-pub fn update_note(input: UpdateNoteInput, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn update_note(input: UpdateNoteInput, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
     let now = Utc::now().to_rfc3339();
     conn.execute(
-        "UPDATE notes SET title=?1, content=?2, category_id=?3, updated_at=?4 WHERE id=?5",
-        params![input.title, input.content, input.category_id, now, input.id],
-    )
-    .map_err(|e| e.to_string())?;
+        "UPDATE notes SET title=?1, content=?2, category_id=?3, updated_at=?4, remind_at=?5 WHERE id=?6",
+        params![input.title, input.content, input.category_id, now, input.remind_at, input.id],
+    )?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_note(id: String, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
-    conn.execute("DELETE FROM notes WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+pub fn delete_note(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    conn.execute("DELETE FROM notes WHERE id = ?1", [&id])?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn toggle_note(id: String, completed: bool, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn toggle_note(id: String, completed: bool, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
     let now = Utc::now().to_rfc3339();
     conn.execute(
         "UPDATE notes SET completed=?1, updated_at=?2 WHERE id=?3",
         params![completed as i32, now, id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_categories(state: State<DbState>) -> Result<Vec<Category>, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn get_categories(state: State<DbState>) -> Result<Vec<Category>, BlueprintError> {
+    let conn = state.conn()?;
     let mut stmt = conn
-        .prepare("SELECT id, name, color FROM categories ORDER BY name COLLATE NOCASE")
-        .map_err(|e| e.to_string())?;
+        .prepare(&format!(
+            "SELECT {} FROM categories ORDER BY name COLLATE NOCASE",
+            Category::COLUMNS
+        ))?;
     let categories = stmt
-        .query_map([], |row| {
-            Ok(Category {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
+        .query_map([], Category::from_row)?
         .filter_map(|r| r.ok())
         .collect();
     Ok(categories)
@@ -189,14 +193,13 @@ pub fn get_categories(state: State<DbState>) -> Result<Vec<Category>, String> {
 pub fn create_category(
     input: CreateCategoryInput,
     state: State<DbState>,
-) -> Result<Category, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+) -> Result<Category, BlueprintError> {
+    let conn = state.conn()?;
     let id = Uuid::new_v4().to_string();
     conn.execute(
         "INSERT INTO categories (id, name, color) VALUES (?1, ?2, ?3)",
         params![id, input.name, input.color],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
     Ok(Category {
         id,
         name: input.name,
@@ -205,47 +208,32 @@ pub fn create_category(
 }
 
 #[tauri::command]
-pub fn update_category(input: UpdateCategoryInput, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn update_category(input: UpdateCategoryInput, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
     conn.execute(
         "UPDATE categories SET name = ?1, color = ?2 WHERE id = ?3",
         params![input.name, input.color, input.id],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_category(id: String, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
-    conn.execute("DELETE FROM categories WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+pub fn delete_category(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    conn.execute("DELETE FROM categories WHERE id = ?1", [&id])?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_incomplete_notes(state: State<DbState>) -> Result<Vec<Note>, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn get_incomplete_notes(state: State<DbState>) -> Result<Vec<Note>, BlueprintError> {
+    let conn = state.conn()?;
     let mut stmt = conn
-        .prepare(
-            "SELECT id, title, content, category_id, completed, sort_order, created_at, updated_at \
-             FROM notes WHERE completed = 0 ORDER BY sort_order ASC, created_at DESC LIMIT 50",
-        )
-        .map_err(|e| e.to_string())?;
+        .prepare(&format!(
+            "SELECT {} FROM notes WHERE completed = 0 ORDER BY sort_order ASC, created_at DESC LIMIT 50",
+            Note::COLUMNS
+        ))?;
     let notes = stmt
-        .query_map([], |row| {
-            Ok(Note {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                content: row.get(2)?,
-                category_id: row.get(3)?,
-                completed: row.get::<_, i32>(4)? != 0,
-                sort_order: row.get(5)?,
-                created_at: row.get(6)?,
-                updated_at: row.get(7)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
+        .query_map([], Note::from_row)?
         .filter_map(|r| r.ok())
         .collect();
     Ok(notes)
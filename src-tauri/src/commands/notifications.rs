@@ -1,88 +1,107 @@
+use crate::commands::services::normalize_thresholds;
 use crate::db::DbState;
-use chrono::NaiveDate;
+use crate::error::BlueprintError;
+use crate::scheduler::{self, NotificationEntry};
+use rusqlite::{params, Row};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use tauri::State;
 use tauri_plugin_autostart::ManagerExt;
 
-fn days_remaining(expires_at: &str) -> i64 {
-    let today = chrono::Local::now().date_naive();
-    if let Ok(exp) = NaiveDate::parse_from_str(expires_at, "%Y-%m-%d") {
-        (exp - today).num_days()
-    } else {
-        i64::MAX
+// Per-category reminder thresholds, keyed by `services.category`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationRule {
+    pub category: String,
+    pub thresholds: String,
+}
+
+impl NotificationRule {
+    pub const COLUMNS: &'static str = "category, thresholds";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(NotificationRule {
+            category: row.get(0)?,
+            thresholds: row.get(1)?,
+        })
     }
 }
 
+// `run_pass` reads behind the app lock for the background timer; a pass
+// asked for by the webview has to respect it.
 #[tauri::command]
-pub fn check_and_notify(
+pub fn check_and_notify(state: State<DbState>, app: tauri::AppHandle) -> Result<(), BlueprintError> {
+    if state.is_app_locked() {
+        return Err(BlueprintError::AppLocked);
+    }
+    Ok(scheduler::run_pass(&app)?)
+}
+
+#[tauri::command]
+pub fn get_notification_history(
+    limit: Option<i64>,
+    unread_only: Option<bool>,
     state: State<DbState>,
-    app: tauri::AppHandle,
-) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
-    let mut stmt = conn
-        .prepare(
-            "SELECT service_name, project_name, expires_at, notify_days \
-             FROM services",
-        )
-        .map_err(|e| e.to_string())?;
+) -> Result<Vec<NotificationEntry>, BlueprintError> {
+    let conn = state.conn()?;
+    Ok(scheduler::history(
+        &conn,
+        limit.unwrap_or(100),
+        unread_only.unwrap_or(false),
+    )?)
+}
 
-    struct Row {
-        service_name: String,
-        project_name: String,
-        expires_at: String,
-        notify_days: i32,
-    }
+#[tauri::command]
+pub fn mark_notification_read(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    Ok(scheduler::mark_read(&conn, &id)?)
+}
 
-    let rows: Vec<Row> = stmt
-        .query_map([], |row| {
-            Ok(Row {
-                service_name: row.get(0)?,
-                project_name: row.get(1)?,
-                expires_at: row.get(2)?,
-                notify_days: row.get(3)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
+#[tauri::command]
+pub fn get_notification_rules(state: State<DbState>) -> Result<Vec<NotificationRule>, BlueprintError> {
+    let conn = state.conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM notification_rules ORDER BY category COLLATE NOCASE",
+        NotificationRule::COLUMNS
+    ))?;
+    let rules = stmt
+        .query_map([], NotificationRule::from_row)?
         .filter_map(|r| r.ok())
         .collect();
+    Ok(rules)
+}
 
-    drop(stmt);
-    drop(conn);
-
-    use tauri_plugin_notification::NotificationExt;
-    for row in rows {
-        let days = days_remaining(&row.expires_at);
-        if days < 0 || days <= row.notify_days as i64 {
-            let title = if days < 0 {
-                format!("Истёк: {}", row.service_name)
-            } else if days == 0 {
-                format!("Истекает сегодня: {}", row.service_name)
-            } else {
-                format!("Истекает через {} дн: {}", days, row.service_name)
-            };
-            let body = format!("{} · {}", row.project_name, row.expires_at);
-            let _ = app
-                .notification()
-                .builder()
-                .title(&title)
-                .body(&body)
-                .show();
-        }
-    }
-
+// Empty thresholds remove the override and the category falls back to the
+// global rule.
+#[tauri::command]
+pub fn set_notification_rule(
+    category: String,
+    thresholds: Option<String>,
+    state: State<DbState>,
+) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    match normalize_thresholds(thresholds.as_deref())? {
+        Some(thresholds) => conn.execute(
+            "INSERT INTO notification_rules (category, thresholds) VALUES (?1, ?2)
+             ON CONFLICT(category) DO UPDATE SET thresholds = excluded.thresholds",
+            params![category, thresholds],
+        )?,
+        None => conn.execute("DELETE FROM notification_rules WHERE category = ?1", [&category])?,
+    };
     Ok(())
 }
 
 #[tauri::command]
-pub fn toggle_autostart(enable: bool, app: tauri::AppHandle) -> Result<(), String> {
+pub fn toggle_autostart(enable: bool, app: tauri::AppHandle) -> Result<(), BlueprintError> {
     let autostart = app.autolaunch();
     if enable {
-        autostart.enable().map_err(|e| e.to_string())
+        autostart.enable().map_err(BlueprintError::from)
     } else {
-        autostart.disable().map_err(|e| e.to_string())
+        autostart.disable().map_err(BlueprintError::from)
     }
 }
 
 #[tauri::command]
-pub fn get_autostart_enabled(app: tauri::AppHandle) -> Result<bool, String> {
-    app.autolaunch().is_enabled().map_err(|e| e.to_string())
+pub fn get_autostart_enabled(app: tauri::AppHandle) -> Result<bool, BlueprintError> {
+    app.autolaunch().is_enabled().map_err(BlueprintError::from)
 }
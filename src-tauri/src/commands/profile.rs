@@ -0,0 +1,1443 @@
+use crate::commands::charges::ServiceCharge;
+use crate::commands::clients::{Client, ClientPayment, PaymentTransaction};
+use crate::commands::fx::FxRate;
+use crate::commands::invoices::{Invoice, InvoiceItem};
+use crate::commands::notes::{Category, Note};
+use crate::commands::notifications::NotificationRule;
+use crate::commands::schedule::Period;
+use crate::commands::services::{Service, ServiceRenewal};
+use crate::commands::settings::{ChatMessage, Setting};
+use crate::commands::time_entries::TimeEntry;
+use crate::crypto::PassphraseEnvelope;
+use crate::db::DbState;
+use crate::error::BlueprintError;
+use crate::i18n;
+use crate::vault::{self, SecretValue, Vault, VaultState};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection, OptionalExtension, Row as SqlRow, Transaction};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, State};
+
+pub const EXPORT_VERSION: i32 = 16;
+pub const ENCRYPTED_FORMAT: &str = "blueprint-profile-encrypted";
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileExport {
+    pub version: i32,
+    pub exported_at: String,
+    pub secrets_included: bool,
+    pub settings: Vec<Setting>,
+    pub secrets: Vec<SecretValue>,
+    pub clients: Vec<Client>,
+    pub client_payments: Vec<ClientPayment>,
+    pub payment_transactions: Vec<PaymentTransaction>,
+    pub invoices: Vec<Invoice>,
+    pub invoice_items: Vec<InvoiceItem>,
+    pub time_entries: Vec<TimeEntry>,
+    pub services: Vec<Service>,
+    pub service_renewals: Vec<ServiceRenewal>,
+    pub service_charges: Vec<ServiceCharge>,
+    pub fx_rates: Vec<FxRate>,
+    pub notification_rules: Vec<NotificationRule>,
+    pub categories: Vec<Category>,
+    pub notes: Vec<Note>,
+    pub chat_history: Vec<ChatMessage>,
+}
+
+type Upgrade = fn(&mut Value) -> Result<(), String>;
+
+// UPGRADES[n] turns a version n + 1 export into version n + 2. Never edit a
+// shipped upgrade, append a new one and bump EXPORT_VERSION instead.
+const UPGRADES: &[Upgrade] = &[
+    upgrade_v1_to_v2,
+    upgrade_v2_to_v3,
+    upgrade_v3_to_v4,
+    upgrade_v4_to_v5,
+    upgrade_v5_to_v6,
+    upgrade_v6_to_v7,
+    upgrade_v7_to_v8,
+    upgrade_v8_to_v9,
+    upgrade_v9_to_v10,
+    upgrade_v10_to_v11,
+    upgrade_v11_to_v12,
+    upgrade_v12_to_v13,
+    upgrade_v13_to_v14,
+    upgrade_v14_to_v15,
+    upgrade_v15_to_v16,
+];
+
+fn table_rows_mut<'a>(
+    profile: &'a mut Value,
+    table: &str,
+) -> impl Iterator<Item = &'a mut serde_json::Map<String, Value>> {
+    profile
+        .get_mut(table)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+fn fill_defaults(profile: &mut Value, table: &str, defaults: &[(&str, Value)]) {
+    for row in table_rows_mut(profile, table) {
+        for (field, default) in defaults {
+            if row.get(*field).is_none_or(Value::is_null) {
+                row.insert(field.to_string(), default.clone());
+            }
+        }
+    }
+}
+
+fn profile_object(profile: &mut Value) -> Result<&mut serde_json::Map<String, Value>, String> {
+    profile
+        .as_object_mut()
+        .ok_or_else(|| i18n::tr("error.profile_not_object").to_string())
+}
+
+// For upgrades that introduce a table: older exports simply have no rows.
+fn add_table(profile: &mut Value, table: &str) -> Result<(), String> {
+    profile_object(profile)?.entry(table).or_insert(json!([]));
+    Ok(())
+}
+
+fn int_to_bool(profile: &mut Value, table: &str, field: &str) {
+    for row in table_rows_mut(profile, table) {
+        if let Some(n) = row.get(field).and_then(Value::as_i64) {
+            row.insert(field.to_string(), Value::Bool(n != 0));
+        }
+    }
+}
+
+// Version 1 stored every table as loose maps: flags were 0/1 integers and
+// columns with database defaults could be left out entirely.
+fn upgrade_v1_to_v2(profile: &mut Value) -> Result<(), String> {
+    int_to_bool(profile, "client_payments", "paid");
+    int_to_bool(profile, "notes", "completed");
+    fill_defaults(
+        profile,
+        "clients",
+        &[("payment_type", json!("monthly")), ("currency", json!("RUB"))],
+    );
+    fill_defaults(profile, "client_payments", &[("paid", json!(false))]);
+    fill_defaults(
+        profile,
+        "services",
+        &[("currency", json!("USD")), ("notify_days", json!(7))],
+    );
+    fill_defaults(profile, "categories", &[("color", json!("#1a73e8"))]);
+    fill_defaults(
+        profile,
+        "notes",
+        &[("completed", json!(false)), ("sort_order", json!(0))],
+    );
+    Ok(())
+}
+
+fn upgrade_v2_to_v3(profile: &mut Value) -> Result<(), String> {
+    let map = profile_object(profile)?;
+    map.entry("secrets_included").or_insert(json!(true));
+    Ok(())
+}
+
+// Version 4 moved API keys out of settings into the secret vault; older
+// exports still carry them as plain setting rows.
+fn upgrade_v3_to_v4(profile: &mut Value) -> Result<(), String> {
+    let exported_at = profile
+        .get("exported_at")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let map = profile_object(profile)?;
+    let mut secrets = Vec::new();
+    if let Some(settings) = map.get_mut("settings").and_then(Value::as_array_mut) {
+        settings.retain(|row| {
+            let key = row.get("key").and_then(Value::as_str).unwrap_or_default();
+            if !vault::APP_SECRETS.contains(&key) {
+                return true;
+            }
+            if let Some(value) = row.get("value").and_then(Value::as_str) {
+                if !value.is_empty() {
+                    secrets.push(json!({ "name": key, "value": value, "updated_at": exported_at }));
+                }
+            }
+            false
+        });
+    }
+    map.insert("secrets".to_string(), Value::Array(secrets));
+    Ok(())
+}
+
+fn upgrade_v4_to_v5(profile: &mut Value) -> Result<(), String> {
+    fill_defaults(profile, "notes", &[("remind_at", Value::Null)]);
+    Ok(())
+}
+
+fn upgrade_v5_to_v6(profile: &mut Value) -> Result<(), String> {
+    fill_defaults(
+        profile,
+        "services",
+        &[("snoozed_until", Value::Null), ("acknowledged_expires_at", Value::Null)],
+    );
+    Ok(())
+}
+
+fn upgrade_v6_to_v7(profile: &mut Value) -> Result<(), String> {
+    fill_defaults(profile, "clients", &[("payment_notify_days", Value::Null)]);
+    Ok(())
+}
+
+// Version 8 replaced the single `notify_days` window with threshold rules.
+fn upgrade_v7_to_v8(profile: &mut Value) -> Result<(), String> {
+    for service in table_rows_mut(profile, "services") {
+        let notify_days = service.get("notify_days").and_then(Value::as_i64);
+        let thresholds = match notify_days {
+            Some(days) if days != 7 => json!(format!("{},0", days)),
+            _ => Value::Null,
+        };
+        service.entry("notify_thresholds").or_insert(thresholds);
+    }
+    add_table(profile, "notification_rules")
+}
+
+fn upgrade_v8_to_v9(profile: &mut Value) -> Result<(), String> {
+    fill_defaults(
+        profile,
+        "services",
+        &[
+            ("billing_cycle", Value::Null),
+            ("billing_cycle_days", Value::Null),
+            ("auto_renew", json!(false)),
+        ],
+    );
+    add_table(profile, "service_renewals")
+}
+
+fn upgrade_v9_to_v10(profile: &mut Value) -> Result<(), String> {
+    add_table(profile, "service_charges")
+}
+
+fn upgrade_v10_to_v11(profile: &mut Value) -> Result<(), String> {
+    add_table(profile, "fx_rates")
+}
+
+fn upgrade_v11_to_v12(profile: &mut Value) -> Result<(), String> {
+    add_table(profile, "invoices")?;
+    add_table(profile, "invoice_items")
+}
+
+fn upgrade_v12_to_v13(profile: &mut Value) -> Result<(), String> {
+    add_table(profile, "payment_transactions")
+}
+
+fn upgrade_v13_to_v14(profile: &mut Value) -> Result<(), String> {
+    fill_defaults(
+        profile,
+        "clients",
+        &[
+            ("start_date", Value::Null),
+            ("end_date", Value::Null),
+            ("pause_from", Value::Null),
+            ("pause_to", Value::Null),
+        ],
+    );
+    Ok(())
+}
+
+// Same normalization as migration m018: payment types other than the two
+// that existed before are treated as monthly.
+fn upgrade_v14_to_v15(profile: &mut Value) -> Result<(), String> {
+    for row in table_rows_mut(profile, "clients") {
+        if !matches!(row.get("payment_type").and_then(Value::as_str), Some("monthly" | "onetime")) {
+            row.insert("payment_type".to_string(), json!("monthly"));
+        }
+    }
+    fill_defaults(
+        profile,
+        "clients",
+        &[("included_hours", Value::Null), ("hourly_rate", Value::Null)],
+    );
+    Ok(())
+}
+
+fn upgrade_v15_to_v16(profile: &mut Value) -> Result<(), String> {
+    add_table(profile, "time_entries")
+}
+
+fn profile_issue(field: &str, message: String) -> ValidationIssue {
+    ValidationIssue {
+        severity: IssueSeverity::Error,
+        table: "profile".to_string(),
+        row: None,
+        id: None,
+        field: Some(field.to_string()),
+        message,
+    }
+}
+
+fn version_supported(version: i64) -> bool {
+    (1..=EXPORT_VERSION as i64).contains(&version)
+}
+
+pub fn parse_profile(mut value: Value) -> Result<ProfileExport, ValidationIssue> {
+    let version = value
+        .get("version")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| profile_issue("version", i18n::tr("error.profile_no_version").to_string()))?;
+    if !version_supported(version) {
+        return Err(profile_issue(
+            "version",
+            i18n::trf(
+                "error.profile_unknown_version",
+                &[("version", &version), ("supported", &EXPORT_VERSION)],
+            ),
+        ));
+    }
+
+    for upgrade in &UPGRADES[(version - 1) as usize..] {
+        upgrade(&mut value).map_err(|e| profile_issue("version", e))?;
+    }
+    value["version"] = json!(EXPORT_VERSION);
+
+    serde_path_to_error::deserialize(value)
+        .map_err(|e| profile_issue(&e.path().to_string(), e.inner().to_string()))
+}
+
+pub fn profile_schema() -> Value {
+    serde_json::to_value(schemars::schema_for!(ProfileExport)).unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_profile_schema() -> Value {
+    profile_schema()
+}
+
+fn table_error(table: &str, e: rusqlite::Error) -> BlueprintError {
+    BlueprintError::Database(format!("{}: {}", table, e))
+}
+
+fn query_all<T>(
+    conn: &Connection,
+    table: &str,
+    columns: &str,
+    map: fn(&SqlRow) -> rusqlite::Result<T>,
+) -> Result<Vec<T>, BlueprintError> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", columns, table))?;
+    let rows = stmt
+        .query_map([], map)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| table_error(table, e))?;
+    Ok(rows)
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportOptions {
+    #[serde(default)]
+    pub exclude_secrets: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedProfile {
+    pub format: String,
+    pub version: i32,
+    #[serde(flatten)]
+    pub envelope: PassphraseEnvelope,
+}
+
+impl EncryptedProfile {
+    fn aad(format: &str, version: i32) -> Vec<u8> {
+        format!("{}:{}", format, version).into_bytes()
+    }
+}
+
+impl ProfileExport {
+    pub fn strip_secrets(&mut self) {
+        self.secrets.clear();
+        for service in &mut self.services {
+            service.login = None;
+        }
+        self.secrets_included = false;
+    }
+
+    pub fn encrypt(&self, passphrase: &str) -> Result<EncryptedProfile, BlueprintError> {
+        let plaintext = serde_json::to_vec(self)?;
+        let aad = EncryptedProfile::aad(ENCRYPTED_FORMAT, self.version);
+        let envelope = PassphraseEnvelope::seal(passphrase, &plaintext, &aad)?;
+        Ok(EncryptedProfile {
+            format: ENCRYPTED_FORMAT.to_string(),
+            version: self.version,
+            envelope,
+        })
+    }
+}
+
+pub fn decrypt_payload(payload: Value, passphrase: Option<&str>) -> Result<Value, ValidationIssue> {
+    if payload.get("format").and_then(Value::as_str) != Some(ENCRYPTED_FORMAT) {
+        return Ok(payload);
+    }
+    let passphrase = passphrase.filter(|p| !p.is_empty()).ok_or_else(|| {
+        profile_issue("passphrase", i18n::tr("error.profile_needs_passphrase").to_string())
+    })?;
+    let encrypted: EncryptedProfile =
+        serde_json::from_value(payload).map_err(|e| profile_issue("format", e.to_string()))?;
+    let aad = EncryptedProfile::aad(&encrypted.format, encrypted.version);
+    let plaintext = encrypted
+        .envelope
+        .open(passphrase, &aad)
+        .map_err(|e| profile_issue("passphrase", e.to_string()))?;
+    serde_json::from_slice(&plaintext).map_err(|e| profile_issue("ciphertext", e.to_string()))
+}
+
+fn export_with_options(
+    conn: &Connection,
+    vault: &Vault,
+    options: ExportOptions,
+) -> Result<ProfileExport, BlueprintError> {
+    let mut profile = build_export(conn, vault)?;
+    if options.exclude_secrets {
+        profile.strip_secrets();
+    }
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn export_profile(
+    options: Option<ExportOptions>,
+    state: State<DbState>,
+    vault: State<VaultState>,
+) -> Result<ProfileExport, BlueprintError> {
+    let conn = state.conn()?;
+    let vault = vault.vault(&conn)?;
+    export_with_options(&conn, &vault, options.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn export_profile_encrypted(
+    passphrase: String,
+    options: Option<ExportOptions>,
+    state: State<DbState>,
+    vault: State<VaultState>,
+) -> Result<EncryptedProfile, BlueprintError> {
+    let profile = {
+        let conn = state.conn()?;
+        let vault = vault.vault(&conn)?;
+        export_with_options(&conn, &vault, options.unwrap_or_default())?
+    };
+    profile.encrypt(&passphrase)
+}
+
+pub fn build_export(conn: &Connection, vault: &Vault) -> Result<ProfileExport, BlueprintError> {
+    Ok(ProfileExport {
+        version: EXPORT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        secrets_included: true,
+        settings: query_all(conn, "settings", Setting::COLUMNS, Setting::from_row)?,
+        secrets: vault.all(conn)?,
+        clients: query_all(conn, "clients", Client::COLUMNS, Client::from_row)?,
+        client_payments: query_all(
+            conn,
+            "client_payments",
+            ClientPayment::COLUMNS,
+            ClientPayment::from_row,
+        )?,
+        payment_transactions: query_all(
+            conn,
+            "payment_transactions",
+            PaymentTransaction::COLUMNS,
+            PaymentTransaction::from_row,
+        )?,
+        invoices: query_all(conn, "invoices", Invoice::COLUMNS, Invoice::from_row)?,
+        invoice_items: query_all(
+            conn,
+            "invoice_items",
+            InvoiceItem::COLUMNS,
+            InvoiceItem::from_row,
+        )?,
+        time_entries: query_all(conn, "time_entries", TimeEntry::COLUMNS, TimeEntry::from_row)?,
+        services: query_all(conn, "services", Service::COLUMNS, Service::from_row)?,
+        service_renewals: query_all(
+            conn,
+            "service_renewals",
+            ServiceRenewal::COLUMNS,
+            ServiceRenewal::from_row,
+        )?,
+        service_charges: query_all(
+            conn,
+            "service_charges",
+            ServiceCharge::COLUMNS,
+            ServiceCharge::from_row,
+        )?,
+        fx_rates: query_all(conn, "fx_rates", FxRate::COLUMNS, FxRate::from_row)?,
+        notification_rules: query_all(
+            conn,
+            "notification_rules",
+            NotificationRule::COLUMNS,
+            NotificationRule::from_row,
+        )?,
+        categories: query_all(conn, "categories", Category::COLUMNS, Category::from_row)?,
+        notes: query_all(conn, "notes", Note::COLUMNS, Note::from_row)?,
+        chat_history: query_all(
+            conn,
+            "chat_history",
+            ChatMessage::COLUMNS,
+            ChatMessage::from_row,
+        )?,
+    })
+}
+
+type Row = serde_json::Map<String, Value>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    #[default]
+    Replace,
+    Merge,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ConflictStrategy {
+    #[serde(rename = "keep_local")]
+    Local,
+    #[serde(rename = "keep_incoming")]
+    Incoming,
+    #[default]
+    #[serde(rename = "keep_newest")]
+    Newest,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub mode: ImportMode,
+    #[serde(default)]
+    pub strategy: ConflictStrategy,
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TableImportReport {
+    pub table: String,
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub conflicts: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    KeptLocal,
+    KeptIncoming,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportConflict {
+    pub table: String,
+    pub id: String,
+    pub resolution: ConflictResolution,
+    pub local_timestamp: Option<String>,
+    pub incoming_timestamp: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    pub tables: Vec<TableImportReport>,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+#[derive(Clone, Copy)]
+enum DateFormat {
+    Timestamp,
+    Day,
+    Period,
+}
+
+struct TableSpec {
+    name: &'static str,
+    key: &'static str,
+    columns: &'static str,
+    dates: &'static [(&'static str, DateFormat)],
+    references: &'static [(&'static str, &'static str)],
+    timestamp: Option<&'static str>,
+}
+
+impl TableSpec {
+    fn columns(&self) -> Vec<&'static str> {
+        self.columns.split(',').map(str::trim).collect()
+    }
+}
+
+// Parents come before children so foreign keys resolve on insert.
+const TABLES: &[TableSpec] = &[
+    TableSpec {
+        name: "settings",
+        key: "key",
+        columns: Setting::COLUMNS,
+        dates: &[],
+        references: &[],
+        timestamp: None,
+    },
+    TableSpec {
+        name: "clients",
+        key: "id",
+        columns: Client::COLUMNS,
+        dates: &[
+            ("created_at", DateFormat::Timestamp),
+            ("start_date", DateFormat::Day),
+            ("end_date", DateFormat::Day),
+            ("pause_from", DateFormat::Day),
+            ("pause_to", DateFormat::Day),
+        ],
+        references: &[],
+        timestamp: Some("created_at"),
+    },
+    TableSpec {
+        name: "client_payments",
+        key: "id",
+        columns: ClientPayment::COLUMNS,
+        dates: &[("period", DateFormat::Period), ("paid_at", DateFormat::Timestamp)],
+        references: &[("client_id", "clients")],
+        timestamp: Some("paid_at"),
+    },
+    TableSpec {
+        name: "payment_transactions",
+        key: "id",
+        columns: PaymentTransaction::COLUMNS,
+        dates: &[("paid_on", DateFormat::Day), ("created_at", DateFormat::Timestamp)],
+        references: &[("payment_id", "client_payments")],
+        timestamp: Some("created_at"),
+    },
+    TableSpec {
+        name: "invoices",
+        key: "id",
+        columns: Invoice::COLUMNS,
+        dates: &[
+            ("period", DateFormat::Period),
+            ("issued_on", DateFormat::Day),
+            ("due_on", DateFormat::Day),
+            ("paid_at", DateFormat::Timestamp),
+            ("created_at", DateFormat::Timestamp),
+        ],
+        references: &[("client_id", "clients")],
+        timestamp: Some("created_at"),
+    },
+    TableSpec {
+        name: "invoice_items",
+        key: "id",
+        columns: InvoiceItem::COLUMNS,
+        dates: &[("period", DateFormat::Period)],
+        references: &[("invoice_id", "invoices")],
+        timestamp: None,
+    },
+    TableSpec {
+        name: "time_entries",
+        key: "id",
+        columns: TimeEntry::COLUMNS,
+        dates: &[
+            ("started_at", DateFormat::Timestamp),
+            ("ended_at", DateFormat::Timestamp),
+            ("created_at", DateFormat::Timestamp),
+        ],
+        references: &[("client_id", "clients"), ("invoice_id", "invoices")],
+        timestamp: Some("created_at"),
+    },
+    TableSpec {
+        name: "services",
+        key: "id",
+        columns: Service::COLUMNS,
+        dates: &[
+            ("expires_at", DateFormat::Day),
+            ("created_at", DateFormat::Timestamp),
+            ("snoozed_until", DateFormat::Day),
+            ("acknowledged_expires_at", DateFormat::Day),
+        ],
+        references: &[],
+        timestamp: Some("created_at"),
+    },
+    TableSpec {
+        name: "service_renewals",
+        key: "id",
+        columns: ServiceRenewal::COLUMNS,
+        dates: &[
+            ("previous_expires_at", DateFormat::Day),
+            ("expires_at", DateFormat::Day),
+            ("renewed_at", DateFormat::Timestamp),
+        ],
+        references: &[("service_id", "services")],
+        timestamp: Some("renewed_at"),
+    },
+    TableSpec {
+        name: "service_charges",
+        key: "id",
+        columns: ServiceCharge::COLUMNS,
+        dates: &[
+            ("charged_on", DateFormat::Day),
+            ("created_at", DateFormat::Timestamp),
+        ],
+        references: &[("service_id", "services"), ("renewal_id", "service_renewals")],
+        timestamp: Some("created_at"),
+    },
+    TableSpec {
+        name: "fx_rates",
+        key: "id",
+        columns: FxRate::COLUMNS,
+        dates: &[("date", DateFormat::Day)],
+        references: &[],
+        timestamp: None,
+    },
+    TableSpec {
+        name: "notification_rules",
+        key: "category",
+        columns: NotificationRule::COLUMNS,
+        dates: &[],
+        references: &[],
+        timestamp: None,
+    },
+    TableSpec {
+        name: "categories",
+        key: "id",
+        columns: Category::COLUMNS,
+        dates: &[],
+        references: &[],
+        timestamp: None,
+    },
+    TableSpec {
+        name: "notes",
+        key: "id",
+        columns: Note::COLUMNS,
+        dates: &[
+            ("created_at", DateFormat::Timestamp),
+            ("updated_at", DateFormat::Timestamp),
+            ("remind_at", DateFormat::Timestamp),
+        ],
+        references: &[("category_id", "categories")],
+        timestamp: Some("updated_at"),
+    },
+    TableSpec {
+        name: "chat_history",
+        key: "id",
+        columns: ChatMessage::COLUMNS,
+        dates: &[("created_at", DateFormat::Timestamp)],
+        references: &[],
+        timestamp: Some("created_at"),
+    },
+];
+
+fn to_rows<T: Serialize>(items: &[T]) -> Vec<Row> {
+    items
+        .iter()
+        .filter_map(|item| match serde_json::to_value(item) {
+            Ok(Value::Object(map)) => Some(map),
+            _ => None,
+        })
+        .collect()
+}
+
+fn payload_rows(payload: &ProfileExport, table: &str) -> Vec<Row> {
+    match table {
+        "settings" => to_rows(&payload.settings),
+        "clients" => to_rows(&payload.clients),
+        "client_payments" => to_rows(&payload.client_payments),
+        "payment_transactions" => to_rows(&payload.payment_transactions),
+        "invoices" => to_rows(&payload.invoices),
+        "invoice_items" => to_rows(&payload.invoice_items),
+        "time_entries" => to_rows(&payload.time_entries),
+        "services" => to_rows(&payload.services),
+        "service_renewals" => to_rows(&payload.service_renewals),
+        "service_charges" => to_rows(&payload.service_charges),
+        "fx_rates" => to_rows(&payload.fx_rates),
+        "notification_rules" => to_rows(&payload.notification_rules),
+        "categories" => to_rows(&payload.categories),
+        "notes" => to_rows(&payload.notes),
+        "chat_history" => to_rows(&payload.chat_history),
+        _ => Vec::new(),
+    }
+}
+
+fn to_sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+fn same_value(a: &SqlValue, b: &SqlValue) -> bool {
+    match (a, b) {
+        (SqlValue::Integer(x), SqlValue::Real(y)) | (SqlValue::Real(y), SqlValue::Integer(x)) => {
+            (*x as f64) == *y
+        }
+        _ => a == b,
+    }
+}
+
+fn row_key(spec: &TableSpec, row: &Row) -> Result<String, BlueprintError> {
+    row.get(spec.key)
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            BlueprintError::validation(i18n::trf(
+                "error.profile_row_without_key",
+                &[("table", &spec.name), ("field", &spec.key)],
+            ))
+        })
+}
+
+fn row_value(row: &Row, column: &str) -> SqlValue {
+    row.get(column).map(to_sql_value).unwrap_or(SqlValue::Null)
+}
+
+fn insert_row(conn: &Connection, spec: &TableSpec, row: &Row) -> Result<(), BlueprintError> {
+    let columns = spec.columns();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        spec.name,
+        spec.columns,
+        placeholders.join(", ")
+    );
+    let values: Vec<SqlValue> = columns.iter().map(|c| row_value(row, c)).collect();
+    conn.execute(&sql, params_from_iter(values))
+        .map_err(|e| table_error(spec.name, e))?;
+    Ok(())
+}
+
+fn update_row(
+    conn: &Connection,
+    spec: &TableSpec,
+    key: &str,
+    row: &Row,
+) -> Result<(), BlueprintError> {
+    let columns: Vec<&str> = spec
+        .columns()
+        .into_iter()
+        .filter(|c| *c != spec.key)
+        .collect();
+    let assignments: Vec<String> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("{} = ?{}", c, i + 1))
+        .collect();
+    let sql = format!(
+        "UPDATE {} SET {} WHERE {} = ?{}",
+        spec.name,
+        assignments.join(", "),
+        spec.key,
+        columns.len() + 1
+    );
+    let mut values: Vec<SqlValue> = columns.iter().map(|c| row_value(row, c)).collect();
+    values.push(SqlValue::Text(key.to_string()));
+    conn.execute(&sql, params_from_iter(values))
+        .map_err(|e| table_error(spec.name, e))?;
+    Ok(())
+}
+
+fn load_local_row(
+    conn: &Connection,
+    spec: &TableSpec,
+    key: &str,
+) -> Result<Option<HashMap<String, SqlValue>>, BlueprintError> {
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {} = ?1",
+        spec.columns, spec.name, spec.key
+    );
+    conn.query_row(&sql, [key], |r| {
+        let mut m = HashMap::new();
+        for (i, c) in spec.columns().into_iter().enumerate() {
+            m.insert(c.to_string(), r.get::<_, SqlValue>(i)?);
+        }
+        Ok(m)
+    })
+    .optional()
+    .map_err(BlueprintError::from)
+}
+
+fn row_differs(spec: &TableSpec, local: &HashMap<String, SqlValue>, row: &Row) -> bool {
+    spec.columns().into_iter().any(|c| {
+        local
+            .get(c)
+            .map(|l| !same_value(l, &row_value(row, c)))
+            .unwrap_or(true)
+    })
+}
+
+fn timestamp_text(value: Option<&SqlValue>) -> Option<String> {
+    match value {
+        Some(SqlValue::Text(s)) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn incoming_is_newer(local: Option<&str>, incoming: Option<&str>) -> bool {
+    match (local, incoming) {
+        (Some(l), Some(i)) => match (
+            DateTime::parse_from_rfc3339(l),
+            DateTime::parse_from_rfc3339(i),
+        ) {
+            (Ok(local), Ok(incoming)) => incoming > local,
+            _ => i > l,
+        },
+        (None, Some(_)) => true,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    pub table: String,
+    pub row: Option<usize>,
+    pub id: Option<String>,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.table)?;
+        if let Some(row) = self.row {
+            write!(f, "[{}]", row)?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, ".{}", field)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TableDiff {
+    pub table: String,
+    pub added: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportPreview {
+    pub version: Option<i64>,
+    pub version_supported: bool,
+    pub valid: bool,
+    pub issues: Vec<ValidationIssue>,
+    pub tables: Vec<TableDiff>,
+}
+
+fn valid_date(value: &str, format: DateFormat) -> bool {
+    match format {
+        DateFormat::Timestamp => DateTime::parse_from_rfc3339(value).is_ok(),
+        DateFormat::Day => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        DateFormat::Period => Period::parse(value).is_some(),
+    }
+}
+
+fn local_key_exists(conn: &Connection, table: &str, key: &str) -> bool {
+    conn.query_row(
+        &format!("SELECT 1 FROM {} WHERE id = ?1", table),
+        [key],
+        |_| Ok(()),
+    )
+    .is_ok()
+}
+
+fn validate_secrets(
+    conn: &Connection,
+    payload: &ProfileExport,
+    mode: ImportMode,
+    service_keys: Option<&HashSet<String>>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let mut seen = HashSet::new();
+    for (idx, secret) in payload.secrets.iter().enumerate() {
+        let mut issue = |field: &str, message: String| {
+            issues.push(ValidationIssue {
+                severity: IssueSeverity::Error,
+                table: "secrets".to_string(),
+                row: Some(idx),
+                id: Some(secret.name.clone()),
+                field: Some(field.to_string()),
+                message,
+            });
+        };
+
+        if let Err(e) = vault::check_name(&secret.name) {
+            issue("name", e.to_string());
+        }
+        if !seen.insert(secret.name.as_str()) {
+            issue("name", i18n::trf("error.profile_duplicate_key", &[("key", &secret.name)]));
+        }
+        if !valid_date(&secret.updated_at, DateFormat::Timestamp) {
+            issue("updated_at", i18n::trf("error.profile_invalid_date", &[("value", &secret.updated_at)]));
+        }
+        if let Some(service_id) = vault::service_id_of(&secret.name) {
+            let in_payload = service_keys.is_some_and(|k| k.contains(service_id));
+            let in_db = mode == ImportMode::Merge && local_key_exists(conn, "services", service_id);
+            if !in_payload && !in_db {
+                issue(
+                    "name",
+                    i18n::trf(
+                        "error.profile_missing_reference",
+                        &[("table", &"services"), ("id", &service_id)],
+                    ),
+                );
+            }
+        }
+    }
+}
+
+pub fn validate_import(
+    conn: &Connection,
+    payload: &ProfileExport,
+    mode: ImportMode,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let mut keys: HashMap<&str, HashSet<String>> = HashMap::new();
+    let rows: Vec<(&TableSpec, Vec<Row>)> = TABLES
+        .iter()
+        .map(|spec| (spec, payload_rows(payload, spec.name)))
+        .collect();
+
+    for (spec, table_rows) in &rows {
+        let mut seen = HashSet::new();
+        for (idx, row) in table_rows.iter().enumerate() {
+            let id = row.get(spec.key).and_then(|v| v.as_str()).map(str::to_string);
+            let mut issue = |field: &str, message: String| {
+                issues.push(ValidationIssue {
+                    severity: IssueSeverity::Error,
+                    table: spec.name.to_string(),
+                    row: Some(idx),
+                    id: id.clone(),
+                    field: Some(field.to_string()),
+                    message,
+                });
+            };
+
+            for (field, format) in spec.dates {
+                if let Some(v) = row.get(*field).and_then(Value::as_str) {
+                    if !valid_date(v, *format) {
+                        issue(field, i18n::trf("error.profile_invalid_date", &[("value", &v)]));
+                    }
+                }
+            }
+            if let Some(id) = &id {
+                if !seen.insert(id.clone()) {
+                    issue(spec.key, i18n::trf("error.profile_duplicate_key", &[("key", &id)]));
+                }
+            }
+        }
+        keys.insert(spec.name, seen);
+    }
+
+    for (spec, table_rows) in &rows {
+        for (idx, row) in table_rows.iter().enumerate() {
+            for (field, target) in spec.references {
+                let Some(reference) = row.get(*field).and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                let in_payload = keys.get(target).is_some_and(|k| k.contains(reference));
+                let in_db = mode == ImportMode::Merge && local_key_exists(conn, target, reference);
+                if !in_payload && !in_db {
+                    issues.push(ValidationIssue {
+                        severity: IssueSeverity::Error,
+                        table: spec.name.to_string(),
+                        row: Some(idx),
+                        id: row.get(spec.key).and_then(|v| v.as_str()).map(str::to_string),
+                        field: Some(field.to_string()),
+                        message: i18n::trf(
+                            "error.profile_missing_reference",
+                            &[("table", target), ("id", &reference)],
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    validate_secrets(conn, payload, mode, keys.get("services"), &mut issues);
+    issues
+}
+
+fn diff_secrets(
+    conn: &Connection,
+    vault: &Vault,
+    payload: &ProfileExport,
+) -> Result<TableDiff, BlueprintError> {
+    let local: HashMap<String, String> = vault
+        .all(conn)?
+        .into_iter()
+        .map(|s| (s.name, s.value))
+        .collect();
+    let mut diff = TableDiff {
+        table: "secrets".to_string(),
+        ..Default::default()
+    };
+    for secret in &payload.secrets {
+        match local.get(&secret.name) {
+            None => diff.added += 1,
+            Some(value) if *value != secret.value => diff.changed += 1,
+            Some(_) => diff.unchanged += 1,
+        }
+    }
+    diff.removed = local
+        .keys()
+        .filter(|name| !payload.secrets.iter().any(|s| &s.name == *name))
+        .count();
+    Ok(diff)
+}
+
+pub fn diff_import(
+    conn: &Connection,
+    vault: &Vault,
+    payload: &ProfileExport,
+) -> Result<Vec<TableDiff>, BlueprintError> {
+    let mut diffs = Vec::with_capacity(TABLES.len());
+    for spec in TABLES {
+        let mut diff = TableDiff {
+            table: spec.name.to_string(),
+            ..Default::default()
+        };
+        let mut incoming_keys = HashSet::new();
+        for row in payload_rows(payload, spec.name) {
+            let Some(key) = row.get(spec.key).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            incoming_keys.insert(key.to_string());
+            match load_local_row(conn, spec, key)? {
+                None => diff.added += 1,
+                Some(local) if row_differs(spec, &local, &row) => diff.changed += 1,
+                Some(_) => diff.unchanged += 1,
+            }
+        }
+
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", spec.key, spec.name))?;
+        diff.removed = stmt
+            .query_map([], |r| r.get::<_, String>(0))?
+            .filter_map(|r| r.ok())
+            .filter(|k| !incoming_keys.contains(k))
+            .count();
+        diffs.push(diff);
+    }
+    diffs.push(diff_secrets(conn, vault, payload)?);
+    Ok(diffs)
+}
+
+// An export made without secrets must not wipe the ones stored locally, so
+// the stripped fields are filled back in from the database before comparing.
+fn with_local_secrets(
+    conn: &Connection,
+    vault: &Vault,
+    payload: &ProfileExport,
+) -> Result<ProfileExport, BlueprintError> {
+    let mut filled = payload.clone();
+    for secret in vault.all(conn)? {
+        let present = filled.secrets.iter().any(|s| s.name == secret.name);
+        let service_kept = vault::service_id_of(&secret.name)
+            .is_none_or(|id| filled.services.iter().any(|s| s.id == id));
+        if !present && service_kept {
+            filled.secrets.push(secret);
+        }
+    }
+    for service in filled.services.iter_mut().filter(|s| s.login.is_none()) {
+        service.login = conn
+            .query_row(
+                "SELECT login FROM services WHERE id = ?1",
+                [&service.id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+    }
+    filled.secrets_included = true;
+    Ok(filled)
+}
+
+pub fn build_preview(
+    conn: &Connection,
+    vault: &Vault,
+    payload: Value,
+    options: &ImportOptions,
+) -> Result<ImportPreview, BlueprintError> {
+    let mut version = payload.get("version").and_then(Value::as_i64);
+    let parsed = decrypt_payload(payload, options.passphrase.as_deref()).and_then(|value| {
+        version = value.get("version").and_then(Value::as_i64);
+        parse_profile(value)
+    });
+    let version_supported = version.is_some_and(version_supported);
+    let profile = match parsed {
+        Ok(profile) if profile.secrets_included => profile,
+        Ok(profile) => with_local_secrets(conn, vault, &profile)?,
+        Err(issue) => {
+            return Ok(ImportPreview {
+                version,
+                version_supported,
+                valid: false,
+                issues: vec![issue],
+                tables: Vec::new(),
+            })
+        }
+    };
+
+    let issues = validate_import(conn, &profile, options.mode);
+    let tables = diff_import(conn, vault, &profile)?;
+    Ok(ImportPreview {
+        version,
+        version_supported,
+        valid: !issues.iter().any(|i| i.severity == IssueSeverity::Error),
+        issues,
+        tables,
+    })
+}
+
+#[tauri::command]
+pub fn preview_import(
+    payload: Value,
+    options: Option<ImportOptions>,
+    state: State<DbState>,
+    vault: State<VaultState>,
+) -> Result<ImportPreview, BlueprintError> {
+    let conn = state.conn()?;
+    let vault = vault.vault(&conn)?;
+    build_preview(&conn, &vault, payload, &options.unwrap_or_default())
+}
+
+fn merge_table(
+    tx: &Transaction,
+    spec: &TableSpec,
+    rows: &[Row],
+    strategy: ConflictStrategy,
+    conflicts: &mut Vec<ImportConflict>,
+) -> Result<TableImportReport, BlueprintError> {
+    let mut report = TableImportReport {
+        table: spec.name.to_string(),
+        ..Default::default()
+    };
+
+    for row in rows {
+        let key = row_key(spec, row)?;
+        let Some(local) = load_local_row(tx, spec, &key)? else {
+            insert_row(tx, spec, row)?;
+            report.added += 1;
+            continue;
+        };
+
+        if !row_differs(spec, &local, row) {
+            report.skipped += 1;
+            continue;
+        }
+
+        let local_ts = spec.timestamp.and_then(|c| timestamp_text(local.get(c)));
+        let incoming_ts = spec
+            .timestamp
+            .and_then(|c| row.get(c))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let take_incoming = match strategy {
+            ConflictStrategy::Local => false,
+            ConflictStrategy::Incoming => true,
+            ConflictStrategy::Newest => {
+                incoming_is_newer(local_ts.as_deref(), incoming_ts.as_deref())
+            }
+        };
+
+        report.conflicts += 1;
+        if take_incoming {
+            update_row(tx, spec, &key, row)?;
+            report.updated += 1;
+        } else {
+            report.skipped += 1;
+        }
+        conflicts.push(ImportConflict {
+            table: spec.name.to_string(),
+            id: key,
+            resolution: if take_incoming {
+                ConflictResolution::KeptIncoming
+            } else {
+                ConflictResolution::KeptLocal
+            },
+            local_timestamp: local_ts,
+            incoming_timestamp: incoming_ts,
+        });
+    }
+
+    Ok(report)
+}
+
+fn import_secrets(
+    tx: &Transaction,
+    vault: &Vault,
+    secrets: &[SecretValue],
+    strategy: ConflictStrategy,
+    conflicts: &mut Vec<ImportConflict>,
+) -> Result<TableImportReport, BlueprintError> {
+    let mut report = TableImportReport {
+        table: "secrets".to_string(),
+        ..Default::default()
+    };
+
+    for secret in secrets {
+        let local = vault.get_entry(tx, &secret.name)?;
+        let take_incoming = match local {
+            None => {
+                report.added += 1;
+                true
+            }
+            Some(local) if local.value == secret.value => {
+                report.skipped += 1;
+                false
+            }
+            Some(local) => {
+                let take = match strategy {
+                    ConflictStrategy::Local => false,
+                    ConflictStrategy::Incoming => true,
+                    ConflictStrategy::Newest => {
+                        incoming_is_newer(Some(&local.updated_at), Some(&secret.updated_at))
+                    }
+                };
+                report.conflicts += 1;
+                if take {
+                    report.updated += 1;
+                } else {
+                    report.skipped += 1;
+                }
+                conflicts.push(ImportConflict {
+                    table: "secrets".to_string(),
+                    id: secret.name.clone(),
+                    resolution: if take {
+                        ConflictResolution::KeptIncoming
+                    } else {
+                        ConflictResolution::KeptLocal
+                    },
+                    local_timestamp: Some(local.updated_at),
+                    incoming_timestamp: Some(secret.updated_at.clone()),
+                });
+                take
+            }
+        };
+        if take_incoming {
+            vault.set_at(tx, &secret.name, &secret.value, &secret.updated_at)?;
+        }
+    }
+
+    Ok(report)
+}
+
+fn replace_table(
+    tx: &Transaction,
+    spec: &TableSpec,
+    rows: &[Row],
+) -> Result<TableImportReport, BlueprintError> {
+    for row in rows {
+        row_key(spec, row)?;
+        insert_row(tx, spec, row)?;
+    }
+    Ok(TableImportReport {
+        table: spec.name.to_string(),
+        added: rows.len(),
+        ..Default::default()
+    })
+}
+
+#[tauri::command]
+pub fn import_profile(
+    payload: Value,
+    options: Option<ImportOptions>,
+    state: State<DbState>,
+    vault: State<VaultState>,
+    app: AppHandle,
+) -> Result<ImportReport, BlueprintError> {
+    let options = options.unwrap_or_default();
+    let profile = decrypt_payload(payload, options.passphrase.as_deref())
+        .and_then(parse_profile)?;
+    let mut conn = state.conn()?;
+    let vault = vault.vault(&conn)?;
+    let report = apply_import(&mut conn, &vault, &profile, options)?;
+    i18n::reload(&app, &conn);
+    Ok(report)
+}
+
+pub fn apply_import(
+    conn: &mut Connection,
+    vault: &Vault,
+    payload: &ProfileExport,
+    options: ImportOptions,
+) -> Result<ImportReport, BlueprintError> {
+    let filled;
+    let payload = if payload.secrets_included {
+        payload
+    } else {
+        filled = with_local_secrets(conn, vault, payload)?;
+        &filled
+    };
+
+    let errors: Vec<ValidationIssue> = validate_import(conn, payload, options.mode)
+        .into_iter()
+        .filter(|i| i.severity == IssueSeverity::Error)
+        .collect();
+    if !errors.is_empty() {
+        let shown: Vec<String> = errors.iter().take(5).map(|i| i.to_string()).collect();
+        let mut message = i18n::trf("error.profile_has_errors", &[("errors", &shown.join("; "))]);
+        if errors.len() > shown.len() {
+            message.push_str(&i18n::trf(
+                "error.profile_more_errors",
+                &[("count", &(errors.len() - shown.len()))],
+            ));
+        }
+        return Err(BlueprintError::Validation {
+            message,
+            details: serde_json::to_value(&errors).ok(),
+        });
+    }
+
+    let tx = conn.transaction()?;
+
+    if options.mode == ImportMode::Replace {
+        tx.execute("DELETE FROM secrets", [])?;
+        for spec in TABLES.iter().rev() {
+            tx.execute(&format!("DELETE FROM {}", spec.name), [])?;
+        }
+    }
+
+    let mut tables = Vec::with_capacity(TABLES.len());
+    let mut conflicts = Vec::new();
+    for spec in TABLES {
+        let rows = payload_rows(payload, spec.name);
+        let report = match options.mode {
+            ImportMode::Replace => replace_table(&tx, spec, &rows)?,
+            ImportMode::Merge => merge_table(&tx, spec, &rows, options.strategy, &mut conflicts)?,
+        };
+        tables.push(report);
+    }
+    tables.push(import_secrets(
+        &tx,
+        vault,
+        &payload.secrets,
+        options.strategy,
+        &mut conflicts,
+    )?);
+
+    tx.commit()?;
+    Ok(ImportReport {
+        mode: options.mode,
+        tables,
+        conflicts,
+    })
+}
@@ -0,0 +1,316 @@
+use crate::commands::clients::{self, Client};
+use crate::commands::time_entries::{self, Rounding};
+use crate::dates::parse_day;
+use crate::db::DbState;
+use crate::error::BlueprintError;
+use crate::i18n::trf;
+use crate::scheduler::due_date;
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, Weekday};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::State;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodKind {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+// A billing period, keyed as YYYY-MM-DD, YYYY-Www (ISO week), YYYY-MM,
+// YYYY-Qn or YYYY. `start` is always the first day of the period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub kind: PeriodKind,
+    pub start: NaiveDate,
+}
+
+impl Period {
+    pub fn containing(kind: PeriodKind, date: NaiveDate) -> Period {
+        let start = match kind {
+            PeriodKind::Day => Some(date),
+            PeriodKind::Week => date.checked_sub_days(Days::new(date.weekday().num_days_from_monday().into())),
+            PeriodKind::Month => date.with_day(1),
+            PeriodKind::Quarter => NaiveDate::from_ymd_opt(date.year(), (date.month0() / 3) * 3 + 1, 1),
+            PeriodKind::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1),
+        };
+        Period {
+            kind,
+            start: start.unwrap_or(date),
+        }
+    }
+
+    pub fn parse(key: &str) -> Option<Period> {
+        let (kind, start) = if let Some((year, week)) = key.split_once("-W") {
+            let start = NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Mon)?;
+            (PeriodKind::Week, start)
+        } else if let Some((year, quarter)) = key.split_once("-Q") {
+            let quarter: u32 = quarter.parse().ok().filter(|q| (1..=4).contains(q))?;
+            (PeriodKind::Quarter, NaiveDate::from_ymd_opt(year.parse().ok()?, quarter * 3 - 2, 1)?)
+        } else if key.len() == 4 && key.bytes().all(|b| b.is_ascii_digit()) {
+            (PeriodKind::Year, NaiveDate::from_ymd_opt(key.parse().ok()?, 1, 1)?)
+        } else if let Ok(day) = NaiveDate::parse_from_str(key, "%Y-%m-%d") {
+            (PeriodKind::Day, day)
+        } else {
+            (PeriodKind::Month, NaiveDate::parse_from_str(&format!("{}-01", key), "%Y-%m-%d").ok()?)
+        };
+        // Round-tripping rejects keys chrono is lenient about, like "2026-3".
+        let period = Period { kind, start };
+        (period.key() == key).then_some(period)
+    }
+
+    pub fn key(&self) -> String {
+        match self.kind {
+            PeriodKind::Day => self.start.format("%Y-%m-%d").to_string(),
+            PeriodKind::Week => {
+                let week = self.start.iso_week();
+                format!("{:04}-W{:02}", week.year(), week.week())
+            }
+            PeriodKind::Month => self.start.format("%Y-%m").to_string(),
+            PeriodKind::Quarter => format!("{:04}-Q{}", self.start.year(), self.start.month0() / 3 + 1),
+            PeriodKind::Year => format!("{:04}", self.start.year()),
+        }
+    }
+
+    fn shift(&self, forward: bool) -> Option<Period> {
+        let start = self.start;
+        let start = match (self.kind, forward) {
+            (PeriodKind::Day, true) => start.checked_add_days(Days::new(1)),
+            (PeriodKind::Day, false) => start.checked_sub_days(Days::new(1)),
+            (PeriodKind::Week, true) => start.checked_add_days(Days::new(7)),
+            (PeriodKind::Week, false) => start.checked_sub_days(Days::new(7)),
+            (kind, forward) => {
+                let months = Months::new(match kind {
+                    PeriodKind::Quarter => 3,
+                    PeriodKind::Year => 12,
+                    _ => 1,
+                });
+                if forward {
+                    start.checked_add_months(months)
+                } else {
+                    start.checked_sub_months(months)
+                }
+            }
+        }?;
+        Some(Period { kind: self.kind, start })
+    }
+
+    pub fn next(&self) -> Option<Period> {
+        self.shift(true)
+    }
+
+    pub fn prev(&self) -> Option<Period> {
+        self.shift(false)
+    }
+
+    pub fn end(&self) -> NaiveDate {
+        self.next()
+            .and_then(|next| next.start.pred_opt())
+            .unwrap_or(self.start)
+    }
+}
+
+// When a client is expected to pay: every period of their billing model from
+// `start_date` (or the day they were added) through `end_date`, minus the
+// pause. A pause without an end lasts until it is lifted. `payment_day` is a
+// weekday (1 = Monday) for weekly clients and a day of the period's first
+// month otherwise; without it the period's last day is the deadline.
+#[derive(Debug, Clone)]
+pub struct BillingTerms {
+    kind: Option<PeriodKind>,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    pause: Option<(NaiveDate, Option<NaiveDate>)>,
+    payment_day: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedPeriod {
+    pub period: String,
+    pub start: NaiveDate,
+    pub due: NaiveDate,
+}
+
+impl BillingTerms {
+    pub fn of(client: &Client) -> Self {
+        let day = |value: &Option<String>| value.as_deref().and_then(|v| parse_day(v).ok());
+        let created = DateTime::parse_from_rfc3339(&client.created_at)
+            .map(|t| t.date_naive())
+            .ok();
+        BillingTerms {
+            kind: client.payment_type.period_kind(),
+            start: day(&client.start_date).or(created),
+            end: day(&client.end_date),
+            pause: day(&client.pause_from).map(|from| (from, day(&client.pause_to))),
+            payment_day: client.payment_day.and_then(|d| u32::try_from(d).ok()).filter(|d| *d > 0),
+        }
+    }
+
+    pub fn kind(&self) -> Option<PeriodKind> {
+        self.kind
+    }
+
+    pub fn expects(&self, due: NaiveDate) -> bool {
+        let paused = self
+            .pause
+            .is_some_and(|(from, to)| due >= from && to.is_none_or(|to| due <= to));
+        !paused && self.start.is_none_or(|start| due >= start) && self.end.is_none_or(|end| due <= end)
+    }
+
+    pub fn due(&self, period: &Period) -> Option<NaiveDate> {
+        match (period.kind, self.payment_day) {
+            (PeriodKind::Day, _) | (_, None) => Some(period.end()),
+            (PeriodKind::Week, Some(day)) => period.start.checked_add_days(Days::new((day.min(7) - 1).into())),
+            (_, Some(day)) => due_date(period.start.year(), period.start.month(), day),
+        }
+    }
+
+    // Periods of the client's model that overlap `from`..=`to`; none for
+    // one-time clients.
+    pub fn expected_periods(&self, from: NaiveDate, to: NaiveDate) -> Vec<ExpectedPeriod> {
+        let Some(kind) = self.kind else {
+            return Vec::new();
+        };
+        std::iter::successors(Some(Period::containing(kind, from)), Period::next)
+            .take_while(|p| p.start <= to)
+            .filter_map(|p| {
+                let due = self.due(&p)?;
+                self.expects(due).then(|| ExpectedPeriod {
+                    period: p.key(),
+                    start: p.start,
+                    due,
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn months(from: NaiveDate, to: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    std::iter::successors(Some(Period::containing(PeriodKind::Month, from)), Period::next)
+        .take_while(move |m| m.start <= to)
+        .map(|m| m.start)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodStatus {
+    Paid,
+    // Running now and not yet past its due date.
+    Unpaid,
+    Upcoming,
+    Overdue,
+}
+
+// `scheduled` is false for payments recorded outside the expected periods,
+// say during a pause or before the start date.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleEntry {
+    pub period: String,
+    pub due_date: String,
+    pub status: PeriodStatus,
+    pub scheduled: bool,
+    pub payment_id: Option<String>,
+    pub currency: String,
+    pub expected: Option<f64>,
+    pub received: f64,
+    pub outstanding: Option<f64>,
+}
+
+fn status(paid: bool, starts: NaiveDate, due: NaiveDate, today: NaiveDate) -> PeriodStatus {
+    if paid {
+        PeriodStatus::Paid
+    } else if due < today {
+        PeriodStatus::Overdue
+    } else if starts > today {
+        PeriodStatus::Upcoming
+    } else {
+        PeriodStatus::Unpaid
+    }
+}
+
+// Expected periods merged with what was recorded, oldest first. One-time
+// clients have no schedule of their own, so only their recorded payment
+// dates show up. Hourly and retainer clients are expected to pay for the
+// time tracked in each period.
+pub fn client_schedule(
+    conn: &Connection,
+    client_id: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    today: NaiveDate,
+) -> Result<Vec<ScheduleEntry>, BlueprintError> {
+    if from > to {
+        return Err(BlueprintError::validation(trf(
+            "error.invalid_range",
+            &[("from", &from), ("to", &to)],
+        )));
+    }
+    let client = conn.query_row(
+        &format!("SELECT {} FROM clients WHERE id = ?1", Client::COLUMNS),
+        [client_id],
+        Client::from_row,
+    )?;
+    let terms = BillingTerms::of(&client);
+    let hours = time_entries::billable_hours(conn, &client, Rounding::load(conn))?;
+    let recurring = terms.kind().is_some();
+    let mut recorded: HashMap<String, clients::PaymentPeriod> = clients::payment_periods(conn, client_id)?
+        .into_iter()
+        .map(|p| (p.payment.period.clone(), p))
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut push = |period: String, starts: NaiveDate, due: NaiveDate, scheduled: bool, recorded: Option<clients::PaymentPeriod>| {
+        let expected = time_entries::expected_amount(&client, hours.get(&period));
+        let (paid, payment_id, received, outstanding) = match recorded {
+            Some(p) => (p.payment.paid, Some(p.payment.id), p.received, p.outstanding),
+            None => (false, None, 0.0, expected),
+        };
+        entries.push(ScheduleEntry {
+            status: status(paid, starts, due, today),
+            due_date: due.format("%Y-%m-%d").to_string(),
+            period,
+            scheduled,
+            payment_id,
+            currency: client.currency.clone(),
+            expected,
+            received,
+            outstanding,
+        });
+    };
+
+    for expected in terms.expected_periods(from, to) {
+        let payment = recorded.remove(&expected.period);
+        push(expected.period, expected.start, expected.due, true, payment);
+    }
+    // Recorded periods may use another model's keys if the client switched.
+    for (key, payment) in recorded {
+        let Some(period) = Period::parse(&key) else {
+            continue;
+        };
+        let Some(due) = terms.due(&period) else {
+            continue;
+        };
+        if period.start > to || period.end() < from {
+            continue;
+        }
+        push(key, period.start, due, !recurring, Some(payment));
+    }
+    entries.sort_by(|a, b| a.due_date.cmp(&b.due_date).then_with(|| a.period.cmp(&b.period)));
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn get_client_schedule(
+    client_id: String,
+    from: String,
+    to: String,
+    state: State<DbState>,
+) -> Result<Vec<ScheduleEntry>, BlueprintError> {
+    let from = parse_day(&from)?;
+    let to = parse_day(&to)?;
+    let conn = state.conn()?;
+    client_schedule(&conn, &client_id, from, to, Local::now().date_naive())
+}
@@ -1,11 +1,19 @@
+use crate::commands::charges::{self, ServiceCharge};
+use crate::commands::fx::normalize_currency;
 use crate::db::DbState;
-use chrono::{Duration, Utc};
-use rusqlite::params;
+use crate::error::BlueprintError;
+use crate::i18n::{tr, trf};
+use crate::scheduler;
+use anyhow::bail;
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Service {
     pub id: String,
     pub project_name: String,
@@ -19,6 +27,127 @@ pub struct Service {
     pub category: Option<String>,
     pub notify_days: i32,
     pub created_at: String,
+    // Reminders stay silent before this day.
+    pub snoozed_until: Option<String>,
+    // Reminders stay silent while `expires_at` still equals this, i.e. until
+    // the service is renewed.
+    pub acknowledged_expires_at: Option<String>,
+    // Comma-separated days before expiry, e.g. "30,7,1,0"; overrides the
+    // category and global rules.
+    pub notify_thresholds: Option<String>,
+    // monthly, quarterly, yearly or custom (every `billing_cycle_days`).
+    pub billing_cycle: Option<String>,
+    pub billing_cycle_days: Option<i32>,
+    pub auto_renew: bool,
+}
+
+impl Service {
+    pub const COLUMNS: &'static str = "id, project_name, service_name, login, url, expires_at, \
+         cost, currency, notes, category, notify_days, created_at, snoozed_until, \
+         acknowledged_expires_at, notify_thresholds, billing_cycle, billing_cycle_days, auto_renew";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Service {
+            id: row.get(0)?,
+            project_name: row.get(1)?,
+            service_name: row.get(2)?,
+            login: row.get(3)?,
+            url: row.get(4)?,
+            expires_at: row.get(5)?,
+            cost: row.get(6)?,
+            currency: row.get(7)?,
+            notes: row.get(8)?,
+            category: row.get(9)?,
+            notify_days: row.get(10)?,
+            created_at: row.get(11)?,
+            snoozed_until: row.get(12)?,
+            acknowledged_expires_at: row.get(13)?,
+            notify_thresholds: row.get(14)?,
+            billing_cycle: row.get(15)?,
+            billing_cycle_days: row.get(16)?,
+            auto_renew: row.get::<_, i32>(17)? != 0,
+        })
+    }
+
+    pub fn cycle(&self) -> Option<BillingCycle> {
+        BillingCycle::from_columns(self.billing_cycle.as_deref(), self.billing_cycle_days)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BillingCycle {
+    Monthly,
+    Quarterly,
+    Yearly,
+    Days(u32),
+}
+
+impl BillingCycle {
+    pub fn from_columns(cycle: Option<&str>, days: Option<i32>) -> Option<BillingCycle> {
+        match cycle? {
+            "monthly" => Some(BillingCycle::Monthly),
+            "quarterly" => Some(BillingCycle::Quarterly),
+            "yearly" => Some(BillingCycle::Yearly),
+            "custom" => days
+                .and_then(|d| u32::try_from(d).ok())
+                .filter(|d| *d > 0)
+                .map(BillingCycle::Days),
+            _ => None,
+        }
+    }
+
+    // The first renewal after `date`. Calendar cycles count whole steps from
+    // `anchor` (where the run of renewals began) and clamp to the month's
+    // last day, so a service due Jan 31 renews on Feb 28 and then Mar 31.
+    pub fn advance(self, anchor: NaiveDate, date: NaiveDate) -> Option<NaiveDate> {
+        let step = match self {
+            BillingCycle::Monthly => 1,
+            BillingCycle::Quarterly => 3,
+            BillingCycle::Yearly => 12,
+            BillingCycle::Days(days) => return date.checked_add_days(chrono::Days::new(days.into())),
+        };
+        let anchor = anchor.min(date);
+        let elapsed = (date.year() - anchor.year()) * 12 + date.month() as i32 - anchor.month() as i32;
+        let mut steps = u32::try_from(elapsed).ok()? / step;
+        loop {
+            let next = anchor.checked_add_months(Months::new(steps.checked_mul(step)?))?;
+            if next > date {
+                return Some(next);
+            }
+            steps += 1;
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceRenewal {
+    pub id: String,
+    pub service_id: String,
+    pub previous_expires_at: String,
+    pub expires_at: String,
+    pub cost: Option<f64>,
+    pub currency: String,
+    pub auto_renewed: bool,
+    pub renewed_at: String,
+}
+
+impl ServiceRenewal {
+    pub const COLUMNS: &'static str =
+        "id, service_id, previous_expires_at, expires_at, cost, currency, auto_renewed, renewed_at";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(ServiceRenewal {
+            id: row.get(0)?,
+            service_id: row.get(1)?,
+            previous_expires_at: row.get(2)?,
+            expires_at: row.get(3)?,
+            cost: row.get(4)?,
+            currency: row.get(5)?,
+            auto_renewed: row.get::<_, i32>(6)? != 0,
+            renewed_at: row.get(7)?,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,6 +162,10 @@ pub struct CreateServiceInput {
     pub notes: Option<String>,
     pub category: Option<String>,
     pub notify_days: Option<i32>,
+    pub notify_thresholds: Option<String>,
+    pub billing_cycle: Option<String>,
+    pub billing_cycle_days: Option<i32>,
+    pub auto_renew: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,35 +181,52 @@ pub struct UpdateServiceInput {
     pub notes: Option<String>,
     pub category: Option<String>,
     pub notify_days: Option<i32>,
+    pub notify_thresholds: Option<String>,
+    pub billing_cycle: Option<String>,
+    pub billing_cycle_days: Option<i32>,
+    pub auto_renew: Option<bool>,
+}
+
+// Only custom cycles keep a day count; auto-renew needs a cycle to roll by.
+fn normalize_cycle(
+    cycle: Option<String>,
+    days: Option<i32>,
+    auto_renew: Option<bool>,
+) -> Result<(Option<String>, Option<i32>, bool), BlueprintError> {
+    let cycle = cycle.filter(|c| !c.is_empty());
+    let days = days.filter(|_| cycle.as_deref() == Some("custom"));
+    if cycle.is_some() && BillingCycle::from_columns(cycle.as_deref(), days).is_none() {
+        return Err(BlueprintError::validation(trf(
+            "error.invalid_billing_cycle",
+            &[("cycle", &cycle.unwrap_or_default())],
+        )));
+    }
+    let auto_renew = auto_renew.unwrap_or(false) && cycle.is_some();
+    Ok((cycle, days, auto_renew))
+}
+
+// Empty means "no override"; anything else must parse.
+pub fn normalize_thresholds(value: Option<&str>) -> Result<Option<String>, BlueprintError> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        None => Ok(None),
+        Some(v) => scheduler::parse_thresholds(v)
+            .map(|t| Some(scheduler::format_thresholds(&t)))
+            .ok_or_else(|| {
+                BlueprintError::validation(trf("error.invalid_thresholds", &[("value", &v)]))
+            }),
+    }
 }
 
 #[tauri::command]
-pub fn get_services(state: State<DbState>) -> Result<Vec<Service>, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+pub fn get_services(state: State<DbState>) -> Result<Vec<Service>, BlueprintError> {
+    let conn = state.conn()?;
     let mut stmt = conn
-        .prepare(
-            "SELECT id, project_name, service_name, login, url, expires_at, cost, currency, \
-             notes, category, notify_days, created_at FROM services ORDER BY expires_at ASC",
-        )
-        .map_err(|e| e.to_string())?;
+        .prepare(&format!(
+            "SELECT {} FROM services ORDER BY expires_at ASC",
+            Service::COLUMNS
+        ))?;
     let services = stmt
-        .query_map([], |row| {
-            Ok(Service {
-                id: row.get(0)?,
-                project_name: row.get(1)?,
-                service_name: row.get(2)?,
-                login: row.get(3)?,
-                url: row.get(4)?,
-                expires_at: row.get(5)?,
-                cost: row.get(6)?,
-                currency: row.get(7)?,
-                notes: row.get(8)?,
-                category: row.get(9)?,
-                notify_days: row.get(10)?,
-                created_at: row.get(11)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
+        .query_map([], Service::from_row)?
         .filter_map(|r| r.ok())
         .collect();
     Ok(services)
@@ -86,27 +236,31 @@ pub fn get_services(state: State<DbState>) -> Result<Vec<Service>, String> {
 pub fn create_service(
     input: CreateServiceInput,
     state: State<DbState>,
-) -> Result<Service, String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
+) -> Result<Service, BlueprintError> {
+    let conn = state.conn()?;
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
-    let currency = input.currency.clone().unwrap_or_else(|| "USD".to_string());
+    let currency = normalize_currency(input.currency.as_deref().unwrap_or("USD"))?;
     let notify_days = input.notify_days.unwrap_or(7);
+    let notify_thresholds = normalize_thresholds(input.notify_thresholds.as_deref())?;
+    let (billing_cycle, billing_cycle_days, auto_renew) =
+        normalize_cycle(input.billing_cycle, input.billing_cycle_days, input.auto_renew)?;
     let expires_at = input.expires_at.clone().unwrap_or_else(|| {
         (Utc::now() + Duration::days(365)).format("%Y-%m-%d").to_string()
     });
 
     conn.execute(
         "INSERT INTO services (id, project_name, service_name, login, url, expires_at, cost, \
-         currency, notes, category, notify_days, created_at) VALUES \
-         (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+         currency, notes, category, notify_days, created_at, notify_thresholds, billing_cycle, \
+         billing_cycle_days, auto_renew) VALUES \
+         (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
         params![
             id, input.project_name, input.service_name, input.login, input.url,
             expires_at, input.cost, currency, input.notes, input.category,
-            notify_days, now
+            notify_days, now, notify_thresholds, billing_cycle, billing_cycle_days,
+            auto_renew as i32
         ],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
 
     Ok(Service {
         id,
@@ -121,31 +275,247 @@ pub fn create_service(
         category: input.category,
         notify_days,
         created_at: now,
+        snoozed_until: None,
+        acknowledged_expires_at: None,
+        notify_thresholds,
+        billing_cycle,
+        billing_cycle_days,
+        auto_renew,
     })
 }
 
 #[tauri::command]
-pub fn update_service(input: UpdateServiceInput, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
-    let currency = input.currency.unwrap_or_else(|| "USD".to_string());
+pub fn update_service(input: UpdateServiceInput, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    let currency = normalize_currency(input.currency.as_deref().unwrap_or("USD"))?;
     let notify_days = input.notify_days.unwrap_or(7);
+    let notify_thresholds = normalize_thresholds(input.notify_thresholds.as_deref())?;
+    let (billing_cycle, billing_cycle_days, auto_renew) =
+        normalize_cycle(input.billing_cycle, input.billing_cycle_days, input.auto_renew)?;
     conn.execute(
         "UPDATE services SET project_name=?1, service_name=?2, login=?3, url=?4, \
-         expires_at=?5, cost=?6, currency=?7, notes=?8, category=?9, notify_days=?10 WHERE id=?11",
+         expires_at=?5, cost=?6, currency=?7, notes=?8, category=?9, notify_days=?10, \
+         notify_thresholds=?11, billing_cycle=?12, billing_cycle_days=?13, auto_renew=?14 \
+         WHERE id=?15",
         params![
             input.project_name, input.service_name, input.login, input.url,
             input.expires_at, input.cost, currency, input.notes, input.category,
-            notify_days, input.id
+            notify_days, notify_thresholds, billing_cycle, billing_cycle_days,
+            auto_renew as i32, input.id
         ],
-    )
-    .map_err(|e| e.to_string())?;
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_service(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    conn.execute("DELETE FROM services WHERE id = ?1", [&id])?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn snooze_service(
+    id: String,
+    until: Option<String>,
+    days: Option<i64>,
+    state: State<DbState>,
+) -> Result<String, BlueprintError> {
+    let until = match (until, days) {
+        (Some(until), _) => NaiveDate::parse_from_str(&until, "%Y-%m-%d")
+            .map_err(|_| BlueprintError::validation(trf("error.invalid_date", &[("date", &until)])))?,
+        (None, Some(days)) if days > 0 => Local::now().date_naive() + Duration::days(days),
+        _ => return Err(BlueprintError::validation(tr("error.snooze_needs_date"))),
+    };
+    let until = until.format("%Y-%m-%d").to_string();
+    let conn = state.conn()?;
+    let updated = conn.execute(
+        "UPDATE services SET snoozed_until = ?1 WHERE id = ?2",
+        params![until, id],
+    )?;
+    if updated == 0 {
+        return Err(rusqlite::Error::QueryReturnedNoRows.into());
+    }
+    Ok(until)
+}
+
+#[tauri::command]
+pub fn acknowledge_service(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    let updated = conn.execute(
+        "UPDATE services SET acknowledged_expires_at = expires_at WHERE id = ?1",
+        [&id],
+    )?;
+    if updated == 0 {
+        return Err(rusqlite::Error::QueryReturnedNoRows.into());
+    }
     Ok(())
 }
 
 #[tauri::command]
-pub fn delete_service(id: String, state: State<DbState>) -> Result<(), String> {
-    let conn = state.0.lock().map_err(|e| e.to_string())?;
-    conn.execute("DELETE FROM services WHERE id = ?1", [&id])
-        .map_err(|e| e.to_string())?;
+pub fn clear_service_snooze(id: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    conn.execute(
+        "UPDATE services SET snoozed_until = NULL, acknowledged_expires_at = NULL WHERE id = ?1",
+        [&id],
+    )?;
     Ok(())
 }
+
+fn load_service(conn: &Connection, id: &str) -> rusqlite::Result<Service> {
+    conn.query_row(
+        &format!("SELECT {} FROM services WHERE id = ?1", Service::COLUMNS),
+        [id],
+        Service::from_row,
+    )
+}
+
+// Where the current run of renewals began: follows the history back from
+// `expires_at` while each renewal picks up where the previous one ended. A
+// hand-edited expiry date starts a new run.
+pub fn renewal_anchor(conn: &Connection, service_id: &str, expires_at: NaiveDate) -> rusqlite::Result<NaiveDate> {
+    let mut stmt = conn.prepare(
+        "SELECT previous_expires_at FROM service_renewals
+         WHERE service_id = ?1 AND expires_at = ?2
+         ORDER BY renewed_at DESC LIMIT 1",
+    )?;
+    let mut anchor = expires_at;
+    loop {
+        let previous: Option<String> = stmt
+            .query_row(params![service_id, anchor.format("%Y-%m-%d").to_string()], |row| row.get(0))
+            .optional()?;
+        match previous.and_then(|p| NaiveDate::parse_from_str(&p, "%Y-%m-%d").ok()) {
+            Some(previous) if previous < anchor => anchor = previous,
+            _ => return Ok(anchor),
+        }
+    }
+}
+
+// Moves `expires_at` one cycle forward and records the step together with the
+// cost at that moment, which is also logged as a charge on the day the old
+// period ended. A running snooze ends with the period it was for.
+pub fn renew(conn: &mut Connection, id: &str, auto: bool) -> anyhow::Result<ServiceRenewal> {
+    let service = load_service(conn, id)?;
+    let Some(cycle) = service.cycle() else {
+        bail!(BlueprintError::validation(trf(
+            "error.no_billing_cycle",
+            &[("name", &service.service_name)],
+        )));
+    };
+    let previous = NaiveDate::parse_from_str(&service.expires_at, "%Y-%m-%d").map_err(|_| {
+        BlueprintError::validation(trf("error.invalid_date", &[("date", &service.expires_at)]))
+    })?;
+    let anchor = renewal_anchor(conn, id, previous)?;
+    let Some(next) = cycle.advance(anchor, previous) else {
+        bail!(BlueprintError::validation(trf(
+            "error.invalid_date",
+            &[("date", &service.expires_at)],
+        )));
+    };
+
+    let renewal = ServiceRenewal {
+        id: Uuid::new_v4().to_string(),
+        service_id: service.id,
+        previous_expires_at: service.expires_at,
+        expires_at: next.format("%Y-%m-%d").to_string(),
+        cost: service.cost,
+        currency: service.currency,
+        auto_renewed: auto,
+        renewed_at: Utc::now().to_rfc3339(),
+    };
+    let tx = conn.transaction()?;
+    tx.execute(
+        "UPDATE services SET expires_at = ?1, snoozed_until = NULL WHERE id = ?2",
+        params![renewal.expires_at, renewal.service_id],
+    )?;
+    tx.execute(
+        &format!(
+            "INSERT INTO service_renewals ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            ServiceRenewal::COLUMNS
+        ),
+        params![
+            renewal.id,
+            renewal.service_id,
+            renewal.previous_expires_at,
+            renewal.expires_at,
+            renewal.cost,
+            renewal.currency,
+            renewal.auto_renewed as i32,
+            renewal.renewed_at
+        ],
+    )?;
+    if let Some(cost) = renewal.cost {
+        charges::insert_charge(
+            &tx,
+            &ServiceCharge {
+                id: Uuid::new_v4().to_string(),
+                service_id: renewal.service_id.clone(),
+                charged_on: renewal.previous_expires_at.clone(),
+                amount: cost,
+                currency: renewal.currency.clone(),
+                note: None,
+                renewal_id: Some(renewal.id.clone()),
+                created_at: renewal.renewed_at.clone(),
+            },
+        )?;
+    }
+    tx.commit()?;
+    Ok(renewal)
+}
+
+// Catches auto-renewing services up to `today`, one recorded renewal per
+// cycle, so a laptop that was closed for months still ends up with a full
+// history.
+pub fn roll_forward(conn: &mut Connection, today: NaiveDate) -> anyhow::Result<Vec<ServiceRenewal>> {
+    let today = today.format("%Y-%m-%d").to_string();
+    let due: Vec<String> = conn
+        .prepare(
+            "SELECT id FROM services
+             WHERE auto_renew = 1 AND billing_cycle IS NOT NULL AND expires_at < ?1",
+        )?
+        .query_map([&today], |row| row.get(0))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut renewals = Vec::new();
+    for id in due {
+        loop {
+            let renewal = match renew(conn, &id, true) {
+                Ok(renewal) => renewal,
+                Err(e) => {
+                    eprintln!("Auto-renew of {} failed: {}", id, e);
+                    break;
+                }
+            };
+            let done = renewal.expires_at >= today;
+            renewals.push(renewal);
+            if done {
+                break;
+            }
+        }
+    }
+    Ok(renewals)
+}
+
+#[tauri::command]
+pub fn renew_service(id: String, state: State<DbState>) -> Result<ServiceRenewal, BlueprintError> {
+    let mut conn = state.conn()?;
+    Ok(renew(&mut conn, &id, false)?)
+}
+
+#[tauri::command]
+pub fn get_service_renewals(
+    service_id: String,
+    state: State<DbState>,
+) -> Result<Vec<ServiceRenewal>, BlueprintError> {
+    let conn = state.conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM service_renewals WHERE service_id = ?1 ORDER BY renewed_at DESC",
+        ServiceRenewal::COLUMNS
+    ))?;
+    let renewals = stmt
+        .query_map([&service_id], ServiceRenewal::from_row)?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(renewals)
+}
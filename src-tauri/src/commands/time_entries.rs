@@ -0,0 +1,503 @@
+use crate::commands::clients::{Client, PaymentType};
+use crate::commands::schedule::{Period, PeriodKind};
+use crate::dates::parse_day;
+use crate::db::{self, DbState};
+use crate::error::BlueprintError;
+use crate::i18n::{tr, trf};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter, State};
+use uuid::Uuid;
+
+// Worked time for a client. Start and end are stored as they happened;
+// rounding only applies when hours are added up. `ended_at` is empty while
+// the timer runs, and `invoice_id` is set once the time has been billed.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TimeEntry {
+    pub id: String,
+    pub client_id: String,
+    pub project: Option<String>,
+    pub note: Option<String>,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub billable: bool,
+    pub invoice_id: Option<String>,
+    pub created_at: String,
+}
+
+impl TimeEntry {
+    pub const COLUMNS: &'static str =
+        "id, client_id, project, note, started_at, ended_at, billable, invoice_id, created_at";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(TimeEntry {
+            id: row.get(0)?,
+            client_id: row.get(1)?,
+            project: row.get(2)?,
+            note: row.get(3)?,
+            started_at: row.get(4)?,
+            ended_at: row.get(5)?,
+            billable: row.get::<_, i32>(6)? != 0,
+            invoice_id: row.get(7)?,
+            created_at: row.get(8)?,
+        })
+    }
+
+    fn started(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.started_at)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    }
+
+    // Whole minutes worked; a running entry counts up to `now`.
+    pub fn minutes(&self, now: DateTime<Utc>) -> i64 {
+        let end = match &self.ended_at {
+            Some(end) => DateTime::parse_from_rfc3339(end).map(|t| t.with_timezone(&Utc)).ok(),
+            None => Some(now),
+        };
+        match (self.started(), end) {
+            (Some(start), Some(end)) => (end - start).num_minutes().max(0),
+            _ => 0,
+        }
+    }
+
+    // Entries belong to the period of the local day they started on.
+    pub fn period(&self, kind: PeriodKind) -> Option<Period> {
+        let day = self.started()?.with_timezone(&Local).date_naive();
+        Some(Period::containing(kind, day))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    Up,
+    Nearest,
+    Down,
+}
+
+// Per-entry rounding from the `time_rounding_minutes` (0 turns it off) and
+// `time_rounding_mode` settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rounding {
+    pub increment: i64,
+    pub mode: RoundingMode,
+}
+
+impl Rounding {
+    pub const NONE: Rounding = Rounding {
+        increment: 0,
+        mode: RoundingMode::Up,
+    };
+
+    pub fn parse_increment(value: &str) -> Option<i64> {
+        value.trim().parse().ok().filter(|m| (0..=60).contains(m))
+    }
+
+    pub fn parse_mode(value: &str) -> Option<RoundingMode> {
+        match value.trim() {
+            "up" => Some(RoundingMode::Up),
+            "nearest" => Some(RoundingMode::Nearest),
+            "down" => Some(RoundingMode::Down),
+            _ => None,
+        }
+    }
+
+    pub fn load(conn: &Connection) -> Rounding {
+        let increment = db::get_setting(conn, "time_rounding_minutes")
+            .and_then(|v| Rounding::parse_increment(&v))
+            .unwrap_or(0);
+        let mode = db::get_setting(conn, "time_rounding_mode")
+            .and_then(|v| Rounding::parse_mode(&v))
+            .unwrap_or(RoundingMode::Up);
+        Rounding { increment, mode }
+    }
+
+    pub fn apply(&self, minutes: i64) -> i64 {
+        let step = self.increment;
+        if step <= 1 {
+            return minutes;
+        }
+        match self.mode {
+            RoundingMode::Up => (minutes + step - 1) / step * step,
+            RoundingMode::Nearest => (minutes + step / 2) / step * step,
+            RoundingMode::Down => minutes / step * step,
+        }
+    }
+}
+
+// Billable time of one billing period, after rounding.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PeriodHours {
+    pub period: String,
+    pub entries: usize,
+    pub minutes: i64,
+    pub hours: f64,
+    pub unbilled_minutes: i64,
+    #[serde(skip)]
+    pub unbilled_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartTimerInput {
+    pub client_id: String,
+    pub project: Option<String>,
+    pub note: Option<String>,
+}
+
+// Either `ended_at` or `minutes`. Moments are RFC 3339, local
+// `YYYY-MM-DDTHH:MM` or a bare day (midnight local time).
+#[derive(Debug, Deserialize)]
+pub struct TimeEntryInput {
+    pub client_id: String,
+    pub project: Option<String>,
+    pub note: Option<String>,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub minutes: Option<i64>,
+    pub billable: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunningTimer {
+    #[serde(flatten)]
+    pub entry: TimeEntry,
+    pub client_name: String,
+    pub elapsed_minutes: i64,
+}
+
+fn text(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn parse_moment(value: &str) -> Result<DateTime<Utc>, BlueprintError> {
+    if let Ok(moment) = DateTime::parse_from_rfc3339(value) {
+        return Ok(moment.with_timezone(&Utc));
+    }
+    let local = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+        .ok()
+        .or_else(|| parse_day(value).ok().and_then(|d| d.and_hms_opt(0, 0, 0)))
+        .and_then(|t| Local.from_local_datetime(&t).earliest());
+    local
+        .map(|t| t.with_timezone(&Utc))
+        .ok_or_else(|| BlueprintError::validation(trf("error.invalid_date", &[("date", &value)])))
+}
+
+fn insert(conn: &Connection, entry: &TimeEntry) -> Result<(), BlueprintError> {
+    conn.execute(
+        &format!(
+            "INSERT INTO time_entries ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            TimeEntry::COLUMNS
+        ),
+        params![
+            entry.id,
+            entry.client_id,
+            entry.project,
+            entry.note,
+            entry.started_at,
+            entry.ended_at,
+            entry.billable as i32,
+            entry.invoice_id,
+            entry.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+fn load(conn: &Connection, id: &str) -> Result<TimeEntry, BlueprintError> {
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM time_entries WHERE id = ?1", TimeEntry::COLUMNS),
+        [id],
+        TimeEntry::from_row,
+    )?)
+}
+
+fn ensure_client(conn: &Connection, client_id: &str) -> Result<(), BlueprintError> {
+    conn.query_row("SELECT 1 FROM clients WHERE id = ?1", [client_id], |_| Ok(()))?;
+    Ok(())
+}
+
+pub fn running(conn: &Connection) -> Result<Option<TimeEntry>, BlueprintError> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM time_entries WHERE ended_at IS NULL ORDER BY started_at DESC LIMIT 1",
+                TimeEntry::COLUMNS
+            ),
+            [],
+            TimeEntry::from_row,
+        )
+        .optional()?)
+}
+
+pub fn running_timer(conn: &Connection, now: DateTime<Utc>) -> Result<Option<RunningTimer>, BlueprintError> {
+    let Some(entry) = running(conn)? else {
+        return Ok(None);
+    };
+    let client_name = conn.query_row("SELECT name FROM clients WHERE id = ?1", [&entry.client_id], |row| {
+        row.get(0)
+    })?;
+    Ok(Some(RunningTimer {
+        elapsed_minutes: entry.minutes(now),
+        client_name,
+        entry,
+    }))
+}
+
+pub fn stop(conn: &Connection, now: DateTime<Utc>) -> Result<Option<TimeEntry>, BlueprintError> {
+    let Some(entry) = running(conn)? else {
+        return Ok(None);
+    };
+    conn.execute(
+        "UPDATE time_entries SET ended_at = ?1 WHERE ended_at IS NULL",
+        [now.to_rfc3339()],
+    )?;
+    load(conn, &entry.id).map(Some)
+}
+
+// Only one timer runs at a time; starting another stops the current one.
+pub fn start(conn: &mut Connection, input: StartTimerInput, now: DateTime<Utc>) -> Result<TimeEntry, BlueprintError> {
+    ensure_client(conn, &input.client_id)?;
+    let entry = TimeEntry {
+        id: Uuid::new_v4().to_string(),
+        client_id: input.client_id,
+        project: text(input.project),
+        note: text(input.note),
+        started_at: now.to_rfc3339(),
+        ended_at: None,
+        billable: true,
+        invoice_id: None,
+        created_at: now.to_rfc3339(),
+    };
+    let tx = conn.transaction()?;
+    stop(&tx, now)?;
+    insert(&tx, &entry)?;
+    tx.commit()?;
+    Ok(entry)
+}
+
+pub fn add(conn: &Connection, input: TimeEntryInput) -> Result<TimeEntry, BlueprintError> {
+    ensure_client(conn, &input.client_id)?;
+    let started = parse_moment(&input.started_at)?;
+    let ended = match (&input.ended_at, input.minutes) {
+        (Some(end), _) if !end.is_empty() => Some(parse_moment(end)?),
+        (_, Some(minutes)) if minutes > 0 => {
+            TimeDelta::try_minutes(minutes).and_then(|span| started.checked_add_signed(span))
+        }
+        _ => None,
+    };
+    let Some(ended) = ended.filter(|ended| *ended > started) else {
+        return Err(BlueprintError::validation(tr("error.invalid_time_entry")));
+    };
+    let entry = TimeEntry {
+        id: Uuid::new_v4().to_string(),
+        client_id: input.client_id,
+        project: text(input.project),
+        note: text(input.note),
+        started_at: started.to_rfc3339(),
+        ended_at: Some(ended.to_rfc3339()),
+        billable: input.billable.unwrap_or(true),
+        invoice_id: None,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    insert(conn, &entry)?;
+    Ok(entry)
+}
+
+pub fn delete(conn: &Connection, id: &str) -> Result<(), BlueprintError> {
+    if conn.execute("DELETE FROM time_entries WHERE id = ?1", [id])? == 0 {
+        return Err(rusqlite::Error::QueryReturnedNoRows.into());
+    }
+    Ok(())
+}
+
+// Newest first; `to` includes the whole day.
+pub fn list(
+    conn: &Connection,
+    client_id: Option<&str>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<Vec<TimeEntry>, BlueprintError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM time_entries WHERE ?1 IS NULL OR client_id = ?1 ORDER BY started_at DESC",
+        TimeEntry::COLUMNS
+    ))?;
+    let entries = stmt
+        .query_map([client_id], TimeEntry::from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries
+        .into_iter()
+        .filter(|e| {
+            let day = e.period(PeriodKind::Day).map(|p| p.start);
+            day.is_some_and(|day| from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to))
+        })
+        .collect())
+}
+
+// Finished billable entries grouped by the client's billing period (months
+// for one-time clients), oldest first.
+pub fn billable_hours(
+    conn: &Connection,
+    client: &Client,
+    rounding: Rounding,
+) -> Result<BTreeMap<String, PeriodHours>, BlueprintError> {
+    let kind = client.payment_type.period_kind().unwrap_or(PeriodKind::Month);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM time_entries
+         WHERE client_id = ?1 AND billable = 1 AND ended_at IS NOT NULL
+         ORDER BY started_at",
+        TimeEntry::COLUMNS
+    ))?;
+    let entries = stmt
+        .query_map([&client.id], TimeEntry::from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let now = Utc::now();
+    let mut periods: BTreeMap<String, PeriodHours> = BTreeMap::new();
+    for entry in entries {
+        let Some(period) = entry.period(kind) else {
+            continue;
+        };
+        let minutes = rounding.apply(entry.minutes(now));
+        let key = period.key();
+        let hours = periods.entry(key.clone()).or_insert_with(|| PeriodHours {
+            period: key,
+            entries: 0,
+            minutes: 0,
+            hours: 0.0,
+            unbilled_minutes: 0,
+            unbilled_ids: Vec::new(),
+        });
+        hours.entries += 1;
+        hours.minutes += minutes;
+        hours.hours = hours.minutes as f64 / 60.0;
+        if entry.invoice_id.is_none() {
+            hours.unbilled_minutes += minutes;
+            hours.unbilled_ids.push(entry.id);
+        }
+    }
+    Ok(periods)
+}
+
+// What a period should bring in: hourly clients pay for the hours worked
+// (nothing is expected before any are logged), retainers pay their amount
+// plus overtime at `hourly_rate`, everyone else their fixed amount.
+pub fn expected_amount(client: &Client, hours: Option<&PeriodHours>) -> Option<f64> {
+    let worked = hours.map_or(0.0, |h| h.hours);
+    match client.payment_type {
+        PaymentType::Hourly => Some(worked * client.hourly_rate?).filter(|_| worked > 0.0),
+        PaymentType::Retainer => {
+            let overtime = (worked - client.included_hours.unwrap_or(0.0)).max(0.0);
+            let extra = client.hourly_rate.map_or(0.0, |rate| overtime * rate);
+            Some(client.amount.unwrap_or(0.0) + extra).filter(|total| *total > 0.0)
+        }
+        _ => client.amount,
+    }
+}
+
+fn load_client(conn: &Connection, id: &str) -> Result<Client, BlueprintError> {
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM clients WHERE id = ?1", Client::COLUMNS),
+        [id],
+        Client::from_row,
+    )?)
+}
+
+pub fn hours_between(
+    conn: &Connection,
+    client_id: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PeriodHours>, BlueprintError> {
+    if from > to {
+        return Err(BlueprintError::validation(trf(
+            "error.invalid_range",
+            &[("from", &from), ("to", &to)],
+        )));
+    }
+    let client = load_client(conn, client_id)?;
+    Ok(billable_hours(conn, &client, Rounding::load(conn))?
+        .into_values()
+        .filter(|h| Period::parse(&h.period).is_some_and(|p| p.start <= to && p.end() >= from))
+        .collect())
+}
+
+// The tray menu and the overlay both show the running timer.
+fn timer_changed(app: &AppHandle) {
+    crate::refresh_tray(app);
+    let _ = app.emit("timer-changed", ());
+}
+
+#[tauri::command]
+pub fn get_time_entries(
+    client_id: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    state: State<DbState>,
+) -> Result<Vec<TimeEntry>, BlueprintError> {
+    let from = from.filter(|d| !d.is_empty()).map(|d| parse_day(&d)).transpose()?;
+    let to = to.filter(|d| !d.is_empty()).map(|d| parse_day(&d)).transpose()?;
+    let conn = state.conn()?;
+    list(&conn, client_id.as_deref(), from, to)
+}
+
+#[tauri::command]
+pub fn get_running_timer(state: State<DbState>) -> Result<Option<RunningTimer>, BlueprintError> {
+    let conn = state.conn()?;
+    running_timer(&conn, Utc::now())
+}
+
+#[tauri::command]
+pub fn start_timer(
+    input: StartTimerInput,
+    state: State<DbState>,
+    app: AppHandle,
+) -> Result<TimeEntry, BlueprintError> {
+    let entry = {
+        let mut conn = state.conn()?;
+        start(&mut conn, input, Utc::now())?
+    };
+    timer_changed(&app);
+    Ok(entry)
+}
+
+#[tauri::command]
+pub fn stop_timer(state: State<DbState>, app: AppHandle) -> Result<Option<TimeEntry>, BlueprintError> {
+    let entry = {
+        let conn = state.conn()?;
+        stop(&conn, Utc::now())?
+    };
+    timer_changed(&app);
+    Ok(entry)
+}
+
+#[tauri::command]
+pub fn add_time_entry(input: TimeEntryInput, state: State<DbState>) -> Result<TimeEntry, BlueprintError> {
+    let conn = state.conn()?;
+    add(&conn, input)
+}
+
+#[tauri::command]
+pub fn delete_time_entry(id: String, state: State<DbState>, app: AppHandle) -> Result<(), BlueprintError> {
+    {
+        let conn = state.conn()?;
+        delete(&conn, &id)?;
+    }
+    timer_changed(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_billable_hours(
+    client_id: String,
+    from: String,
+    to: String,
+    state: State<DbState>,
+) -> Result<Vec<PeriodHours>, BlueprintError> {
+    let from = parse_day(&from)?;
+    let to = parse_day(&to)?;
+    let conn = state.conn()?;
+    hours_between(&conn, &client_id, from, to)
+}
@@ -0,0 +1,151 @@
+use crate::error::BlueprintError;
+use crate::i18n::{tr, trf};
+use anyhow::{anyhow, bail, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+pub const KEY_LEN: usize = 32;
+pub const KDF_ALGORITHM: &str = "argon2id";
+pub const CIPHER: &str = "xchacha20poly1305";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+// KDF parameters arrive with imported files, so anything costlier than a
+// generous ceiling is refused before Argon2 gets to allocate.
+const MAX_MEMORY_KIB: u32 = 256 * 1024;
+const MAX_ITERATIONS: u32 = 10;
+const MAX_PARALLELISM: u32 = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    pub fn generate() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        KdfParams {
+            algorithm: KDF_ALGORITHM.to_string(),
+            salt: STANDARD.encode(salt),
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+
+    pub fn derive_key(&self, passphrase: &str) -> Result<[u8; KEY_LEN]> {
+        if self.algorithm != KDF_ALGORITHM {
+            bail!(trf("error.unsupported_kdf", &[("algorithm", &self.algorithm)]));
+        }
+        if self.memory_kib > MAX_MEMORY_KIB
+            || self.iterations > MAX_ITERATIONS
+            || self.parallelism > MAX_PARALLELISM
+        {
+            bail!(BlueprintError::validation(tr("error.kdf_too_costly")));
+        }
+        let salt = decode(&self.salt, "salt")?;
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|e| anyhow!(trf("error.invalid_kdf_params", &[("error", &e)])))?;
+        let mut key = [0u8; KEY_LEN];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| anyhow!(trf("error.key_derivation_failed", &[("error", &e)])))?;
+        Ok(key)
+    }
+}
+
+fn decode(value: &str, field: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(value)
+        .map_err(|e| anyhow!(trf("error.corrupt_field", &[("field", &field), ("error", &e)])))
+}
+
+pub fn encrypt(key: &[u8; KEY_LEN], plaintext: &[u8], aad: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| anyhow!(tr("error.encryption_failed")))?;
+    Ok((nonce.to_vec(), ciphertext))
+}
+
+pub fn decrypt(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8],
+    ciphertext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>> {
+    if nonce.len() != NONCE_LEN {
+        bail!(tr("error.corrupt_nonce"));
+    }
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| {
+            BlueprintError::InvalidPassphrase(tr("error.wrong_passphrase_or_corrupt").to_string())
+                .into()
+        })
+}
+
+// Self-describing container: the KDF parameters travel with the data so they
+// can be raised later without breaking files sealed with the old ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PassphraseEnvelope {
+    pub kdf: KdfParams,
+    pub cipher: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl PassphraseEnvelope {
+    pub fn seal(passphrase: &str, plaintext: &[u8], aad: &[u8]) -> Result<Self> {
+        if passphrase.is_empty() {
+            bail!(BlueprintError::validation(tr("error.empty_passphrase")));
+        }
+        let kdf = KdfParams::generate();
+        let key = kdf.derive_key(passphrase)?;
+        let (nonce, ciphertext) = encrypt(&key, plaintext, aad)?;
+        Ok(PassphraseEnvelope {
+            kdf,
+            cipher: CIPHER.to_string(),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    pub fn open(&self, passphrase: &str, aad: &[u8]) -> Result<Vec<u8>> {
+        if self.cipher != CIPHER {
+            bail!(trf("error.unsupported_cipher", &[("cipher", &self.cipher)]));
+        }
+        let key = self.kdf.derive_key(passphrase)?;
+        let nonce = decode(&self.nonce, "nonce")?;
+        let ciphertext = decode(&self.ciphertext, "ciphertext")?;
+        decrypt(&key, &nonce, &ciphertext, aad)
+    }
+}
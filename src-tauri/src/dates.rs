@@ -0,0 +1,10 @@
+use crate::error::BlueprintError;
+use crate::i18n::trf;
+use chrono::NaiveDate;
+
+// Days travel as YYYY-MM-DD strings everywhere: in the database, in profile
+// exports and between the webview and the commands.
+pub(crate) fn parse_day(value: &str) -> Result<NaiveDate, BlueprintError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| BlueprintError::validation(trf("error.invalid_date", &[("date", &value)])))
+}
@@ -1,21 +1,354 @@
-use anyhow::Result;
-use rusqlite::Connection;
-use std::path::Path;
-use std::sync::Mutex;
+use crate::error::BlueprintError;
+use crate::i18n::{tr, trf};
+use anyhow::{anyhow, bail, Result};
+use rusqlite::{params, Connection, DatabaseName, OpenFlags};
+use std::io::Read;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
 
-pub struct DbState(pub Mutex<Connection>);
+const PLAINTEXT_HEADER: &[u8; 16] = b"SQLite format 3\0";
 
-pub fn init_db(db_path: &Path) -> Result<Connection> {
+#[derive(Default)]
+struct DbSlot {
+    conn: Option<Connection>,
+    key: Option<String>,
+}
+
+// The connection is absent while an encrypted database waits for its
+// passphrase; commands go through `conn()` and get an error until then, and
+// also while the app lock is engaged.
+pub struct DbState {
+    path: PathBuf,
+    slot: Mutex<DbSlot>,
+    app_locked: AtomicBool,
+}
+
+pub struct DbGuard<'a>(MutexGuard<'a, DbSlot>);
+
+impl DbGuard<'_> {
+    pub fn key(&self) -> Option<&str> {
+        self.0.key.as_deref()
+    }
+}
+
+impl Deref for DbGuard<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.0.conn.as_ref().expect("DbGuard holds an open connection")
+    }
+}
+
+impl DerefMut for DbGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.0.conn.as_mut().expect("DbGuard holds an open connection")
+    }
+}
+
+impl DbState {
+    pub fn new(path: PathBuf, conn: Connection, key: Option<String>) -> Self {
+        DbState {
+            path,
+            slot: Mutex::new(DbSlot {
+                conn: Some(conn),
+                key,
+            }),
+            app_locked: AtomicBool::new(false),
+        }
+    }
+
+    pub fn locked(path: PathBuf) -> Self {
+        DbState {
+            path,
+            slot: Mutex::new(DbSlot::default()),
+            app_locked: AtomicBool::new(false),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn slot(&self) -> Result<MutexGuard<'_, DbSlot>> {
+        self.slot.lock().map_err(|e| anyhow!(e.to_string()))
+    }
+
+    // Re-keying comes from the webview, so like `conn()` it waits for the
+    // app lock to be lifted.
+    fn unlocked_slot(&self) -> Result<MutexGuard<'_, DbSlot>> {
+        if self.is_app_locked() {
+            bail!(BlueprintError::AppLocked);
+        }
+        self.slot()
+    }
+
+    pub fn conn(&self) -> Result<DbGuard<'_>, BlueprintError> {
+        if self.is_app_locked() {
+            return Err(BlueprintError::AppLocked);
+        }
+        self.background_conn()
+    }
+
+    // Scheduled jobs keep running behind the app lock; only calls coming
+    // from the webview go through `conn()`.
+    pub fn background_conn(&self) -> Result<DbGuard<'_>, BlueprintError> {
+        let slot = self
+            .slot
+            .lock()
+            .map_err(|e| BlueprintError::Internal(e.to_string()))?;
+        if slot.conn.is_none() {
+            return Err(BlueprintError::DatabaseLocked);
+        }
+        Ok(DbGuard(slot))
+    }
+
+    pub fn set_app_locked(&self, locked: bool) {
+        self.app_locked.store(locked, Ordering::SeqCst);
+    }
+
+    pub fn is_app_locked(&self) -> bool {
+        self.app_locked.load(Ordering::SeqCst)
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.slot.lock().map(|s| s.conn.is_none()).unwrap_or(true)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        match self.slot.lock() {
+            Ok(slot) if slot.conn.is_some() => slot.key.is_some(),
+            _ => is_encrypted(&self.path),
+        }
+    }
+
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        let mut slot = self.slot()?;
+        if slot.conn.is_some() {
+            return Ok(());
+        }
+        slot.conn = Some(open(&self.path, Some(passphrase))?);
+        slot.key = Some(passphrase.to_string());
+        Ok(())
+    }
+
+    pub fn enable_encryption(&self, passphrase: &str) -> Result<()> {
+        if passphrase.is_empty() {
+            bail!(BlueprintError::validation(tr("error.empty_passphrase")));
+        }
+        let mut slot = self.unlocked_slot()?;
+        if slot.key.is_some() {
+            bail!(BlueprintError::validation(tr("error.database_already_encrypted")));
+        }
+        self.convert(&mut slot, Some(passphrase))
+    }
+
+    pub fn disable_encryption(&self, passphrase: &str) -> Result<()> {
+        let mut slot = self.unlocked_slot()?;
+        check_key(&slot, passphrase)?;
+        self.convert(&mut slot, None)
+    }
+
+    pub fn change_passphrase(&self, current: &str, new: &str) -> Result<()> {
+        if new.is_empty() {
+            bail!(BlueprintError::validation(tr("error.empty_passphrase")));
+        }
+        let mut slot = self.unlocked_slot()?;
+        check_key(&slot, current)?;
+        self.convert(&mut slot, Some(new))
+    }
+
+    // Copies the database into a sibling file with the new key, closes the
+    // live connection and swaps the files, so a failure at any step leaves
+    // the original database untouched and open.
+    fn convert(&self, slot: &mut DbSlot, key: Option<&str>) -> Result<()> {
+        let conn = slot
+            .conn
+            .take()
+            .ok_or(BlueprintError::DatabaseLocked)?;
+        let tmp = self.path.with_extension("db.convert");
+        if let Err(e) = export_to(&conn, &tmp, key) {
+            slot.conn = Some(conn);
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e);
+        }
+        if let Err((conn, e)) = conn.close() {
+            slot.conn = Some(conn);
+            let _ = std::fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+
+        let swapped = replace_file(&tmp, &self.path);
+        let (conn, key) = match swapped {
+            Ok(()) => (open(&self.path, key)?, key.map(str::to_string)),
+            Err(_) => (open(&self.path, slot.key.as_deref())?, slot.key.take()),
+        };
+        slot.conn = Some(conn);
+        slot.key = key;
+        swapped
+    }
+}
+
+fn check_key(slot: &DbSlot, passphrase: &str) -> Result<()> {
+    match slot.key.as_deref() {
+        None => bail!(BlueprintError::validation(tr("error.database_not_encrypted"))),
+        Some(key) if key != passphrase => bail!(wrong_passphrase()),
+        Some(_) => Ok(()),
+    }
+}
+
+fn wrong_passphrase() -> BlueprintError {
+    BlueprintError::InvalidPassphrase(tr("error.wrong_database_passphrase").to_string())
+}
+
+pub(crate) fn export_to(conn: &Connection, target: &Path, key: Option<&str>) -> Result<()> {
+    let _ = std::fs::remove_file(target);
+    conn.execute(
+        "ATTACH DATABASE ?1 AS export KEY ?2",
+        params![target.to_string_lossy(), key.unwrap_or("")],
+    )?;
+    let exported = conn
+        .query_row("SELECT sqlcipher_export('export')", [], |_| Ok(()))
+        .map_err(anyhow::Error::from)
+        .and_then(|_| {
+            let version = schema_version(conn)?;
+            conn.pragma_update(Some(DatabaseName::Attached("export")), "user_version", version)?;
+            Ok(())
+        });
+    conn.execute_batch("DETACH DATABASE export")?;
+    exported
+}
+
+pub(crate) fn replace_file(from: &Path, to: &Path) -> Result<()> {
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = to.as_os_str().to_owned();
+        sidecar.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(sidecar));
+    }
+    std::fs::rename(from, to)?;
+    Ok(())
+}
+
+type Migration = fn(&Connection) -> Result<()>;
+
+// Each entry upgrades the schema by one version; index + 1 is the version it
+// produces. Never edit or reorder shipped migrations, only append new ones.
+const MIGRATIONS: &[Migration] = &[
+    m001_initial_schema,
+    m002_client_payment_day,
+    m003_backup_settings,
+    m004_secrets,
+    m005_app_lock,
+    m006_language,
+    m007_reminders,
+    m008_notification_log,
+    m009_service_snooze,
+    m010_payment_notify_days,
+    m011_notification_rules,
+    m012_service_renewals,
+    m013_service_charges,
+    m014_fx_rates,
+    m015_invoices,
+    m016_payment_transactions,
+    m017_client_billing_terms,
+    m018_client_billing_models,
+    m019_time_entries,
+];
+
+pub const SCHEMA_VERSION: i32 = MIGRATIONS.len() as i32;
+
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+        Ok(()) => &header != PLAINTEXT_HEADER,
+        Err(_) => false,
+    }
+}
+
+pub fn open_keyed(path: &Path, key: Option<&str>, flags: OpenFlags) -> Result<Connection> {
+    let conn = Connection::open_with_flags(path, flags)?;
+    if let Some(key) = key {
+        conn.pragma_update(None, "key", key)?;
+    }
+    if conn
+        .query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .is_err()
+    {
+        bail!(wrong_passphrase());
+    }
+    Ok(conn)
+}
+
+pub fn open(db_path: &Path, key: Option<&str>) -> Result<Connection> {
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    let conn = Connection::open(db_path)?;
+    let mut conn = open_keyed(db_path, key, OpenFlags::default())?;
     conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
-    run_migrations(&conn)?;
+    run_migrations(&mut conn)?;
     Ok(conn)
 }
 
-fn run_migrations(conn: &Connection) -> Result<()> {
+pub fn init_db(db_path: &Path) -> Result<DbState> {
+    if is_encrypted(db_path) {
+        return Ok(DbState::locked(db_path.to_path_buf()));
+    }
+    let conn = open(db_path, None)?;
+    Ok(DbState::new(db_path.to_path_buf(), conn, None))
+}
+
+pub fn schema_version(conn: &Connection) -> Result<i32> {
+    Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+pub fn run_migrations(conn: &mut Connection) -> Result<()> {
+    let current = schema_version(conn)?;
+    if current > SCHEMA_VERSION {
+        bail!(BlueprintError::validation(trf(
+            "error.database_too_new",
+            &[("version", &current), ("supported", &SCHEMA_VERSION)]
+        )));
+    }
+
+    for (idx, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = idx as i32 + 1;
+        let tx = conn.transaction()?;
+        migration(&tx).map_err(|e| {
+            anyhow::anyhow!(trf("error.migration_failed", &[("version", &version), ("error", &e)]))
+        })?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+pub fn get_setting(conn: &Connection, key: &str) -> Option<String> {
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+        row.get::<_, String>(0)
+    })
+    .ok()
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let count: i32 = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn m001_initial_schema(conn: &Connection) -> Result<()> {
     conn.execute_batch(
         "
         CREATE TABLE IF NOT EXISTS clients (
@@ -94,18 +427,318 @@ fn run_migrations(conn: &Connection) -> Result<()> {
         INSERT OR IGNORE INTO settings (key, value) VALUES ('autostart', 'false');
         ",
     )?;
+    Ok(())
+}
 
-    let has_payment_day: bool = conn
-        .query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('clients') WHERE name='payment_day'",
-            [],
-            |row| row.get::<_, i32>(0),
-        )
-        .unwrap_or(0)
-        > 0;
-    if !has_payment_day {
+// Databases created before versioning may already have this column from the
+// old ad-hoc check, so it is added only when missing.
+fn m002_client_payment_day(conn: &Connection) -> Result<()> {
+    if !column_exists(conn, "clients", "payment_day")? {
         conn.execute_batch("ALTER TABLE clients ADD COLUMN payment_day INTEGER;")?;
     }
+    Ok(())
+}
+
+fn m003_backup_settings(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('backup_enabled', 'true');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('backup_interval_hours', '24');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('backup_keep_daily', '7');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('backup_keep_weekly', '4');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('backup_keep_monthly', '12');
+        ",
+    )?;
+    Ok(())
+}
+
+fn m004_secrets(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS secrets (
+            name TEXT PRIMARY KEY,
+            service_id TEXT REFERENCES services(id) ON DELETE CASCADE,
+            nonce BLOB NOT NULL,
+            ciphertext BLOB NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('secret_reveal_seconds', '30');
+        ",
+    )?;
+    Ok(())
+}
+
+fn m005_app_lock(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS app_lock (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            hash TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('app_lock_idle_minutes', '10');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('app_lock_on_hide', 'true');
+        ",
+    )?;
+    Ok(())
+}
 
+fn m006_language(conn: &Connection) -> Result<()> {
+    conn.execute_batch("INSERT OR IGNORE INTO settings (key, value) VALUES ('language', 'ru');")?;
+    Ok(())
+}
+
+fn m007_reminders(conn: &Connection) -> Result<()> {
+    if !column_exists(conn, "notes", "remind_at")? {
+        conn.execute_batch("ALTER TABLE notes ADD COLUMN remind_at TEXT;")?;
+    }
+    conn.execute_batch(
+        "
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('scheduler_interval_minutes', '15');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('quiet_hours_start', '');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('quiet_hours_end', '');
+        ",
+    )?;
+    Ok(())
+}
+
+fn m008_notification_log(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS notification_log (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            due TEXT NOT NULL,
+            threshold TEXT NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            sent_at TEXT NOT NULL,
+            read_at TEXT,
+            UNIQUE(kind, entity_id, due, threshold)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_notification_log_sent_at ON notification_log(sent_at);
+        ",
+    )?;
+    Ok(())
+}
+
+fn m009_service_snooze(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        ALTER TABLE services ADD COLUMN snoozed_until TEXT;
+        ALTER TABLE services ADD COLUMN acknowledged_expires_at TEXT;
+        ",
+    )?;
+    Ok(())
+}
+
+fn m010_payment_notify_days(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE clients ADD COLUMN payment_notify_days INTEGER;")?;
+    Ok(())
+}
+
+// `notify_days` is superseded by threshold lists; services that changed it
+// keep their window plus the expiry day as a per-service rule.
+fn m011_notification_rules(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        ALTER TABLE services ADD COLUMN notify_thresholds TEXT;
+        UPDATE services SET notify_thresholds = notify_days || ',0' WHERE notify_days != 7;
+
+        CREATE TABLE IF NOT EXISTS notification_rules (
+            category TEXT PRIMARY KEY,
+            thresholds TEXT NOT NULL
+        );
+
+        UPDATE settings SET value = '30,7,1,0' WHERE key = 'notify_days_before' AND value = '7';
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('notify_days_before', '30,7,1,0');
+        ",
+    )?;
+    Ok(())
+}
+
+fn m012_service_renewals(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        ALTER TABLE services ADD COLUMN billing_cycle TEXT;
+        ALTER TABLE services ADD COLUMN billing_cycle_days INTEGER;
+        ALTER TABLE services ADD COLUMN auto_renew INTEGER NOT NULL DEFAULT 0;
+
+        CREATE TABLE IF NOT EXISTS service_renewals (
+            id TEXT PRIMARY KEY,
+            service_id TEXT NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+            previous_expires_at TEXT NOT NULL,
+            expires_at TEXT NOT NULL,
+            cost REAL,
+            currency TEXT NOT NULL,
+            auto_renewed INTEGER NOT NULL DEFAULT 0,
+            renewed_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_service_renewals_service ON service_renewals(service_id);
+        ",
+    )?;
+    Ok(())
+}
+
+// Renewals recorded before this table existed become charges on the day the
+// old period ended.
+fn m013_service_charges(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS service_charges (
+            id TEXT PRIMARY KEY,
+            service_id TEXT NOT NULL REFERENCES services(id) ON DELETE CASCADE,
+            charged_on TEXT NOT NULL,
+            amount REAL NOT NULL,
+            currency TEXT NOT NULL,
+            note TEXT,
+            renewal_id TEXT REFERENCES service_renewals(id) ON DELETE SET NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_service_charges_service ON service_charges(service_id);
+        CREATE INDEX IF NOT EXISTS idx_service_charges_charged_on ON service_charges(charged_on);
+
+        INSERT INTO service_charges (id, service_id, charged_on, amount, currency, renewal_id, created_at)
+        SELECT lower(hex(randomblob(16))), service_id, previous_expires_at, cost, currency, id, renewed_at
+        FROM service_renewals WHERE cost IS NOT NULL;
+        ",
+    )?;
+    Ok(())
+}
+
+// `base_currency` is what reports convert into unless they are asked for
+// another one.
+fn m014_fx_rates(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS fx_rates (
+            id TEXT PRIMARY KEY,
+            base TEXT NOT NULL,
+            quote TEXT NOT NULL,
+            date TEXT NOT NULL,
+            rate REAL NOT NULL,
+            UNIQUE(base, quote, date)
+        );
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('base_currency', 'RUB');
+        ",
+    )?;
+    Ok(())
+}
+
+fn m015_invoices(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS invoices (
+            id TEXT PRIMARY KEY,
+            number TEXT NOT NULL UNIQUE,
+            client_id TEXT NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+            period TEXT,
+            issued_on TEXT NOT NULL,
+            due_on TEXT,
+            currency TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'issued',
+            notes TEXT,
+            paid_at TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS invoice_items (
+            id TEXT PRIMARY KEY,
+            invoice_id TEXT NOT NULL REFERENCES invoices(id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            description TEXT NOT NULL,
+            quantity REAL NOT NULL DEFAULT 1,
+            unit_price REAL NOT NULL,
+            period TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_invoices_client ON invoices(client_id);
+        CREATE INDEX IF NOT EXISTS idx_invoice_items_invoice ON invoice_items(invoice_id);
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('invoice_prefix', 'INV-{year}-');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('invoice_next_number', '1');
+        ",
+    )?;
+    Ok(())
+}
+
+// Partial payments: a period may be settled by several transfers, each in its
+// own currency. `client_payments.paid` stays the source of truth for reminders
+// and is recomputed whenever a transaction is added or removed.
+fn m016_payment_transactions(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS payment_transactions (
+            id TEXT PRIMARY KEY,
+            payment_id TEXT NOT NULL REFERENCES client_payments(id) ON DELETE CASCADE,
+            paid_on TEXT NOT NULL,
+            amount REAL NOT NULL,
+            currency TEXT NOT NULL,
+            method TEXT,
+            note TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_payment_transactions_payment
+            ON payment_transactions(payment_id);
+        ",
+    )?;
+    Ok(())
+}
+
+fn m017_client_billing_terms(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        ALTER TABLE clients ADD COLUMN start_date TEXT;
+        ALTER TABLE clients ADD COLUMN end_date TEXT;
+        ALTER TABLE clients ADD COLUMN pause_from TEXT;
+        ALTER TABLE clients ADD COLUMN pause_to TEXT;
+        ",
+    )?;
+    Ok(())
+}
+
+// Anything but the two original payment types is a typo from hand-edited
+// data; treating it as monthly keeps the row loadable.
+fn m018_client_billing_models(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        ALTER TABLE clients ADD COLUMN included_hours REAL;
+        ALTER TABLE clients ADD COLUMN hourly_rate REAL;
+
+        UPDATE clients SET payment_type = 'monthly' WHERE payment_type NOT IN ('monthly', 'onetime');
+        ",
+    )?;
+    Ok(())
+}
+
+fn m019_time_entries(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS time_entries (
+            id TEXT PRIMARY KEY,
+            client_id TEXT NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+            project TEXT,
+            note TEXT,
+            started_at TEXT NOT NULL,
+            ended_at TEXT,
+            billable INTEGER NOT NULL DEFAULT 1,
+            invoice_id TEXT REFERENCES invoices(id) ON DELETE SET NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_time_entries_client ON time_entries(client_id, started_at);
+
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('time_rounding_minutes', '0');
+        INSERT OR IGNORE INTO settings (key, value) VALUES ('time_rounding_mode', 'up');
+        ",
+    )?;
     Ok(())
 }
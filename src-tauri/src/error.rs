@@ -0,0 +1,192 @@
+use crate::commands::profile::ValidationIssue;
+use crate::i18n::{tr, trf};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+
+// Every command fails with this type. The webview receives
+// `{ code, message, details }`: `code` is stable and meant for branching,
+// `message` is already localized and ready to show to the user.
+#[derive(Debug)]
+pub enum BlueprintError {
+    Validation {
+        message: String,
+        details: Option<Value>,
+    },
+    NotFound(String),
+    MissingApiKey { secret: String },
+    InvalidPassphrase(String),
+    AppLocked,
+    DatabaseLocked,
+    Network(String),
+    Provider {
+        provider: String,
+        status: u16,
+        body: String,
+    },
+    Database(String),
+    Io(String),
+    Internal(String),
+}
+
+impl fmt::Display for BlueprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlueprintError::Validation { message, .. } => f.write_str(message),
+            BlueprintError::NotFound(message)
+            | BlueprintError::InvalidPassphrase(message)
+            | BlueprintError::Internal(message) => f.write_str(message),
+            BlueprintError::MissingApiKey { secret } => f.write_str(match secret.as_str() {
+                "groq_api_key" => tr("error.missing_groq_key"),
+                _ => tr("error.missing_ai_key"),
+            }),
+            BlueprintError::AppLocked => f.write_str(tr("error.app_locked")),
+            BlueprintError::DatabaseLocked => f.write_str(tr("error.database_locked")),
+            BlueprintError::Network(error) => {
+                f.write_str(&trf("error.network", &[("error", error)]))
+            }
+            BlueprintError::Provider {
+                provider,
+                status,
+                body,
+            } => f.write_str(&trf(
+                "error.provider",
+                &[("provider", provider), ("status", status), ("body", body)],
+            )),
+            BlueprintError::Database(error) => {
+                f.write_str(&trf("error.database", &[("error", error)]))
+            }
+            BlueprintError::Io(error) => f.write_str(&trf("error.io", &[("error", error)])),
+        }
+    }
+}
+
+impl std::error::Error for BlueprintError {}
+
+impl BlueprintError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        BlueprintError::Validation {
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        BlueprintError::NotFound(message.into())
+    }
+
+    pub fn missing_key(secret: impl Into<String>) -> Self {
+        BlueprintError::MissingApiKey {
+            secret: secret.into(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            BlueprintError::Validation { .. } => "validation",
+            BlueprintError::NotFound(_) => "not_found",
+            BlueprintError::MissingApiKey { .. } => "missing_api_key",
+            BlueprintError::InvalidPassphrase(_) => "invalid_passphrase",
+            BlueprintError::AppLocked => "app_locked",
+            BlueprintError::DatabaseLocked => "database_locked",
+            BlueprintError::Network(_) => "network",
+            BlueprintError::Provider { .. } => "provider",
+            BlueprintError::Database(_) => "database",
+            BlueprintError::Io(_) => "io",
+            BlueprintError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            BlueprintError::Validation { details, .. } => details.clone(),
+            BlueprintError::MissingApiKey { secret } => Some(json!({ "secret": secret })),
+            BlueprintError::Provider {
+                provider,
+                status,
+                body,
+            } => Some(json!({ "provider": provider, "status": status, "body": body })),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for BlueprintError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("BlueprintError", 3)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("details", &self.details())?;
+        s.end()
+    }
+}
+
+impl From<rusqlite::Error> for BlueprintError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => BlueprintError::not_found(tr("error.row_not_found")),
+            e => BlueprintError::Database(e.to_string()),
+        }
+    }
+}
+
+// Internal helpers still use anyhow; typed errors raised inside them survive
+// the round trip, everything else becomes `internal`.
+impl From<anyhow::Error> for BlueprintError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<BlueprintError>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        match e.downcast::<rusqlite::Error>() {
+            Ok(e) => e.into(),
+            Err(e) => BlueprintError::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for BlueprintError {
+    fn from(e: reqwest::Error) -> Self {
+        BlueprintError::Network(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for BlueprintError {
+    fn from(e: serde_json::Error) -> Self {
+        BlueprintError::Internal(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for BlueprintError {
+    fn from(e: std::io::Error) -> Self {
+        BlueprintError::Io(e.to_string())
+    }
+}
+
+impl From<tauri::Error> for BlueprintError {
+    fn from(e: tauri::Error) -> Self {
+        BlueprintError::Internal(e.to_string())
+    }
+}
+
+impl From<tauri_plugin_autostart::Error> for BlueprintError {
+    fn from(e: tauri_plugin_autostart::Error) -> Self {
+        BlueprintError::Internal(e.to_string())
+    }
+}
+
+impl From<tauri_plugin_global_shortcut::Error> for BlueprintError {
+    fn from(e: tauri_plugin_global_shortcut::Error) -> Self {
+        BlueprintError::Internal(e.to_string())
+    }
+}
+
+impl From<ValidationIssue> for BlueprintError {
+    fn from(issue: ValidationIssue) -> Self {
+        BlueprintError::Validation {
+            message: issue.to_string(),
+            details: serde_json::to_value(vec![issue]).ok(),
+        }
+    }
+}
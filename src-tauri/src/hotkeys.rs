@@ -1,3 +1,5 @@
+use crate::error::BlueprintError;
+use crate::i18n::trf;
 use std::convert::TryInto;
 use std::sync::Mutex;
 use tauri::{App, AppHandle, Emitter, Manager};
@@ -70,7 +72,7 @@ pub fn register_shortcuts(app: &mut App) -> Result<(), Box<dyn std::error::Error
 fn get_setting_from_app(app: &App, key: &str) -> Option<String> {
     use crate::db::DbState;
     let state = app.state::<DbState>();
-    let conn = state.0.lock().ok()?;
+    let conn = state.background_conn().ok()?;
     conn.query_row(
         "SELECT value FROM settings WHERE key = ?1",
         [key],
@@ -96,7 +98,7 @@ fn toggle_overlay_window_internal(app: &AppHandle) {
 fn get_overlay_position(app: &AppHandle) -> String {
     use crate::db::DbState;
     if let Some(state) = app.try_state::<DbState>() {
-        if let Ok(conn) = state.0.lock() {
+        if let Ok(conn) = state.background_conn() {
             if let Ok(pos) = conn.query_row(
                 "SELECT value FROM settings WHERE key = 'overlay_position'",
                 [],
@@ -174,7 +176,7 @@ pub fn create_voice_indicator_window(app: &AppHandle) {
 }
 
 #[tauri::command]
-pub fn toggle_overlay_window(app: AppHandle) -> Result<(), String> {
+pub fn toggle_overlay_window(app: AppHandle) -> Result<(), BlueprintError> {
     toggle_overlay_window_internal(&app);
     Ok(())
 }
@@ -184,18 +186,28 @@ pub fn update_hotkeys(
     overlay_hotkey: String,
     voice_hotkey: String,
     app: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), BlueprintError> {
     let _ = app.global_shortcut().unregister_all();
 
     if !overlay_hotkey.is_empty() {
         app.global_shortcut()
             .register(overlay_hotkey.as_str())
-            .map_err(|e| format!("Неверный формат overlay хоткея '{}': {}", overlay_hotkey, e))?;
+            .map_err(|e| {
+                BlueprintError::validation(trf(
+                    "error.invalid_hotkey",
+                    &[("kind", &"overlay"), ("hotkey", &overlay_hotkey), ("error", &e)],
+                ))
+            })?;
     }
     if !voice_hotkey.is_empty() {
         app.global_shortcut()
             .register(voice_hotkey.as_str())
-            .map_err(|e| format!("Неверный формат voice хоткея '{}': {}", voice_hotkey, e))?;
+            .map_err(|e| {
+                BlueprintError::validation(trf(
+                    "error.invalid_hotkey",
+                    &[("kind", &"voice"), ("hotkey", &voice_hotkey), ("error", &e)],
+                ))
+            })?;
     }
 
     let state = app.state::<HotkeyState>();
@@ -206,15 +218,14 @@ pub fn update_hotkeys(
 }
 
 #[tauri::command]
-pub fn pause_hotkeys(app: AppHandle) -> Result<(), String> {
+pub fn pause_hotkeys(app: AppHandle) -> Result<(), BlueprintError> {
     app.global_shortcut()
-        .unregister_all()
-        .map_err(|e| e.to_string())?;
+        .unregister_all()?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn resume_hotkeys(app: AppHandle) -> Result<(), String> {
+pub fn resume_hotkeys(app: AppHandle) -> Result<(), BlueprintError> {
     let state = app.state::<HotkeyState>();
     let overlay = state.overlay_hotkey.lock().unwrap().clone();
     let voice = state.voice_hotkey.lock().unwrap().clone();
@@ -230,7 +241,7 @@ pub fn resume_hotkeys(app: AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn update_overlay_position(position: String, app: AppHandle) -> Result<(), String> {
+pub fn update_overlay_position(position: String, app: AppHandle) -> Result<(), BlueprintError> {
     if let Some(window) = app.get_webview_window("overlay") {
         let win_w = 380.0_f64;
         let win_h = 550.0_f64;
@@ -249,14 +260,13 @@ pub fn update_overlay_position(position: String, app: AppHandle) -> Result<(), S
             (100.0, 100.0)
         };
         window
-            .set_position(tauri::PhysicalPosition::new(x as i32, y as i32))
-            .map_err(|e| e.to_string())?;
+            .set_position(tauri::PhysicalPosition::new(x as i32, y as i32))?;
     }
     Ok(())
 }
 
 #[tauri::command]
-pub fn show_voice_indicator(app: AppHandle) -> Result<(), String> {
+pub fn show_voice_indicator(app: AppHandle) -> Result<(), BlueprintError> {
     create_voice_indicator_window(&app);
     if let Some(win) = app.get_webview_window("voice-indicator") {
         let _ = win.show();
@@ -265,7 +275,7 @@ pub fn show_voice_indicator(app: AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn hide_voice_indicator(app: AppHandle) -> Result<(), String> {
+pub fn hide_voice_indicator(app: AppHandle) -> Result<(), BlueprintError> {
     if let Some(win) = app.get_webview_window("voice-indicator") {
         let _ = win.close();
     }
@@ -273,6 +283,6 @@ pub fn hide_voice_indicator(app: AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn emit_voice_message_saved(app: AppHandle) -> Result<(), String> {
-    app.emit("voice-message-saved", ()).map_err(|e| e.to_string())
+pub fn emit_voice_message_saved(app: AppHandle) -> Result<(), BlueprintError> {
+    app.emit("voice-message-saved", ()).map_err(BlueprintError::from)
 }
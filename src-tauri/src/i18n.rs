@@ -0,0 +1,490 @@
+use crate::db;
+use rusqlite::Connection;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Ru,
+    En,
+}
+
+pub const LANGUAGES: &[Language] = &[Language::Ru, Language::En];
+
+impl Language {
+    pub fn parse(code: &str) -> Option<Language> {
+        match code {
+            "ru" => Some(Language::Ru),
+            "en" => Some(Language::En),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Ru => "ru",
+            Language::En => "en",
+        }
+    }
+}
+
+// The `language` setting, cached so messages can be built anywhere without a
+// connection. Kept in sync by `load` at startup and by `save_setting`.
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn current() -> Language {
+    LANGUAGES
+        .get(CURRENT.load(Ordering::Relaxed) as usize)
+        .copied()
+        .unwrap_or(Language::Ru)
+}
+
+pub fn set_current(language: Language) {
+    let idx = LANGUAGES.iter().position(|l| *l == language).unwrap_or(0);
+    CURRENT.store(idx as u8, Ordering::Relaxed);
+}
+
+pub fn load(conn: &Connection) -> Language {
+    let language = db::get_setting(conn, "language")
+        .and_then(|code| Language::parse(&code))
+        .unwrap_or(Language::Ru);
+    set_current(language);
+    language
+}
+
+// For when the settings table was swapped underneath us (unlock, import,
+// restore): picks up the stored language and relabels the tray.
+pub fn reload(app: &AppHandle, conn: &Connection) {
+    load(conn);
+    crate::refresh_tray(app);
+}
+
+// key, ru, en. Placeholders are `{name}` and must match across languages.
+pub const MESSAGES: &[(&str, &str, &str)] = &[
+    ("tray.show", "Показать Blueprint", "Show Blueprint"),
+    ("tray.quit", "Выйти", "Quit"),
+    ("tray.timer_running", "⏱ {client} · с {time}", "⏱ {client} · since {time}"),
+    ("tray.timer_running_locked", "⏱ Таймер идёт", "⏱ Timer running"),
+    ("tray.stop_timer", "Остановить таймер", "Stop timer"),
+    ("notify.expired", "Истёк: {name}", "Expired: {name}"),
+    ("notify.expires_today", "Истекает сегодня: {name}", "Expires today: {name}"),
+    (
+        "notify.expires_in",
+        "Истекает через {days} дн: {name}",
+        "Expires in {days} d: {name}",
+    ),
+    (
+        "notify.payment_upcoming",
+        "Оплата через {days} дн: {name}",
+        "Payment in {days} d: {name}",
+    ),
+    ("notify.payment_due", "Ожидается оплата: {name}", "Payment due: {name}"),
+    ("notify.payment_overdue", "Просрочена оплата: {name}", "Payment overdue: {name}"),
+    ("notify.note_reminder", "Напоминание: {title}", "Reminder: {title}"),
+    ("invoice.title", "Счёт № {number}", "Invoice {number}"),
+    ("invoice.from", "Исполнитель", "From"),
+    ("invoice.bill_to", "Плательщик", "Bill to"),
+    ("invoice.issued", "Дата", "Date"),
+    ("invoice.due", "Оплатить до", "Due"),
+    ("invoice.description", "Описание", "Description"),
+    ("invoice.quantity", "Кол-во", "Qty"),
+    ("invoice.price", "Цена", "Price"),
+    ("invoice.amount", "Сумма", "Amount"),
+    ("invoice.total", "Итого", "Total"),
+    ("invoice.default_item", "Услуги за период {period}", "Services for {period}"),
+    ("invoice.default_item_no_period", "Услуги", "Services"),
+    ("invoice.hours_item", "Работа за период {period}, ч", "Work for {period}, hours"),
+    (
+        "invoice.overtime_item",
+        "Часы сверх абонемента за период {period}",
+        "Hours beyond the retainer for {period}",
+    ),
+    ("error.app_locked", "Приложение заблокировано", "The app is locked"),
+    (
+        "error.database_locked",
+        "База данных зашифрована и ещё не разблокирована",
+        "The database is encrypted and not unlocked yet",
+    ),
+    (
+        "error.missing_ai_key",
+        "API ключ не настроен. Перейди в Настройки и добавь ключ.",
+        "API key is not configured. Add it in Settings.",
+    ),
+    (
+        "error.missing_groq_key",
+        "Groq API ключ не настроен. Добавьте его в Настройки → Голосовой ввод.",
+        "Groq API key is not configured. Add it in Settings → Voice input.",
+    ),
+    ("error.network", "Сетевая ошибка: {error}", "Network error: {error}"),
+    (
+        "error.provider",
+        "{provider} API ошибка {status}: {body}",
+        "{provider} API error {status}: {body}",
+    ),
+    ("error.database", "Ошибка базы данных: {error}", "Database error: {error}"),
+    ("error.io", "Ошибка файловой системы: {error}", "File system error: {error}"),
+    ("error.row_not_found", "Запись не найдена", "Record not found"),
+    ("error.invalid_date", "Некорректная дата: {date}", "Invalid date: {date}"),
+    ("error.invalid_period", "Некорректный период: {period}", "Invalid period: {period}"),
+    (
+        "error.invoice_empty",
+        "В счёте нет позиций, а у клиента не указана сумма",
+        "The invoice has no items and the client has no amount",
+    ),
+    (
+        "error.invoice_due_before_issue",
+        "Срок оплаты {due} раньше даты счёта {issued}",
+        "Due date {due} is before the issue date {issued}",
+    ),
+    (
+        "error.invoice_item_no_description",
+        "У позиции счёта нет описания",
+        "An invoice item has no description",
+    ),
+    (
+        "error.invoice_item_bad_price",
+        "Некорректная цена позиции счёта: {price}",
+        "Invalid invoice item price: {price}",
+    ),
+    (
+        "error.invoice_item_bad_quantity",
+        "Количество в позиции счёта должно быть больше нуля: {quantity}",
+        "Invoice item quantity must be greater than zero: {quantity}",
+    ),
+    (
+        "error.invalid_range",
+        "Начало периода позже конца: {from} — {to}",
+        "Range starts after it ends: {from} — {to}",
+    ),
+    (
+        "error.invalid_billing_cycle",
+        "Неизвестный период оплаты: {cycle}",
+        "Unknown billing cycle: {cycle}",
+    ),
+    (
+        "error.no_billing_cycle",
+        "У сервиса «{name}» не задан период оплаты",
+        "Service \"{name}\" has no billing cycle",
+    ),
+    (
+        "error.unknown_currency",
+        "Неизвестный код валюты: {code}",
+        "Unknown currency code: {code}",
+    ),
+    (
+        "error.invalid_payment_type",
+        "Неизвестный тип оплаты: {value}",
+        "Unknown payment type: {value}",
+    ),
+    (
+        "error.invalid_payment_day",
+        "Некорректный день оплаты: {day}",
+        "Invalid payment day: {day}",
+    ),
+    (
+        "error.invalid_hours",
+        "Количество часов должно быть больше нуля: {value}",
+        "Hours must be greater than zero: {value}",
+    ),
+    (
+        "error.invalid_hourly_rate",
+        "Ставка должна быть больше нуля: {value}",
+        "Hourly rate must be greater than zero: {value}",
+    ),
+    (
+        "error.retainer_needs_hours",
+        "Для абонемента укажите включённые часы",
+        "A retainer needs included hours",
+    ),
+    (
+        "error.hourly_needs_rate",
+        "Для почасовой оплаты укажите ставку",
+        "Hourly billing needs a rate",
+    ),
+    (
+        "error.pause_without_start",
+        "У паузы указан конец, но нет начала",
+        "The pause has an end but no start",
+    ),
+    (
+        "error.invalid_time_entry",
+        "Запись времени должна заканчиваться позже, чем начинается",
+        "A time entry must end after it starts",
+    ),
+    (
+        "error.invalid_rounding",
+        "Некорректное правило округления: {value}",
+        "Invalid rounding rule: {value}",
+    ),
+    (
+        "error.kdf_too_costly",
+        "Параметры ключа в файле слишком велики",
+        "The key parameters in the file are too large",
+    ),
+    (
+        "error.invalid_amount",
+        "Сумма должна быть больше нуля: {amount}",
+        "Amount must be greater than zero: {amount}",
+    ),
+    (
+        "error.invalid_charge_amount",
+        "Сумма списания должна быть ненулевым числом: {amount}",
+        "A charge amount must be a non-zero number: {amount}",
+    ),
+    (
+        "error.invalid_fx_rate",
+        "Некорректный курс {base}/{quote}: {rate}",
+        "Invalid {base}/{quote} rate: {rate}",
+    ),
+    (
+        "error.no_fx_rate",
+        "Нет курса {from}/{to} на {date}",
+        "No {from}/{to} rate for {date}",
+    ),
+    (
+        "error.invalid_fx_import",
+        "Не удалось разобрать курсы, строка {line}: {error}",
+        "Could not parse rates, line {line}: {error}",
+    ),
+    (
+        "error.invalid_thresholds",
+        "Пороги напоминаний должны быть списком дней через запятую: {value}",
+        "Reminder thresholds must be a comma-separated list of days: {value}",
+    ),
+    (
+        "error.snooze_needs_date",
+        "Укажите дату или количество дней",
+        "Specify a date or a number of days",
+    ),
+    (
+        "error.invalid_audio",
+        "Некорректные аудиоданные: {error}",
+        "Invalid audio data: {error}",
+    ),
+    (
+        "error.invalid_hotkey",
+        "Неверный формат {kind} хоткея '{hotkey}': {error}",
+        "Invalid {kind} hotkey '{hotkey}': {error}",
+    ),
+    (
+        "error.secret_in_settings",
+        "{key} хранится в хранилище секретов, а не в настройках",
+        "{key} is kept in the secret vault, not in settings",
+    ),
+    (
+        "error.unknown_language",
+        "Неподдерживаемый язык: {code}",
+        "Unsupported language: {code}",
+    ),
+    (
+        "error.empty_passphrase",
+        "Парольная фраза не может быть пустой",
+        "Passphrase must not be empty",
+    ),
+    (
+        "error.database_already_encrypted",
+        "База данных уже зашифрована",
+        "The database is already encrypted",
+    ),
+    (
+        "error.database_not_encrypted",
+        "База данных не зашифрована",
+        "The database is not encrypted",
+    ),
+    (
+        "error.wrong_database_passphrase",
+        "Неверная парольная фраза базы данных",
+        "Wrong database passphrase",
+    ),
+    (
+        "error.database_too_new",
+        "База данных создана более новой версией Blueprint (схема v{version}, поддерживается до v{supported})",
+        "The database was created by a newer Blueprint (schema v{version}, supported up to v{supported})",
+    ),
+    (
+        "error.wrong_passphrase_or_corrupt",
+        "Неверная парольная фраза или данные повреждены",
+        "Wrong passphrase or corrupted data",
+    ),
+    (
+        "error.pin_too_short",
+        "PIN или пароль должен быть не короче 4 символов",
+        "PIN or password must be at least 4 characters",
+    ),
+    (
+        "error.app_lock_disabled",
+        "Блокировка приложения не включена",
+        "App lock is not enabled",
+    ),
+    ("error.wrong_pin", "Неверный PIN или пароль", "Wrong PIN or password"),
+    ("error.unknown_secret", "Неизвестный секрет: {name}", "Unknown secret: {name}"),
+    ("error.secret_not_set", "Секрет не задан: {name}", "Secret is not set: {name}"),
+    (
+        "error.backup_encrypted",
+        "Резервная копия зашифрована, а текущая база данных нет",
+        "The backup is encrypted but the current database is not",
+    ),
+    (
+        "error.backup_corrupt",
+        "Резервная копия повреждена: {result}",
+        "The backup is corrupted: {result}",
+    ),
+    (
+        "error.backup_too_new",
+        "Резервная копия создана более новой версией Blueprint (схема v{version}, поддерживается до v{supported})",
+        "The backup was created by a newer Blueprint (schema v{version}, supported up to v{supported})",
+    ),
+    (
+        "error.backup_bad_name",
+        "Некорректное имя резервной копии: {name}",
+        "Invalid backup name: {name}",
+    ),
+    (
+        "error.backup_not_found",
+        "Резервная копия не найдена: {name}",
+        "Backup not found: {name}",
+    ),
+    (
+        "error.backup_unreadable",
+        "Не удалось прочитать резервную копию {name}",
+        "Could not read backup {name}",
+    ),
+    (
+        "error.migration_failed",
+        "Миграция v{version} не выполнена: {error}",
+        "Migration v{version} failed: {error}",
+    ),
+    (
+        "error.unsupported_kdf",
+        "Неподдерживаемый алгоритм ключа: {algorithm}",
+        "Unsupported key algorithm: {algorithm}",
+    ),
+    (
+        "error.invalid_kdf_params",
+        "Некорректные параметры ключа: {error}",
+        "Invalid key parameters: {error}",
+    ),
+    (
+        "error.key_derivation_failed",
+        "Не удалось получить ключ: {error}",
+        "Could not derive the key: {error}",
+    ),
+    (
+        "error.corrupt_field",
+        "Поле {field} повреждено: {error}",
+        "Field {field} is corrupted: {error}",
+    ),
+    ("error.corrupt_nonce", "Поле nonce повреждено", "Field nonce is corrupted"),
+    (
+        "error.encryption_failed",
+        "Не удалось зашифровать данные",
+        "Could not encrypt the data",
+    ),
+    (
+        "error.unsupported_cipher",
+        "Неподдерживаемый шифр: {cipher}",
+        "Unsupported cipher: {cipher}",
+    ),
+    (
+        "error.secret_decrypt_failed",
+        "Не удалось расшифровать секрет {name}",
+        "Could not decrypt secret {name}",
+    ),
+    (
+        "error.vault_key_corrupt",
+        "Ключ хранилища секретов повреждён",
+        "The secret vault key is corrupted",
+    ),
+    (
+        "error.vault_key_missing",
+        "Ключ хранилища секретов не найден, сохранённые секреты не расшифровать",
+        "The secret vault key is missing, stored secrets cannot be decrypted",
+    ),
+    (
+        "error.pin_save_failed",
+        "Не удалось сохранить пароль: {error}",
+        "Could not save the password: {error}",
+    ),
+    (
+        "error.pin_hash_corrupt",
+        "Хэш пароля повреждён: {error}",
+        "The password hash is corrupted: {error}",
+    ),
+    (
+        "error.profile_not_object",
+        "профиль должен быть объектом",
+        "the profile must be an object",
+    ),
+    (
+        "error.profile_no_version",
+        "версия формата не указана",
+        "format version is missing",
+    ),
+    (
+        "error.profile_unknown_version",
+        "Неизвестная версия формата {version} (поддерживается до {supported})",
+        "Unknown format version {version} (supported up to {supported})",
+    ),
+    (
+        "error.profile_needs_passphrase",
+        "файл зашифрован, укажите парольную фразу",
+        "the file is encrypted, enter the passphrase",
+    ),
+    (
+        "error.profile_row_without_key",
+        "Запись {table} без поля {field}",
+        "{table} row without {field}",
+    ),
+    (
+        "error.profile_duplicate_key",
+        "повторяющийся ключ \"{key}\"",
+        "duplicate key \"{key}\"",
+    ),
+    (
+        "error.profile_invalid_date",
+        "некорректная дата \"{value}\"",
+        "invalid date \"{value}\"",
+    ),
+    (
+        "error.profile_missing_reference",
+        "ссылка на несуществующую запись {table} \"{id}\"",
+        "reference to a missing {table} row \"{id}\"",
+    ),
+    (
+        "error.profile_has_errors",
+        "Файл профиля содержит ошибки: {errors}",
+        "The profile file has errors: {errors}",
+    ),
+    (
+        "error.profile_more_errors",
+        " (и ещё {count})",
+        " (and {count} more)",
+    ),
+];
+
+pub fn tr_in(language: Language, key: &str) -> &str {
+    MESSAGES
+        .iter()
+        .find(|(k, _, _)| *k == key)
+        .map(|(_, ru, en)| match language {
+            Language::Ru => *ru,
+            Language::En => *en,
+        })
+        .unwrap_or(key)
+}
+
+pub fn tr(key: &str) -> &str {
+    tr_in(current(), key)
+}
+
+pub fn trf_in(language: Language, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter()
+        .fold(tr_in(language, key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+}
+
+pub fn trf(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    trf_in(current(), key, args)
+}
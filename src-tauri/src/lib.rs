@@ -1,15 +1,86 @@
-mod commands;
-mod db;
+pub mod backup;
+pub mod commands;
+pub mod crypto;
+mod dates;
+pub mod db;
+pub mod error;
 mod hotkeys;
+pub mod i18n;
+pub mod lock;
+pub mod pdf;
+pub mod scheduler;
+pub mod vault;
 
-use db::DbState;
-use std::sync::Mutex;
+use i18n::tr;
 use tauri::{
     menu::{Menu, MenuItem, PredefinedMenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Manager,
+    AppHandle, Emitter, Manager, Runtime,
 };
 
+const TRAY_ID: &str = "main";
+
+// Label for the running timer, if there is one. Reads behind the app lock
+// like the scheduler does, but while locked only says that a timer runs,
+// without the client.
+fn timer_label<R: Runtime, M: Manager<R>>(app: &M) -> Option<String> {
+    let state = app.try_state::<db::DbState>()?;
+    let conn = state.background_conn().ok()?;
+    let timer = commands::time_entries::running_timer(&conn, chrono::Utc::now()).ok()??;
+    if state.is_app_locked() {
+        return Some(tr("tray.timer_running_locked").to_string());
+    }
+    let started = chrono::DateTime::parse_from_rfc3339(&timer.entry.started_at).ok()?;
+    Some(i18n::trf(
+        "tray.timer_running",
+        &[
+            ("client", &timer.client_name),
+            ("time", &started.with_timezone(&chrono::Local).format("%H:%M")),
+        ],
+    ))
+}
+
+fn tray_menu<R: Runtime, M: Manager<R>>(app: &M) -> tauri::Result<Menu<R>> {
+    let show_i = MenuItem::with_id(app, "show", tr("tray.show"), true, None::<&str>)?;
+    let sep = PredefinedMenuItem::separator(app)?;
+    let quit_i = MenuItem::with_id(app, "quit", tr("tray.quit"), true, None::<&str>)?;
+    let Some(label) = timer_label(app) else {
+        return Menu::with_items(app, &[&show_i, &sep, &quit_i]);
+    };
+    let timer_i = MenuItem::with_id(app, "timer", label, false, None::<&str>)?;
+    let unlocked = app.try_state::<db::DbState>().is_some_and(|state| !state.is_app_locked());
+    let stop_i = MenuItem::with_id(app, "stop_timer", tr("tray.stop_timer"), unlocked, None::<&str>)?;
+    let timer_sep = PredefinedMenuItem::separator(app)?;
+    Menu::with_items(app, &[&show_i, &sep, &timer_i, &stop_i, &timer_sep, &quit_i])
+}
+
+fn stop_timer_from_tray(app: &AppHandle) {
+    let stopped = {
+        let state = app.state::<db::DbState>();
+        if state.is_app_locked() {
+            return;
+        }
+        let Ok(conn) = state.background_conn() else {
+            return;
+        };
+        commands::time_entries::stop(&conn, chrono::Utc::now())
+    };
+    if let Err(e) = stopped {
+        eprintln!("Failed to stop timer: {}", e);
+    }
+    refresh_tray(app);
+    let _ = app.emit("timer-changed", ());
+}
+
+// Menu items carry their labels, so a language switch swaps in a new menu.
+pub fn refresh_tray(app: &AppHandle) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        if let Err(e) = tray_menu(app).and_then(|menu| tray.set_menu(Some(menu))) {
+            eprintln!("Failed to rebuild tray menu: {}", e);
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -22,15 +93,22 @@ pub fn run() {
         .setup(|app| {
             let app_data_dir = app.path().app_data_dir()?;
             let db_path = app_data_dir.join("blueprint.db");
-            let conn = db::init_db(&db_path).map_err(|e| e.to_string())?;
-            app.manage(DbState(Mutex::new(conn)));
+            let state = db::init_db(&db_path).map_err(|e| e.to_string())?;
+            if let Ok(conn) = state.background_conn() {
+                i18n::load(&conn);
+            }
+            app.manage(state);
+            app.manage(vault::VaultState::new(&app_data_dir));
+            if let Err(e) = vault::prepare(app.handle()) {
+                eprintln!("Failed to prepare secret vault: {}", e);
+            }
+            lock::start(app.handle().clone());
+            backup::start_scheduler(app.handle().clone());
+            scheduler::start(app.handle().clone());
 
-            let show_i = MenuItem::with_id(app, "show", "Показать Blueprint", true, None::<&str>)?;
-            let sep = PredefinedMenuItem::separator(app)?;
-            let quit_i = MenuItem::with_id(app, "quit", "Выйти", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &sep, &quit_i])?;
+            let menu = tray_menu(app)?;
 
-            TrayIconBuilder::new()
+            TrayIconBuilder::with_id(TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
                 .tooltip("Blueprint")
@@ -42,6 +120,7 @@ pub fn run() {
                             let _ = w.set_focus();
                         }
                     }
+                    "stop_timer" => stop_timer_from_tray(app),
                     "quit" => app.exit(0),
                     _ => {}
                 })
@@ -56,6 +135,7 @@ pub fn run() {
                         if let Some(w) = app.get_webview_window("main") {
                             if w.is_visible().unwrap_or(false) {
                                 let _ = w.hide();
+                                lock::on_main_hidden(app);
                             } else {
                                 let _ = w.show();
                                 let _ = w.unminimize();
@@ -74,6 +154,7 @@ pub fn run() {
                         if let Some(w) = handle.get_webview_window("main") {
                             let _ = w.hide();
                         }
+                        lock::on_main_hidden(&handle);
                     }
                 });
             }
@@ -88,10 +169,42 @@ pub fn run() {
             commands::clients::delete_client,
             commands::clients::get_client_payments,
             commands::clients::toggle_payment,
+            commands::clients::record_payment_transaction,
+            commands::clients::delete_payment_transaction,
+            commands::schedule::get_client_schedule,
+            commands::time_entries::get_time_entries,
+            commands::time_entries::get_running_timer,
+            commands::time_entries::start_timer,
+            commands::time_entries::stop_timer,
+            commands::time_entries::add_time_entry,
+            commands::time_entries::delete_time_entry,
+            commands::time_entries::get_billable_hours,
             commands::services::get_services,
             commands::services::create_service,
             commands::services::update_service,
             commands::services::delete_service,
+            commands::services::snooze_service,
+            commands::services::acknowledge_service,
+            commands::services::clear_service_snooze,
+            commands::services::renew_service,
+            commands::services::get_service_renewals,
+            commands::charges::get_service_charges,
+            commands::charges::log_service_charge,
+            commands::charges::delete_service_charge,
+            commands::charges::get_spending_summary,
+            commands::dashboard::get_dashboard,
+            commands::invoices::get_invoices,
+            commands::invoices::create_invoice,
+            commands::invoices::mark_invoice_paid,
+            commands::invoices::delete_invoice,
+            commands::invoices::render_invoice_html,
+            commands::invoices::render_invoice_pdf,
+            commands::fx::get_fx_rates,
+            commands::fx::set_fx_rate,
+            commands::fx::delete_fx_rate,
+            commands::fx::import_fx_rates,
+            commands::fx::convert_amount,
+            commands::fx::get_currencies,
             commands::notes::get_notes,
             commands::notes::create_note,
             commands::notes::update_note,
@@ -106,15 +219,16 @@ pub fn run() {
             commands::ai::transcribe_audio,
             commands::settings::get_settings,
             commands::settings::save_setting,
-            commands::settings::get_ai_key,
             commands::settings::save_ai_key,
-            commands::settings::get_groq_key,
             commands::settings::save_groq_key,
             commands::settings::get_chat_history,
             commands::settings::save_chat_message,
             commands::settings::clear_chat_history,
-            commands::settings::export_profile,
-            commands::settings::import_profile,
+            commands::profile::export_profile,
+            commands::profile::export_profile_encrypted,
+            commands::profile::import_profile,
+            commands::profile::preview_import,
+            commands::profile::get_profile_schema,
             hotkeys::toggle_overlay_window,
             hotkeys::update_hotkeys,
             hotkeys::pause_hotkeys,
@@ -124,8 +238,33 @@ pub fn run() {
             hotkeys::hide_voice_indicator,
             hotkeys::emit_voice_message_saved,
             commands::notifications::check_and_notify,
+            commands::notifications::get_notification_history,
+            commands::notifications::mark_notification_read,
+            commands::notifications::get_notification_rules,
+            commands::notifications::set_notification_rule,
             commands::notifications::toggle_autostart,
             commands::notifications::get_autostart_enabled,
+            backup::list_backups,
+            backup::create_backup,
+            backup::restore_backup,
+            commands::database::get_database_status,
+            commands::database::unlock_database,
+            commands::database::enable_database_encryption,
+            commands::database::disable_database_encryption,
+            commands::database::change_database_passphrase,
+            lock::get_app_lock_status,
+            lock::set_app_lock,
+            lock::disable_app_lock,
+            lock::lock_app,
+            lock::unlock_app,
+            lock::record_activity,
+            vault::get_secret_status,
+            vault::set_secret,
+            vault::delete_secret,
+            vault::reveal_secret,
+            vault::get_service_secrets,
+            vault::set_service_secret,
+            vault::reveal_service_secret,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
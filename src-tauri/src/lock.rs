@@ -0,0 +1,215 @@
+use crate::db::{self, DbState};
+use crate::error::BlueprintError;
+use crate::i18n::{tr, trf};
+use anyhow::{anyhow, bail, Result};
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Serialize)]
+pub struct AppLockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub idle_minutes: u64,
+    pub lock_on_hide: bool,
+}
+
+pub struct AppLock {
+    last_activity: Mutex<Instant>,
+}
+
+impl Default for AppLock {
+    fn default() -> Self {
+        AppLock {
+            last_activity: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl AppLock {
+    pub fn touch(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_activity
+            .lock()
+            .map(|last| last.elapsed())
+            .unwrap_or_default()
+    }
+}
+
+fn stored_hash(conn: &Connection) -> Result<Option<String>> {
+    Ok(conn
+        .query_row("SELECT hash FROM app_lock WHERE id = 1", [], |row| row.get(0))
+        .optional()?)
+}
+
+pub fn is_enabled(conn: &Connection) -> bool {
+    stored_hash(conn).ok().flatten().is_some()
+}
+
+pub fn set_passphrase(conn: &Connection, passphrase: &str) -> Result<()> {
+    if passphrase.chars().count() < 4 {
+        bail!(BlueprintError::validation(tr("error.pin_too_short")));
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map_err(|e| anyhow!(trf("error.pin_save_failed", &[("error", &e)])))?
+        .to_string();
+    conn.execute(
+        "INSERT INTO app_lock (id, hash, updated_at) VALUES (1, ?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET hash = excluded.hash, updated_at = excluded.updated_at",
+        params![hash, Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+pub fn verify_passphrase(conn: &Connection, passphrase: &str) -> Result<()> {
+    let hash = stored_hash(conn)?.ok_or_else(not_enabled)?;
+    let parsed = PasswordHash::new(&hash).map_err(|e| anyhow!(trf("error.pin_hash_corrupt", &[("error", &e)])))?;
+    Argon2::default()
+        .verify_password(passphrase.as_bytes(), &parsed)
+        .map_err(|_| BlueprintError::InvalidPassphrase(tr("error.wrong_pin").to_string()).into())
+}
+
+fn not_enabled() -> BlueprintError {
+    BlueprintError::validation(tr("error.app_lock_disabled"))
+}
+
+pub fn clear_passphrase(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM app_lock", [])?;
+    Ok(())
+}
+
+fn idle_limit(conn: &Connection) -> Option<Duration> {
+    db::get_setting(conn, "app_lock_idle_minutes")
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|m| *m > 0)
+        .map(|m| Duration::from_secs(m * 60))
+}
+
+fn status(db: &DbState, conn: &Connection) -> AppLockStatus {
+    AppLockStatus {
+        enabled: is_enabled(conn),
+        locked: db.is_app_locked(),
+        idle_minutes: db::get_setting(conn, "app_lock_idle_minutes")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0),
+        lock_on_hide: db::get_setting(conn, "app_lock_on_hide").as_deref() != Some("false"),
+    }
+}
+
+pub fn lock(app: &AppHandle) {
+    let db = app.state::<DbState>();
+    if db.is_app_locked() {
+        return;
+    }
+    db.set_app_locked(true);
+    if let Some(overlay) = app.get_webview_window("overlay") {
+        let _ = overlay.hide();
+    }
+    crate::refresh_tray(app);
+    let _ = app.emit("app-locked", ());
+}
+
+fn lock_if_enabled(app: &AppHandle, condition: impl Fn(&Connection) -> bool) {
+    let db = app.state::<DbState>();
+    let should_lock = match db.background_conn() {
+        Ok(conn) => is_enabled(&conn) && condition(&conn),
+        Err(_) => false,
+    };
+    if should_lock {
+        lock(app);
+    }
+}
+
+pub fn on_main_hidden(app: &AppHandle) {
+    lock_if_enabled(app, |conn| {
+        db::get_setting(conn, "app_lock_on_hide").as_deref() != Some("false")
+    });
+}
+
+// Starts locked when a passphrase is set and watches for inactivity; the
+// webview reports activity through `record_activity`.
+pub fn start(app: AppHandle) {
+    app.manage(AppLock::default());
+    lock_if_enabled(&app, |_| true);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            let idle = app.state::<AppLock>().idle_for();
+            lock_if_enabled(&app, |conn| idle_limit(conn).is_some_and(|limit| idle >= limit));
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_app_lock_status(state: State<DbState>) -> Result<AppLockStatus, BlueprintError> {
+    let conn = state.background_conn()?;
+    Ok(status(&state, &conn))
+}
+
+#[tauri::command]
+pub fn set_app_lock(
+    passphrase: String,
+    current: Option<String>,
+    state: State<DbState>,
+) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    if is_enabled(&conn) {
+        verify_passphrase(&conn, current.as_deref().unwrap_or_default())?;
+    }
+    Ok(set_passphrase(&conn, &passphrase)?)
+}
+
+#[tauri::command]
+pub fn disable_app_lock(passphrase: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    verify_passphrase(&conn, &passphrase)?;
+    Ok(clear_passphrase(&conn)?)
+}
+
+#[tauri::command]
+pub fn lock_app(state: State<DbState>, app: AppHandle) -> Result<(), BlueprintError> {
+    let conn = state.background_conn()?;
+    if !is_enabled(&conn) {
+        return Err(not_enabled());
+    }
+    drop(conn);
+    lock(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unlock_app(
+    passphrase: String,
+    state: State<DbState>,
+    lock: State<AppLock>,
+    app: AppHandle,
+) -> Result<(), BlueprintError> {
+    let conn = state.background_conn()?;
+    verify_passphrase(&conn, &passphrase)?;
+    drop(conn);
+    state.set_app_locked(false);
+    lock.touch();
+    crate::refresh_tray(&app);
+    let _ = app.emit("app-unlocked", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn record_activity(lock: State<AppLock>) {
+    lock.touch();
+}
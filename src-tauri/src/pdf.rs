@@ -0,0 +1,424 @@
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+pub const A4_WIDTH: f32 = 595.0;
+pub const A4_HEIGHT: f32 = 842.0;
+
+// Helvetica advance widths for 32..=126 from the standard AFM, in 1/1000 em.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+// Fonts tried in order when none is configured. Only plain TrueType files
+// (not collections) with Cyrillic coverage.
+pub const SYSTEM_FONTS: &[&str] = &[
+    "C:\\Windows\\Fonts\\arial.ttf",
+    "C:\\Windows\\Fonts\\segoeui.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+];
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn i16_at(data: &[u8], at: usize) -> Option<i16> {
+    u16_at(data, at).map(|v| v as i16)
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+// Just enough of a TrueType font to embed it whole: the character map,
+// advance widths and the metrics the font descriptor needs.
+pub struct TrueTypeFont {
+    data: Vec<u8>,
+    units_per_em: u16,
+    bbox: [i16; 4],
+    ascent: i16,
+    descent: i16,
+    advances: Vec<u16>,
+    cmap: BTreeMap<char, u16>,
+}
+
+impl TrueTypeFont {
+    pub fn parse(data: Vec<u8>) -> Option<TrueTypeFont> {
+        let num_tables = u16_at(&data, 4)? as usize;
+        let mut tables = BTreeMap::new();
+        for i in 0..num_tables {
+            let record = 12 + 16 * i;
+            let tag = data.get(record..record + 4)?.to_vec();
+            let offset = u32_at(&data, record + 8)? as usize;
+            tables.insert(tag, offset);
+        }
+        let table = |tag: &[u8]| tables.get(tag).copied();
+        // CFF outlines would need a different embedding.
+        table(b"glyf")?;
+        let head = table(b"head")?;
+        let hhea = table(b"hhea")?;
+        let hmtx = table(b"hmtx")?;
+        let maxp = table(b"maxp")?;
+        let cmap = table(b"cmap")?;
+
+        let units_per_em = u16_at(&data, head + 18)?.max(1);
+        let bbox = [
+            i16_at(&data, head + 36)?,
+            i16_at(&data, head + 38)?,
+            i16_at(&data, head + 40)?,
+            i16_at(&data, head + 42)?,
+        ];
+        let ascent = i16_at(&data, hhea + 4)?;
+        let descent = i16_at(&data, hhea + 6)?;
+        let metrics = u16_at(&data, hhea + 34)? as usize;
+        let glyphs = u16_at(&data, maxp + 4)? as usize;
+        let mut advances = Vec::with_capacity(glyphs);
+        for i in 0..glyphs {
+            let advance = match u16_at(&data, hmtx + 4 * i.min(metrics.saturating_sub(1))) {
+                Some(advance) => advance,
+                None => break,
+            };
+            advances.push(advance);
+        }
+        let cmap = parse_cmap(&data, cmap)?;
+        Some(TrueTypeFont {
+            data,
+            units_per_em,
+            bbox,
+            ascent,
+            descent,
+            advances,
+            cmap,
+        })
+    }
+
+    pub fn load(path: &Path) -> Option<TrueTypeFont> {
+        TrueTypeFont::parse(std::fs::read(path).ok()?)
+    }
+
+    pub fn covers(&self, text: &str) -> bool {
+        text.chars().all(|c| c.is_control() || self.cmap.contains_key(&c))
+    }
+
+    fn glyph(&self, c: char) -> u16 {
+        self.cmap.get(&c).copied().unwrap_or(0)
+    }
+
+    fn advance(&self, glyph: u16) -> f32 {
+        let units = self
+            .advances
+            .get(glyph as usize)
+            .or(self.advances.last())
+            .copied()
+            .unwrap_or(0);
+        units as f32 * 1000.0 / self.units_per_em as f32
+    }
+
+    fn scale(&self, value: i16) -> i32 {
+        (value as f32 * 1000.0 / self.units_per_em as f32).round() as i32
+    }
+}
+
+fn parse_cmap(data: &[u8], cmap: usize) -> Option<BTreeMap<char, u16>> {
+    let count = u16_at(data, cmap + 2)? as usize;
+    let mut best: Option<(u8, usize)> = None;
+    for i in 0..count {
+        let record = cmap + 4 + 8 * i;
+        let platform = u16_at(data, record)?;
+        let encoding = u16_at(data, record + 2)?;
+        let offset = cmap + u32_at(data, record + 4)? as usize;
+        let rank = match (platform, encoding, u16_at(data, offset)?) {
+            (3, 10, 12) | (0, _, 12) => 2,
+            (3, 1, 4) | (0, _, 4) => 1,
+            _ => continue,
+        };
+        if best.is_none_or(|(r, _)| rank > r) {
+            best = Some((rank, offset));
+        }
+    }
+    let (_, table) = best?;
+    let mut map = BTreeMap::new();
+    if u16_at(data, table)? == 12 {
+        let groups = u32_at(data, table + 12)? as usize;
+        for g in 0..groups {
+            let at = table + 16 + 12 * g;
+            let (start, end, glyph) = (u32_at(data, at)?, u32_at(data, at + 4)?, u32_at(data, at + 8)?);
+            // Ranges and glyph ids come straight from the file, so nothing
+            // here may overflow on a malformed one.
+            for code in start..=end.min(start.saturating_add(0xFFFF)) {
+                let glyph = glyph.checked_add(code - start).and_then(|g| u16::try_from(g).ok());
+                if let (Some(c), Some(glyph)) = (char::from_u32(code), glyph) {
+                    map.insert(c, glyph);
+                }
+            }
+        }
+        return Some(map);
+    }
+    let segments = u16_at(data, table + 6)? as usize / 2;
+    let ends = table + 14;
+    let starts = ends + 2 * segments + 2;
+    let deltas = starts + 2 * segments;
+    let range_offsets = deltas + 2 * segments;
+    for s in 0..segments {
+        let end = u16_at(data, ends + 2 * s)?;
+        let start = u16_at(data, starts + 2 * s)?;
+        let delta = u16_at(data, deltas + 2 * s)?;
+        let range_at = range_offsets + 2 * s;
+        let range_offset = u16_at(data, range_at)? as usize;
+        if start > end {
+            continue;
+        }
+        for code in start..=end {
+            if code == 0xFFFF {
+                break;
+            }
+            let glyph = if range_offset == 0 {
+                code.wrapping_add(delta)
+            } else {
+                let at = range_at + range_offset + 2 * (code - start) as usize;
+                match u16_at(data, at)? {
+                    0 => 0,
+                    g => g.wrapping_add(delta),
+                }
+            };
+            if let (Some(c), true) = (char::from_u32(code as u32), glyph != 0) {
+                map.insert(c, glyph);
+            }
+        }
+    }
+    Some(map)
+}
+
+enum Font {
+    // Built into every viewer, but only covers WinAnsi (no Cyrillic).
+    Helvetica,
+    Embedded(TrueTypeFont),
+}
+
+// A4 pages of positioned text and rules, written with a single font.
+// Coordinates are in points from the bottom-left corner.
+pub struct PdfDocument {
+    font: Font,
+    pages: Vec<String>,
+    used: BTreeMap<u16, char>,
+}
+
+impl PdfDocument {
+    pub fn new(font: Option<TrueTypeFont>) -> PdfDocument {
+        PdfDocument {
+            font: font.map(Font::Embedded).unwrap_or(Font::Helvetica),
+            pages: vec![String::new()],
+            used: BTreeMap::new(),
+        }
+    }
+
+    pub fn embeds_font(&self) -> bool {
+        matches!(self.font, Font::Embedded(_))
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+    }
+
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let units: f32 = match &self.font {
+            Font::Helvetica => text
+                .chars()
+                .map(|c| match c as u32 {
+                    code @ 32..=126 => HELVETICA_WIDTHS[code as usize - 32] as f32,
+                    _ => 556.0,
+                })
+                .sum(),
+            Font::Embedded(font) => text.chars().map(|c| font.advance(font.glyph(c))).sum(),
+        };
+        units * size / 1000.0
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        let encoded: String = match &self.font {
+            Font::Helvetica => text
+                .chars()
+                .map(|c| match c as u32 {
+                    32..=126 | 0xA0..=0xFF => c as u32 as u8,
+                    _ => b'?',
+                })
+                .map(|b| format!("{:02X}", b))
+                .collect(),
+            Font::Embedded(font) => text
+                .chars()
+                .filter(|c| !c.is_control())
+                .map(|c| {
+                    let glyph = font.glyph(c);
+                    self.used.entry(glyph).or_insert(c);
+                    format!("{:04X}", glyph)
+                })
+                .collect(),
+        };
+        let page = self.pages.last_mut().expect("document has a page");
+        page.push_str(&format!("BT /F1 {:.1} Tf {:.2} {:.2} Td <{}> Tj ET\n", size, x, y, encoded));
+    }
+
+    pub fn text_right(&mut self, right: f32, y: f32, size: f32, text: &str) {
+        let width = self.text_width(text, size);
+        self.text(right - width, y, size, text);
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let page = self.pages.last_mut().expect("document has a page");
+        page.push_str(&format!("0.5 w {:.2} {:.2} m {:.2} {:.2} l S\n", x1, y1, x2, y2));
+    }
+
+    pub fn render(self) -> Vec<u8> {
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        // 1: catalog, 2: page tree, 3: font; everything else follows.
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        objects.push(Vec::new());
+        objects.push(Vec::new());
+
+        objects[2] = match &self.font {
+            Font::Helvetica => {
+                b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                    .to_vec()
+            }
+            Font::Embedded(font) => {
+                let file = objects.len() + 1;
+                let mut header = format!("<< /Length1 {}", font.data.len());
+                objects.push(stream(&mut header, &font.data));
+                let descriptor = objects.len() + 1;
+                objects.push(
+                    format!(
+                        "<< /Type /FontDescriptor /FontName /Embedded /Flags 32 \
+                         /FontBBox [{} {} {} {}] /ItalicAngle 0 /Ascent {} /Descent {} \
+                         /CapHeight {} /StemV 80 /FontFile2 {} 0 R >>",
+                        font.scale(font.bbox[0]),
+                        font.scale(font.bbox[1]),
+                        font.scale(font.bbox[2]),
+                        font.scale(font.bbox[3]),
+                        font.scale(font.ascent),
+                        font.scale(font.descent),
+                        font.scale(font.ascent),
+                        file
+                    )
+                    .into_bytes(),
+                );
+                let widths: String = self
+                    .used
+                    .keys()
+                    .map(|g| format!("{} [{:.0}] ", g, font.advance(*g)))
+                    .collect();
+                let cid = objects.len() + 1;
+                objects.push(
+                    format!(
+                        "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /Embedded \
+                         /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                         /FontDescriptor {} 0 R /CIDToGIDMap /Identity /W [{}] >>",
+                        descriptor, widths
+                    )
+                    .into_bytes(),
+                );
+                let to_unicode = objects.len() + 1;
+                objects.push(stream(&mut String::from("<<"), to_unicode_cmap(&self.used).as_bytes()));
+                format!(
+                    "<< /Type /Font /Subtype /Type0 /BaseFont /Embedded /Encoding /Identity-H \
+                     /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+                    cid, to_unicode
+                )
+                .into_bytes()
+            }
+        };
+
+        let mut kids = Vec::new();
+        for content in &self.pages {
+            let content_id = objects.len() + 1;
+            objects.push(stream(&mut String::from("<<"), content.as_bytes()));
+            kids.push(objects.len() + 1);
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                    A4_WIDTH, A4_HEIGHT, content_id
+                )
+                .into_bytes(),
+            );
+        }
+        let kids_refs: Vec<String> = kids.iter().map(|k| format!("{} 0 R", k)).collect();
+        objects[1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids_refs.join(" "),
+            kids.len()
+        )
+        .into_bytes();
+
+        let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(object);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+        let xref = out.len();
+        out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
+
+// `header` is an open dictionary; length and filter are appended here.
+fn stream(header: &mut String, data: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .unwrap_or_default();
+    header.push_str(&format!(" /Length {} /Filter /FlateDecode >>\nstream\n", compressed.len()));
+    let mut out = header.clone().into_bytes();
+    out.extend_from_slice(&compressed);
+    out.extend_from_slice(b"\nendstream");
+    out
+}
+
+fn to_unicode_cmap(used: &BTreeMap<u16, char>) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin 12 dict begin begincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def /CMapType 2 def\n\
+         1 begincodespacerange <0000> <FFFF> endcodespacerange\n",
+    );
+    let entries: Vec<_> = used.iter().collect();
+    for chunk in entries.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (glyph, c) in chunk {
+            let utf16: String = c
+                .encode_utf16(&mut [0; 2])
+                .iter()
+                .map(|u| format!("{:04X}", u))
+                .collect();
+            cmap.push_str(&format!("<{:04X}> <{}>\n", glyph, utf16));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap CMapName currentdict /CMap defineresource pop end end\n");
+    cmap
+}
@@ -0,0 +1,494 @@
+use crate::commands::clients::Client;
+use crate::commands::schedule::{BillingTerms, Period};
+use crate::commands::services;
+use crate::db::{self, DbState};
+use crate::i18n::trf;
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveTime, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use uuid::Uuid;
+
+const TICK: Duration = Duration::from_secs(60);
+// A tick that arrives this much later than planned means the machine slept.
+const WAKE_SLACK: Duration = Duration::from_secs(120);
+// Registered by the webview with snooze/acknowledge buttons; platforms
+// without notification actions ignore it.
+pub const SERVICE_ACTIONS: &str = "service-reminder";
+pub const DEFAULT_PAYMENT_NOTIFY_DAYS: i64 = 3;
+
+// `kind`, `entity_id`, `due` and `threshold` identify a reminder in
+// `notification_log`; `due` is part of it so a renewed service or a new
+// payment period starts over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub kind: &'static str,
+    pub entity_id: String,
+    pub due: String,
+    pub threshold: String,
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationEntry {
+    pub id: String,
+    pub kind: String,
+    pub entity_id: String,
+    pub due: String,
+    pub threshold: String,
+    pub title: String,
+    pub body: String,
+    pub sent_at: String,
+    pub read_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn from_settings(conn: &Connection) -> Option<QuietHours> {
+        let parse = |key| {
+            db::get_setting(conn, key)
+                .and_then(|v| NaiveTime::parse_from_str(v.trim(), "%H:%M").ok())
+        };
+        let (start, end) = (parse("quiet_hours_start")?, parse("quiet_hours_end")?);
+        (start != end).then_some(QuietHours { start, end })
+    }
+
+    // The window may cross midnight, e.g. 22:00–08:00.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+pub const DEFAULT_THRESHOLDS: &[i64] = &[30, 7, 1, 0];
+
+// "30, 7,1,0" -> [30, 7, 1, 0]. Rejects negatives and garbage so a typo does
+// not silently turn reminders off.
+pub fn parse_thresholds(value: &str) -> Option<Vec<i64>> {
+    let mut thresholds = value
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<i64>().ok().filter(|days| *days >= 0))
+        .collect::<Option<Vec<_>>>()?;
+    thresholds.sort_unstable_by(|a, b| b.cmp(a));
+    thresholds.dedup();
+    (!thresholds.is_empty()).then_some(thresholds)
+}
+
+pub fn format_thresholds(thresholds: &[i64]) -> String {
+    thresholds
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// The threshold a reminder belongs to: the closest one already reached. A
+// machine that was off for a while reports only the latest, not every
+// threshold it missed.
+pub fn reached_threshold(thresholds: &[i64], days: i64) -> Option<i64> {
+    thresholds.iter().copied().filter(|t| days <= *t).min()
+}
+
+// A service's own rule wins over its category's, which wins over the global
+// `notify_days_before` setting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotificationRules {
+    pub default: Vec<i64>,
+    pub categories: HashMap<String, Vec<i64>>,
+}
+
+impl NotificationRules {
+    pub fn load(conn: &Connection) -> Result<NotificationRules> {
+        let default = db::get_setting(conn, "notify_days_before")
+            .and_then(|v| parse_thresholds(&v))
+            .unwrap_or_else(|| DEFAULT_THRESHOLDS.to_vec());
+        let mut stmt = conn.prepare("SELECT category, thresholds FROM notification_rules")?;
+        let categories = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .filter_map(|r| r.ok())
+            .filter_map(|(category, value)| Some((category, parse_thresholds(&value)?)))
+            .collect();
+        Ok(NotificationRules {
+            default,
+            categories,
+        })
+    }
+
+    pub fn thresholds_for(&self, own: Option<&str>, category: Option<&str>) -> Vec<i64> {
+        own.and_then(parse_thresholds)
+            .or_else(|| category.and_then(|c| self.categories.get(c).cloned()))
+            .unwrap_or_else(|| self.default.clone())
+    }
+}
+
+pub fn days_remaining(expires_at: &str, today: NaiveDate) -> Option<i64> {
+    NaiveDate::parse_from_str(expires_at, "%Y-%m-%d")
+        .ok()
+        .map(|exp| (exp - today).num_days())
+}
+
+// Day `payment_day` of the given month, pulled back to the last day when the
+// month is shorter (31 -> 28 in February).
+pub fn due_date(year: i32, month: u32, payment_day: u32) -> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = first.checked_add_months(chrono::Months::new(1))?;
+    let last_day = (next - ChronoDuration::days(1)).day();
+    NaiveDate::from_ymd_opt(year, month, payment_day.clamp(1, last_day))
+}
+
+pub fn service_reminders(conn: &Connection, today: NaiveDate) -> Result<Vec<Reminder>> {
+    let rules = NotificationRules::load(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, service_name, project_name, expires_at, notify_thresholds, category
+         FROM services
+         WHERE (snoozed_until IS NULL OR snoozed_until <= ?1)
+           AND (acknowledged_expires_at IS NULL OR acknowledged_expires_at != expires_at)",
+    )?;
+    let rows = stmt
+        .query_map(params![today.format("%Y-%m-%d").to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })?
+        .filter_map(|r| r.ok());
+
+    let mut reminders = Vec::new();
+    for (id, name, project, expires_at, own, category) in rows {
+        let Some(days) = days_remaining(&expires_at, today) else {
+            continue;
+        };
+        let (threshold, title) = if days < 0 {
+            ("expired".to_string(), trf("notify.expired", &[("name", &name)]))
+        } else {
+            let thresholds = rules.thresholds_for(own.as_deref(), category.as_deref());
+            let Some(threshold) = reached_threshold(&thresholds, days) else {
+                continue;
+            };
+            let title = if days == 0 {
+                trf("notify.expires_today", &[("name", &name)])
+            } else {
+                trf("notify.expires_in", &[("days", &days), ("name", &name)])
+            };
+            (threshold.to_string(), title)
+        };
+        reminders.push(Reminder {
+            kind: "service",
+            entity_id: id,
+            due: expires_at.clone(),
+            threshold,
+            title,
+            body: format!("{} · {}", project, expires_at),
+        });
+    }
+    Ok(reminders)
+}
+
+fn period_paid(conn: &Connection, client_id: &str, period: &str) -> Result<bool> {
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM client_payments WHERE client_id = ?1 AND period = ?2 AND paid = 1)",
+        params![client_id, period],
+        |row| row.get(0),
+    )?)
+}
+
+// Looks at the previous and the current billing period so a payment that
+// slipped past the period boundary is still reported as overdue. Each period
+// notifies up to three times: `lead` days ahead, on the due date and once it
+// is overdue.
+pub fn payment_reminders(conn: &Connection, today: NaiveDate) -> Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM clients WHERE payment_type != 'onetime'",
+        Client::COLUMNS
+    ))?;
+    let clients = stmt
+        .query_map([], Client::from_row)?
+        .filter_map(|r| r.ok())
+        .collect::<Vec<_>>();
+
+    let mut reminders = Vec::new();
+    for client in clients {
+        let Client { id, name, amount, currency, .. } = &client;
+        let lead = client.payment_notify_days.map_or(DEFAULT_PAYMENT_NOTIFY_DAYS, i64::from);
+        let terms = BillingTerms::of(&client);
+        let Some(kind) = terms.kind() else {
+            continue;
+        };
+        let current = Period::containing(kind, today);
+        for period in [current.prev(), Some(current)].iter().flatten() {
+            let Some(due) = terms.due(period) else {
+                continue;
+            };
+            if !terms.expects(due) {
+                continue;
+            }
+            let period = period.key();
+            let days = (due - today).num_days();
+            let (threshold, title) = if days < 0 {
+                ("overdue", trf("notify.payment_overdue", &[("name", &name)]))
+            } else if days == 0 {
+                ("due", trf("notify.payment_due", &[("name", &name)]))
+            } else if days <= lead {
+                (
+                    "before",
+                    trf("notify.payment_upcoming", &[("days", &days), ("name", &name)]),
+                )
+            } else {
+                continue;
+            };
+            if period_paid(conn, id, &period)? {
+                continue;
+            }
+            let body = match amount {
+                Some(amount) => format!("{} {} · {}", amount, currency, period),
+                None => period.clone(),
+            };
+            reminders.push(Reminder {
+                kind: "payment",
+                entity_id: id.clone(),
+                due: period,
+                threshold: threshold.to_string(),
+                title,
+                body,
+            });
+        }
+    }
+    Ok(reminders)
+}
+
+// Notes fire once, on the first pass after their `remind_at` has passed.
+pub fn note_reminders(
+    conn: &Connection,
+    since: DateTime<Local>,
+    now: DateTime<Local>,
+) -> Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, content, remind_at FROM notes
+         WHERE completed = 0 AND remind_at IS NOT NULL",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .filter_map(|r| r.ok());
+
+    let mut reminders = Vec::new();
+    for (id, title, content, remind_at) in rows {
+        let Ok(at) = DateTime::parse_from_rfc3339(&remind_at) else {
+            continue;
+        };
+        if at <= since || at > now {
+            continue;
+        }
+        reminders.push(Reminder {
+            kind: "note",
+            entity_id: id,
+            due: remind_at,
+            threshold: "at".to_string(),
+            title: trf("notify.note_reminder", &[("title", &title)]),
+            body: content
+                .as_deref()
+                .and_then(|c| c.lines().next())
+                .unwrap_or_default()
+                .to_string(),
+        });
+    }
+    Ok(reminders)
+}
+
+pub fn collect(
+    conn: &Connection,
+    since: DateTime<Local>,
+    now: DateTime<Local>,
+) -> Result<Vec<Reminder>> {
+    let today = now.date_naive();
+    let mut reminders = service_reminders(conn, today)?;
+    reminders.extend(payment_reminders(conn, today)?);
+    reminders.extend(note_reminders(conn, since, now)?);
+    Ok(reminders)
+}
+
+fn interval(conn: &Connection) -> Duration {
+    let minutes = db::get_setting(conn, "scheduler_interval_minutes")
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|m| *m > 0)
+        .unwrap_or(15);
+    Duration::from_secs(minutes * 60)
+}
+
+fn last_run(conn: &Connection) -> Option<DateTime<Local>> {
+    db::get_setting(conn, "scheduler_last_run")
+        .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+        .map(|t| t.with_timezone(&Local))
+}
+
+// Logs the reminder unless the same threshold was already sent; returns the
+// new entry only when it should be shown.
+pub fn record(conn: &Connection, reminder: &Reminder) -> Result<Option<NotificationEntry>> {
+    let entry = NotificationEntry {
+        id: Uuid::new_v4().to_string(),
+        kind: reminder.kind.to_string(),
+        entity_id: reminder.entity_id.clone(),
+        due: reminder.due.clone(),
+        threshold: reminder.threshold.clone(),
+        title: reminder.title.clone(),
+        body: reminder.body.clone(),
+        sent_at: Utc::now().to_rfc3339(),
+        read_at: None,
+    };
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO notification_log
+         (id, kind, entity_id, due, threshold, title, body, sent_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            entry.id,
+            entry.kind,
+            entry.entity_id,
+            entry.due,
+            entry.threshold,
+            entry.title,
+            entry.body,
+            entry.sent_at
+        ],
+    )?;
+    Ok((inserted > 0).then_some(entry))
+}
+
+pub fn history(conn: &Connection, limit: i64, unread_only: bool) -> Result<Vec<NotificationEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, entity_id, due, threshold, title, body, sent_at, read_at
+         FROM notification_log
+         WHERE ?1 = 0 OR read_at IS NULL
+         ORDER BY sent_at DESC
+         LIMIT ?2",
+    )?;
+    let entries = stmt
+        .query_map(params![unread_only, limit], |row| {
+            Ok(NotificationEntry {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                entity_id: row.get(2)?,
+                due: row.get(3)?,
+                threshold: row.get(4)?,
+                title: row.get(5)?,
+                body: row.get(6)?,
+                sent_at: row.get(7)?,
+                read_at: row.get(8)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(entries)
+}
+
+pub fn mark_read(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE notification_log SET read_at = ?1 WHERE id = ?2 AND read_at IS NULL",
+        params![Utc::now().to_rfc3339(), id],
+    )?;
+    Ok(())
+}
+
+// Auto-renewals always run; quiet hours only postpone the notifications.
+// `scheduler_last_run` stays put then, so anything that came due in the
+// meantime fires once they end.
+pub fn run_pass(app: &AppHandle) -> Result<()> {
+    let state = app.state::<DbState>();
+    if state.is_locked() {
+        return Ok(());
+    }
+    let now = Local::now();
+    let renewed = services::roll_forward(&mut *state.background_conn()?, now.date_naive())?;
+    if !renewed.is_empty() {
+        let _ = app.emit("services-renewed", renewed.len());
+    }
+
+    let sent = {
+        let conn = state.background_conn()?;
+        if QuietHours::from_settings(&conn).is_some_and(|q| q.contains(now.time())) {
+            return Ok(());
+        }
+        let since = last_run(&conn).unwrap_or(now - ChronoDuration::days(1));
+        let mut sent = Vec::new();
+        for reminder in collect(&conn, since, now)? {
+            if let Some(entry) = record(&conn, &reminder)? {
+                sent.push(entry);
+            }
+        }
+        db::set_setting(&conn, "scheduler_last_run", &now.to_rfc3339())?;
+        sent
+    };
+
+    if sent.is_empty() {
+        return Ok(());
+    }
+    for entry in &sent {
+        let mut notification = app
+            .notification()
+            .builder()
+            .title(&entry.title)
+            .body(&entry.body);
+        if entry.kind == "service" {
+            notification = notification
+                .action_type_id(SERVICE_ACTIONS)
+                .extra("service_id", &entry.entity_id);
+        }
+        let _ = notification.show();
+    }
+    let _ = app.emit("notifications-logged", sent.len());
+    Ok(())
+}
+
+fn current_interval(app: &AppHandle) -> Duration {
+    app.state::<DbState>()
+        .background_conn()
+        .map(|conn| interval(&conn))
+        .unwrap_or(Duration::from_secs(15 * 60))
+}
+
+// Wakes every minute; runs a pass when the configured interval has elapsed or
+// when the wall clock jumped, which is how a resume from sleep shows up.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_pass: Option<Instant> = None;
+        let mut last_tick = Local::now();
+        loop {
+            let now = Local::now();
+            let woke = (now - last_tick)
+                .to_std()
+                .is_ok_and(|gap| gap > TICK + WAKE_SLACK);
+            let due = last_pass.is_none_or(|t| t.elapsed() >= current_interval(&app));
+            if woke || due {
+                if let Err(e) = run_pass(&app) {
+                    eprintln!("Scheduled check failed: {}", e);
+                }
+                last_pass = Some(Instant::now());
+            }
+            last_tick = now;
+            tokio::time::sleep(TICK).await;
+        }
+    });
+}
@@ -0,0 +1,400 @@
+use crate::crypto::{self, KEY_LEN};
+use crate::db::{self, DbState};
+use crate::error::BlueprintError;
+use crate::i18n::{tr, trf};
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const KEYRING_SERVICE: &str = "blueprint";
+const KEYRING_USER: &str = "vault-key";
+const KEY_FILE: &str = "vault.key";
+const SERVICE_PREFIX: &str = "service:";
+
+pub const APP_SECRETS: &[&str] = &["ai_api_key", "groq_api_key"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceSecretKind {
+    Password,
+    Token,
+}
+
+impl ServiceSecretKind {
+    const ALL: [ServiceSecretKind; 2] = [ServiceSecretKind::Password, ServiceSecretKind::Token];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ServiceSecretKind::Password => "password",
+            ServiceSecretKind::Token => "token",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SecretValue {
+    pub name: String,
+    pub value: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecretStatus {
+    pub name: String,
+    pub configured: bool,
+    pub hint: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevealedSecret {
+    pub name: String,
+    pub value: String,
+    pub expires_in_secs: u64,
+}
+
+pub fn service_secret_name(service_id: &str, kind: ServiceSecretKind) -> String {
+    format!("{}{}:{}", SERVICE_PREFIX, service_id, kind.as_str())
+}
+
+pub fn service_id_of(name: &str) -> Option<&str> {
+    let (id, kind) = name.strip_prefix(SERVICE_PREFIX)?.rsplit_once(':')?;
+    ServiceSecretKind::ALL
+        .iter()
+        .any(|k| k.as_str() == kind)
+        .then_some(id)
+        .filter(|id| !id.is_empty())
+}
+
+pub fn check_name(name: &str) -> Result<()> {
+    if APP_SECRETS.contains(&name) || service_id_of(name).is_some() {
+        Ok(())
+    } else {
+        bail!(BlueprintError::validation(trf(
+            "error.unknown_secret",
+            &[("name", &name)]
+        )))
+    }
+}
+
+fn hint(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "••••".to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("••••{}", tail)
+}
+
+pub struct Vault {
+    key: [u8; KEY_LEN],
+}
+
+impl Vault {
+    pub fn new(key: [u8; KEY_LEN]) -> Self {
+        Vault { key }
+    }
+
+    pub fn get(&self, conn: &Connection, name: &str) -> Result<Option<String>> {
+        Ok(self.get_entry(conn, name)?.map(|s| s.value))
+    }
+
+    pub fn get_entry(&self, conn: &Connection, name: &str) -> Result<Option<SecretValue>> {
+        let row = conn
+            .query_row(
+                "SELECT nonce, ciphertext, updated_at FROM secrets WHERE name = ?1",
+                [name],
+                |row| {
+                    Ok((
+                        row.get::<_, Vec<u8>>(0)?,
+                        row.get::<_, Vec<u8>>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((nonce, ciphertext, updated_at)) = row else {
+            return Ok(None);
+        };
+        let plaintext = crypto::decrypt(&self.key, &nonce, &ciphertext, name.as_bytes())
+            .map_err(|_| anyhow!(trf("error.secret_decrypt_failed", &[("name", &name)])))?;
+        Ok(Some(SecretValue {
+            name: name.to_string(),
+            value: String::from_utf8(plaintext)?,
+            updated_at,
+        }))
+    }
+
+    pub fn set(&self, conn: &Connection, name: &str, value: &str) -> Result<()> {
+        self.set_at(conn, name, value, &Utc::now().to_rfc3339())
+    }
+
+    // An empty value removes the secret, matching how the settings form
+    // treats a cleared field.
+    pub fn set_at(&self, conn: &Connection, name: &str, value: &str, updated_at: &str) -> Result<()> {
+        check_name(name)?;
+        if value.is_empty() {
+            return delete(conn, name);
+        }
+        let (nonce, ciphertext) = crypto::encrypt(&self.key, value.as_bytes(), name.as_bytes())?;
+        conn.execute(
+            "INSERT INTO secrets (name, service_id, nonce, ciphertext, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(name) DO UPDATE SET nonce = excluded.nonce,
+                 ciphertext = excluded.ciphertext, updated_at = excluded.updated_at",
+            params![name, service_id_of(name), nonce, ciphertext, updated_at],
+        )?;
+        Ok(())
+    }
+
+    pub fn all(&self, conn: &Connection) -> Result<Vec<SecretValue>> {
+        let mut secrets = Vec::new();
+        for name in names(conn)? {
+            if let Some(secret) = self.get_entry(conn, &name)? {
+                secrets.push(secret);
+            }
+        }
+        Ok(secrets)
+    }
+
+    pub fn status(&self, conn: &Connection, name: &str) -> Result<SecretStatus> {
+        let entry = self.get_entry(conn, name)?;
+        Ok(SecretStatus {
+            name: name.to_string(),
+            configured: entry.is_some(),
+            hint: entry.as_ref().map(|s| hint(&s.value)),
+            updated_at: entry.map(|s| s.updated_at),
+        })
+    }
+}
+
+pub fn names(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM secrets ORDER BY name")?;
+    let names = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(names)
+}
+
+pub fn delete(conn: &Connection, name: &str) -> Result<()> {
+    conn.execute("DELETE FROM secrets WHERE name = ?1", [name])?;
+    Ok(())
+}
+
+// Keys that older builds kept as plain rows in `settings` are moved into the
+// vault once and the plaintext rows are dropped.
+pub fn migrate_legacy_settings(conn: &Connection, vault: &Vault) -> Result<()> {
+    for name in APP_SECRETS {
+        let Some(value) = db::get_setting(conn, name) else {
+            continue;
+        };
+        if !value.is_empty() && vault.get(conn, name)?.is_none() {
+            vault.set(conn, name, &value)?;
+        }
+        conn.execute("DELETE FROM settings WHERE key = ?1", [name])?;
+    }
+    Ok(())
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; KEY_LEN]> {
+    STANDARD
+        .decode(encoded.trim())?
+        .try_into()
+        .map_err(|_| anyhow!(tr("error.vault_key_corrupt")))
+}
+
+fn read_stored_key(key_file: &Path) -> Result<Option<[u8; KEY_LEN]>> {
+    let from_keyring = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .and_then(|entry| entry.get_password())
+        .ok();
+    match from_keyring.or_else(|| std::fs::read_to_string(key_file).ok()) {
+        Some(encoded) => Ok(Some(decode_key(&encoded)?)),
+        None => Ok(None),
+    }
+}
+
+fn write_key_file(key_file: &Path, encoded: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    std::io::Write::write_all(&mut options.open(key_file)?, encoded.as_bytes())?;
+    Ok(())
+}
+
+// The OS keyring is preferred; a key file next to the database is the
+// fallback for desktops without a secret service.
+fn store_key(key_file: &Path, key: &[u8; KEY_LEN]) -> Result<()> {
+    let encoded = STANDARD.encode(key);
+    let stored = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .and_then(|entry| entry.set_password(&encoded));
+    if stored.is_err() {
+        write_key_file(key_file, &encoded)?;
+    }
+    Ok(())
+}
+
+pub struct VaultState {
+    key_file: PathBuf,
+    key: Mutex<Option<[u8; KEY_LEN]>>,
+}
+
+impl VaultState {
+    pub fn new(data_dir: &Path) -> Self {
+        VaultState {
+            key_file: data_dir.join(KEY_FILE),
+            key: Mutex::new(None),
+        }
+    }
+
+    pub fn vault(&self, conn: &Connection) -> Result<Vault> {
+        let mut cached = self.key.lock().map_err(|e| anyhow!(e.to_string()))?;
+        if let Some(key) = *cached {
+            return Ok(Vault::new(key));
+        }
+        let key = match read_stored_key(&self.key_file)? {
+            Some(key) => key,
+            None => {
+                if !names(conn)?.is_empty() {
+                    bail!(tr("error.vault_key_missing"));
+                }
+                let mut key = [0u8; KEY_LEN];
+                OsRng.fill_bytes(&mut key);
+                store_key(&self.key_file, &key)?;
+                key
+            }
+        };
+        *cached = Some(key);
+        Ok(Vault::new(key))
+    }
+}
+
+pub fn prepare(app: &AppHandle) -> Result<()> {
+    let db = app.state::<DbState>();
+    let Ok(conn) = db.background_conn() else {
+        return Ok(());
+    };
+    let vault = app.state::<VaultState>().vault(&conn)?;
+    migrate_legacy_settings(&conn, &vault)
+}
+
+fn reveal(app: &AppHandle, conn: &Connection, vault: &Vault, name: &str) -> Result<RevealedSecret> {
+    let value = vault
+        .get(conn, name)?
+        .ok_or_else(|| BlueprintError::not_found(trf("error.secret_not_set", &[("name", &name)])))?;
+    let expires_in_secs = db::get_setting(conn, "secret_reveal_seconds")
+        .and_then(|v| v.parse().ok())
+        .filter(|s| *s > 0)
+        .unwrap_or(30);
+
+    let app = app.clone();
+    let hidden = name.to_string();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(expires_in_secs)).await;
+        let _ = app.emit("secret-hidden", hidden);
+    });
+
+    Ok(RevealedSecret {
+        name: name.to_string(),
+        value,
+        expires_in_secs,
+    })
+}
+
+#[tauri::command]
+pub fn get_secret_status(
+    name: String,
+    state: State<DbState>,
+    vault: State<VaultState>,
+) -> Result<SecretStatus, BlueprintError> {
+    let conn = state.conn()?;
+    check_name(&name)?;
+    let vault = vault.vault(&conn)?;
+    vault.status(&conn, &name).map_err(BlueprintError::from)
+}
+
+#[tauri::command]
+pub fn set_secret(
+    name: String,
+    value: String,
+    state: State<DbState>,
+    vault: State<VaultState>,
+) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    let vault = vault.vault(&conn)?;
+    vault.set(&conn, &name, &value).map_err(BlueprintError::from)
+}
+
+#[tauri::command]
+pub fn delete_secret(name: String, state: State<DbState>) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    check_name(&name)?;
+    delete(&conn, &name).map_err(BlueprintError::from)
+}
+
+#[tauri::command]
+pub fn reveal_secret(
+    name: String,
+    state: State<DbState>,
+    vault: State<VaultState>,
+    app: AppHandle,
+) -> Result<RevealedSecret, BlueprintError> {
+    let conn = state.conn()?;
+    check_name(&name)?;
+    let vault = vault.vault(&conn)?;
+    reveal(&app, &conn, &vault, &name).map_err(BlueprintError::from)
+}
+
+#[tauri::command]
+pub fn get_service_secrets(
+    service_id: String,
+    state: State<DbState>,
+    vault: State<VaultState>,
+) -> Result<Vec<SecretStatus>, BlueprintError> {
+    let conn = state.conn()?;
+    let vault = vault.vault(&conn)?;
+    ServiceSecretKind::ALL
+        .iter()
+        .map(|kind| vault.status(&conn, &service_secret_name(&service_id, *kind)))
+        .collect::<Result<Vec<_>>>()
+        .map_err(BlueprintError::from)
+}
+
+#[tauri::command]
+pub fn set_service_secret(
+    service_id: String,
+    kind: ServiceSecretKind,
+    value: String,
+    state: State<DbState>,
+    vault: State<VaultState>,
+) -> Result<(), BlueprintError> {
+    let conn = state.conn()?;
+    let vault = vault.vault(&conn)?;
+    vault
+        .set(&conn, &service_secret_name(&service_id, kind), &value)
+        .map_err(BlueprintError::from)
+}
+
+#[tauri::command]
+pub fn reveal_service_secret(
+    service_id: String,
+    kind: ServiceSecretKind,
+    state: State<DbState>,
+    vault: State<VaultState>,
+    app: AppHandle,
+) -> Result<RevealedSecret, BlueprintError> {
+    let conn = state.conn()?;
+    let vault = vault.vault(&conn)?;
+    reveal(&app, &conn, &vault, &service_secret_name(&service_id, kind))
+        .map_err(BlueprintError::from)
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use blueprint_lib::db;
+use blueprint_lib::error::BlueprintError;
+use blueprint_lib::lock;
+use common::{open_db, TempDir};
+
+#[test]
+fn passphrase_is_hashed_and_verified() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+
+    assert!(!lock::is_enabled(&conn));
+    assert!(lock::set_passphrase(&conn, "123").is_err());
+    lock::set_passphrase(&conn, "2468").unwrap();
+
+    let hash: String = conn
+        .query_row("SELECT hash FROM app_lock", [], |row| row.get(0))
+        .unwrap();
+    assert!(hash.starts_with("$argon2id$"));
+    assert!(lock::is_enabled(&conn));
+    assert!(lock::verify_passphrase(&conn, "2468").is_ok());
+    assert!(lock::verify_passphrase(&conn, "1357").is_err());
+
+    lock::clear_passphrase(&conn).unwrap();
+    assert!(!lock::is_enabled(&conn));
+}
+
+#[test]
+fn locked_app_refuses_command_access() {
+    let dir = TempDir::new();
+    let state = db::init_db(&dir.path("blueprint.db")).unwrap();
+
+    state.set_app_locked(true);
+    assert!(state.conn().is_err());
+    assert!(state.background_conn().is_ok());
+
+    state.set_app_locked(false);
+    assert!(state.conn().is_ok());
+}
+
+#[test]
+fn locked_app_refuses_to_rekey_the_database() {
+    let dir = TempDir::new();
+    let state = db::init_db(&dir.path("blueprint.db")).unwrap();
+    let code = |result: anyhow::Result<()>| BlueprintError::from(result.unwrap_err()).code();
+
+    state.set_app_locked(true);
+    assert_eq!(code(state.enable_encryption("secret")), "app_locked");
+    assert!(!state.is_encrypted());
+
+    state.set_app_locked(false);
+    state.enable_encryption("secret").unwrap();
+    state.set_app_locked(true);
+    assert_eq!(code(state.change_passphrase("secret", "other")), "app_locked");
+    assert_eq!(code(state.disable_encryption("secret")), "app_locked");
+    assert!(state.is_encrypted());
+}
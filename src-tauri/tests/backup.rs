@@ -0,0 +1,64 @@
+mod common;
+
+use blueprint_lib::backup::{self, RetentionPolicy};
+use blueprint_lib::db;
+use common::{open_db, TempDir};
+
+#[test]
+fn snapshot_restores_previous_state() {
+    let dir = TempDir::new();
+    let backups = dir.path("backups");
+    std::fs::create_dir_all(&backups).unwrap();
+    let mut conn = open_db(&dir);
+
+    conn.execute("UPDATE settings SET value = 'dark' WHERE key = 'theme'", [])
+        .unwrap();
+    let info = backup::snapshot(&conn, None, &backups).unwrap();
+    conn.execute("UPDATE settings SET value = 'light' WHERE key = 'theme'", [])
+        .unwrap();
+
+    backup::restore(&mut conn, None, &backups.join(&info.file_name)).unwrap();
+
+    assert_eq!(db::get_setting(&conn, "theme").as_deref(), Some("dark"));
+    assert_eq!(db::schema_version(&conn).unwrap(), db::SCHEMA_VERSION);
+}
+
+#[test]
+fn corrupt_backup_is_rejected() {
+    let dir = TempDir::new();
+    let path = dir.path("blueprint-20260101-120000.db");
+    std::fs::write(&path, b"definitely not sqlite").unwrap();
+
+    assert!(backup::verify(&path, None).is_err());
+}
+
+#[test]
+fn retention_keeps_newest_per_bucket() {
+    let names: Vec<String> = [
+        "blueprint-20261018-090000.db",
+        "blueprint-20261018-180000.db",
+        "blueprint-20261017-180000.db",
+        "blueprint-20261010-180000.db",
+        "blueprint-20260915-180000.db",
+        "blueprint-20260801-180000.db",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    let policy = RetentionPolicy {
+        daily: 2,
+        weekly: 2,
+        monthly: 2,
+    };
+
+    let mut expired = backup::expired_backups(&names, policy);
+    expired.sort();
+
+    assert_eq!(
+        expired,
+        vec![
+            "blueprint-20260801-180000.db".to_string(),
+            "blueprint-20261018-090000.db".to_string(),
+        ]
+    );
+}
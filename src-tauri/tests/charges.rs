@@ -0,0 +1,87 @@
+mod common;
+
+use blueprint_lib::commands::charges::{self, LogChargeInput, ProjectSpending};
+use blueprint_lib::commands::services;
+use common::{seeded_db, TempDir};
+
+const SEED: &str = "
+    INSERT INTO services (id, project_name, service_name, expires_at, cost, currency,
+                          created_at, billing_cycle)
+    VALUES ('s1', 'Site', 'Hosting', '2026-01-31', 10, 'USD', '2026-01-01T00:00:00+00:00', 'monthly'),
+           ('s2', 'Site', 'Fonts', '2026-06-01', NULL, 'EUR', '2026-01-01T00:00:00+00:00', NULL),
+           ('s3', 'Shop', 'Domain', '2026-06-01', 12, 'USD', '2026-01-01T00:00:00+00:00', NULL);
+";
+
+fn charge(service_id: &str, charged_on: &str, amount: f64) -> LogChargeInput {
+    LogChargeInput {
+        service_id: service_id.to_string(),
+        charged_on: charged_on.to_string(),
+        amount,
+        currency: None,
+        note: None,
+    }
+}
+
+#[test]
+fn renewals_and_manual_charges_build_the_history() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+
+    let renewal = services::renew(&mut conn, "s1", false).unwrap();
+    let manual = charges::log_charge(&conn, charge("s2", "2026-02-10", 30.0)).unwrap();
+    assert_eq!(manual.currency, "EUR");
+
+    let (charged_on, renewal_id): (String, Option<String>) = conn
+        .query_row(
+            "SELECT charged_on, renewal_id FROM service_charges WHERE service_id = 's1'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(charged_on, "2026-01-31");
+    assert_eq!(renewal_id, Some(renewal.id));
+
+    assert!(charges::log_charge(&conn, charge("s2", "10.02.2026", 1.0)).is_err());
+    for amount in [0.0, f64::NAN, f64::INFINITY] {
+        let err = charges::log_charge(&conn, charge("s2", "2026-02-10", amount)).unwrap_err();
+        assert_eq!(err.code(), "validation");
+    }
+    let refund = charges::log_charge(&conn, charge("s2", "2026-2-11", -5.0)).unwrap();
+    assert_eq!(refund.charged_on, "2026-02-11");
+    let missing = charges::log_charge(&conn, charge("nope", "2026-02-10", 1.0)).unwrap_err();
+    assert_eq!(missing.code(), "not_found");
+}
+
+#[test]
+fn spending_is_summed_per_project_and_currency_within_the_range() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    for (service, day, amount) in [
+        ("s1", "2025-12-31", 10.0),
+        ("s1", "2026-01-31", 10.0),
+        ("s1", "2026-02-28", 12.5),
+        ("s2", "2026-02-10", 30.0),
+        ("s3", "2026-03-01", 12.0),
+        ("s3", "2027-03-01", 14.0),
+    ] {
+        charges::log_charge(&conn, charge(service, day, amount)).unwrap();
+    }
+
+    let summary = charges::spending_summary(&conn, "2026-01-01", "2026-12-31").unwrap();
+    let row = |project: &str, currency: &str, total: f64, count: i64| ProjectSpending {
+        project_name: project.to_string(),
+        currency: currency.to_string(),
+        total,
+        charges: count,
+    };
+    assert_eq!(
+        summary,
+        vec![
+            row("Shop", "USD", 12.0, 1),
+            row("Site", "EUR", 30.0, 1),
+            row("Site", "USD", 22.5, 2),
+        ]
+    );
+    assert_eq!(charges::spending_summary(&conn, "2026-1-1", "2026-12-31").unwrap(), summary);
+    assert!(charges::spending_summary(&conn, "2026-01-01", "soon").is_err());
+}
@@ -0,0 +1,52 @@
+#![allow(dead_code)]
+
+use blueprint_lib::db;
+use blueprint_lib::vault::Vault;
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use std::path::PathBuf;
+
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("blueprint-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn open_db(dir: &TempDir) -> Connection {
+    db::open(&dir.path("blueprint.db"), None).unwrap()
+}
+
+// A fresh database with `seed` applied on top of the migrations.
+pub fn seeded_db(dir: &TempDir, seed: &str) -> Connection {
+    let conn = open_db(dir);
+    conn.execute_batch(seed).unwrap();
+    conn
+}
+
+pub fn day(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+pub fn v0_fixture(path: &std::path::Path) {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(include_str!("../fixtures/v0.sql"))
+        .unwrap();
+}
+
+pub fn test_vault() -> Vault {
+    Vault::new([7; 32])
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use blueprint_lib::commands::dashboard::{self, SpendingGroup, UnconvertedKind};
+use common::{day, seeded_db, TempDir};
+
+const SEED: &str = "
+    INSERT INTO fx_rates (id, base, quote, date, rate)
+    VALUES ('usd', 'USD', 'RUB', '2025-12-01', 100), ('eur', 'EUR', 'RUB', '2025-12-01', 110);
+
+    INSERT INTO clients (id, name, payment_type, amount, currency, payment_day, created_at)
+    VALUES ('c1', 'Acme', 'monthly', 1000, 'RUB', 10, '2025-06-01T00:00:00+00:00'),
+           ('c2', 'Globex', 'monthly', 10, 'USD', 31, '2026-02-15T00:00:00+00:00'),
+           ('c3', 'Initech', 'onetime', 50, 'EUR', NULL, '2026-01-01T00:00:00+00:00'),
+           ('c4', 'Hooli', 'monthly', NULL, 'RUB', 5, '2025-01-01T00:00:00+00:00');
+    INSERT INTO client_payments (id, client_id, period, paid)
+    VALUES ('p1', 'c1', '2026-01', 1), ('p2', 'c1', '2026-03', 1),
+           ('p3', 'c2', '2026-02', 1), ('p4', 'c3', '2026-03-01', 0);
+
+    INSERT INTO services (id, project_name, service_name, expires_at, cost, currency, category,
+                          created_at, billing_cycle)
+    VALUES ('s1', 'Site', 'Hosting', '2026-03-20', 10, 'USD', 'hosting', '2026-01-01T00:00:00+00:00', 'monthly'),
+           ('s2', 'Site', 'Fonts', '2026-04-01', 5, 'EUR', NULL, '2026-01-01T00:00:00+00:00', NULL),
+           ('s3', 'Shop', 'VPS', '2026-03-01', 2000, 'RUB', 'hosting', '2026-01-01T00:00:00+00:00', 'monthly'),
+           ('s4', 'Shop', 'Domain', '2026-04-01', NULL, 'USD', NULL, '2026-01-01T00:00:00+00:00', NULL);
+    INSERT INTO service_charges (id, service_id, charged_on, amount, currency, created_at)
+    VALUES ('ch1', 's1', '2026-01-05', 10, 'USD', '2026-01-05T00:00:00+00:00'),
+           ('ch2', 's2', '2026-02-01', 5, 'EUR', '2026-02-01T00:00:00+00:00'),
+           ('ch3', 's3', '2026-02-10', 2000, 'RUB', '2026-02-10T00:00:00+00:00'),
+           ('ch4', 's3', '2025-12-20', 2000, 'RUB', '2025-12-20T00:00:00+00:00');
+";
+
+#[test]
+fn dashboard_aggregates_income_receivables_costs_and_spending() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+
+    let dash =
+        dashboard::build_dashboard(&conn, day("2026-01-01"), day("2026-03-31"), "rub", day("2026-03-15"))
+            .unwrap();
+    assert_eq!(dash.base_currency, "RUB");
+
+    let income: Vec<(&str, f64, f64)> = dash
+        .income
+        .iter()
+        .map(|m| (m.period.as_str(), m.expected, m.received))
+        .collect();
+    // Globex joined mid-February, so January expects nothing from it and its
+    // day 31 falls on February 28.
+    assert_eq!(
+        income,
+        vec![
+            ("2026-01", 1000.0, 1000.0),
+            ("2026-02", 2000.0, 1000.0),
+            ("2026-03", 7500.0, 1000.0),
+        ]
+    );
+
+    let overdue: Vec<(&str, &str, i64, f64)> = dash
+        .overdue
+        .iter()
+        .map(|o| (o.client_id.as_str(), o.due_date.as_str(), o.days_overdue, o.amount))
+        .collect();
+    assert_eq!(overdue, vec![("c1", "2026-02-10", 33, 1000.0), ("c3", "2026-03-01", 14, 5500.0)]);
+    assert_eq!(dash.overdue[1].original_currency, "EUR");
+    assert_eq!(dash.overdue_total, 6500.0);
+
+    let upcoming: Vec<(&str, &str)> = dash
+        .upcoming
+        .iter()
+        .map(|c| (c.service_id.as_str(), c.due_date.as_str()))
+        .collect();
+    assert_eq!(
+        upcoming,
+        vec![("s1", "2026-03-20"), ("s2", "2026-04-01"), ("s1", "2026-04-20"), ("s1", "2026-05-20")]
+    );
+    assert_eq!(dash.upcoming_30_days, 1550.0);
+    assert_eq!(dash.upcoming_90_days, 3550.0);
+
+    let group = |name: Option<&str>, total, charges| SpendingGroup {
+        name: name.map(str::to_string),
+        total,
+        charges,
+    };
+    assert_eq!(
+        dash.spending_by_project,
+        vec![group(Some("Shop"), 2000.0, 1), group(Some("Site"), 1550.0, 2)]
+    );
+    assert_eq!(
+        dash.spending_by_category,
+        vec![group(Some("hosting"), 3000.0, 2), group(None, 550.0, 1)]
+    );
+    assert!(dash.unconverted.is_empty());
+}
+
+#[test]
+fn dashboard_rejects_bad_input_and_flags_missing_rates() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    let today = day("2026-03-15");
+
+    let reversed = dashboard::build_dashboard(&conn, day("2026-03-01"), day("2026-01-01"), "RUB", today);
+    assert_eq!(reversed.unwrap_err().code(), "validation");
+    assert!(dashboard::build_dashboard(&conn, day("2026-01-01"), day("2026-03-31"), "RUBLES", today).is_err());
+
+    // Nothing converts into GBP, so every amount is flagged and none is counted.
+    let gbp = dashboard::build_dashboard(&conn, day("2026-01-01"), day("2026-03-31"), "GBP", today).unwrap();
+    assert!(gbp.income.iter().all(|m| m.expected == 0.0 && m.received == 0.0));
+    assert!(gbp.upcoming.is_empty());
+    assert!(gbp.spending_by_project.is_empty());
+    assert!(!gbp.unconverted.is_empty());
+
+    conn.execute("DELETE FROM fx_rates WHERE base = 'EUR'", []).unwrap();
+    let dash = dashboard::build_dashboard(&conn, day("2026-01-01"), day("2026-03-31"), "RUB", today).unwrap();
+    let group = |name: Option<&str>, total, charges| SpendingGroup {
+        name: name.map(str::to_string),
+        total,
+        charges,
+    };
+    let flagged: Vec<_> = dash.unconverted.iter().map(|u| (u.kind, u.id.as_str(), u.currency.as_str())).collect();
+    assert_eq!(
+        flagged,
+        vec![
+            (UnconvertedKind::Receivable, "c3", "EUR"),
+            (UnconvertedKind::Upcoming, "s2", "EUR"),
+            (UnconvertedKind::Charge, "s2", "EUR"),
+        ]
+    );
+    assert_eq!(
+        dash.spending_by_project,
+        vec![group(Some("Shop"), 2000.0, 1), group(Some("Site"), 1000.0, 1)]
+    );
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use blueprint_lib::backup;
+use blueprint_lib::db;
+use common::TempDir;
+
+fn theme(state: &db::DbState) -> Option<String> {
+    db::get_setting(&state.conn().unwrap(), "theme")
+}
+
+#[test]
+fn encrypted_database_starts_locked_and_unlocks_with_passphrase() {
+    let dir = TempDir::new();
+    let path = dir.path("blueprint.db");
+    let state = db::init_db(&path).unwrap();
+    state
+        .conn()
+        .unwrap()
+        .execute("UPDATE settings SET value = 'dark' WHERE key = 'theme'", [])
+        .unwrap();
+
+    state.enable_encryption("secret").unwrap();
+    assert!(db::is_encrypted(&path));
+    assert_eq!(theme(&state).as_deref(), Some("dark"));
+    drop(state);
+
+    let state = db::init_db(&path).unwrap();
+    assert!(state.is_locked());
+    assert!(state.is_encrypted());
+    assert!(state.conn().is_err());
+    assert!(state.unlock("wrong").is_err());
+    assert!(state.is_locked());
+
+    state.unlock("secret").unwrap();
+    assert_eq!(theme(&state).as_deref(), Some("dark"));
+    assert_eq!(
+        db::schema_version(&state.conn().unwrap()).unwrap(),
+        db::SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn encryption_can_be_removed_and_passphrase_changed() {
+    let dir = TempDir::new();
+    let path = dir.path("blueprint.db");
+    let state = db::init_db(&path).unwrap();
+    state.enable_encryption("first").unwrap();
+
+    assert!(state.change_passphrase("wrong", "second").is_err());
+    state.change_passphrase("first", "second").unwrap();
+    assert!(db::open(&path, Some("first")).is_err());
+
+    assert!(state.disable_encryption("first").is_err());
+    state.disable_encryption("second").unwrap();
+    assert!(!db::is_encrypted(&path));
+    assert!(!state.is_encrypted());
+    assert_eq!(theme(&state).as_deref(), Some("system"));
+}
+
+#[test]
+fn backups_of_encrypted_database_stay_encrypted() {
+    let dir = TempDir::new();
+    let backups = dir.path("backups");
+    std::fs::create_dir_all(&backups).unwrap();
+    let state = db::init_db(&dir.path("blueprint.db")).unwrap();
+    state.enable_encryption("secret").unwrap();
+
+    let mut conn = state.conn().unwrap();
+    let info = backup::snapshot(&conn, conn.key(), &backups).unwrap();
+    let path = backups.join(&info.file_name);
+    assert!(info.encrypted);
+    assert!(backup::verify(&path, None).is_err());
+
+    conn.execute("UPDATE settings SET value = 'light' WHERE key = 'theme'", [])
+        .unwrap();
+    backup::restore(&mut conn, Some("secret"), &path).unwrap();
+    assert_eq!(db::get_setting(&conn, "theme").as_deref(), Some("system"));
+}
+
+#[test]
+fn key_changes_rekey_existing_backups() {
+    let dir = TempDir::new();
+    let backups = dir.path("backups");
+    std::fs::create_dir_all(&backups).unwrap();
+    let state = db::init_db(&dir.path("blueprint.db")).unwrap();
+    let plain = backup::snapshot(&state.conn().unwrap(), None, &backups).unwrap();
+    let path = backups.join(&plain.file_name);
+    std::fs::write(backups.join("blueprint-20200101-000000.db"), b"not a database").unwrap();
+
+    state.enable_encryption("first").unwrap();
+    let unreadable = backup::rekey(&backups, None, Some("first")).unwrap();
+    assert_eq!(unreadable, vec!["blueprint-20200101-000000.db".to_string()]);
+    assert!(db::is_encrypted(&path));
+    backup::verify(&path, Some("first")).unwrap();
+
+    state.change_passphrase("first", "second").unwrap();
+    backup::rekey(&backups, Some("first"), Some("second")).unwrap();
+    assert!(backup::verify(&path, Some("first")).is_err());
+    backup::verify(&path, Some("second")).unwrap();
+    // Running it again finds the backups already under the new key.
+    assert_eq!(backup::rekey(&backups, Some("first"), Some("second")).unwrap().len(), 1);
+
+    state.disable_encryption("second").unwrap();
+    backup::rekey(&backups, Some("second"), None).unwrap();
+    assert!(!db::is_encrypted(&path));
+    let mut conn = state.conn().unwrap();
+    backup::restore(&mut conn, None, &path).unwrap();
+    assert_eq!(db::get_setting(&conn, "theme").as_deref(), Some("system"));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use blueprint_lib::db;
+use blueprint_lib::error::BlueprintError;
+use common::TempDir;
+use serde_json::json;
+
+#[test]
+fn errors_serialize_with_code_message_and_details() {
+    let err = BlueprintError::missing_key("groq_api_key");
+
+    assert_eq!(
+        serde_json::to_value(&err).unwrap(),
+        json!({
+            "code": "missing_api_key",
+            "message": err.to_string(),
+            "details": { "secret": "groq_api_key" }
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(BlueprintError::AppLocked).unwrap()["details"],
+        json!(null)
+    );
+}
+
+#[test]
+fn typed_errors_survive_internal_helpers() {
+    let dir = TempDir::new();
+    let path = dir.path("blueprint.db");
+    let state = db::init_db(&path).unwrap();
+    state.enable_encryption("secret").unwrap();
+    drop(state);
+
+    let state = db::init_db(&path).unwrap();
+    assert_eq!(state.conn().err().unwrap().code(), "database_locked");
+    let err = BlueprintError::from(state.unlock("wrong").unwrap_err());
+    assert_eq!(err.code(), "invalid_passphrase");
+
+    state.unlock("secret").unwrap();
+    state.set_app_locked(true);
+    assert_eq!(state.conn().err().unwrap().code(), "app_locked");
+    assert!(state.background_conn().is_ok());
+}
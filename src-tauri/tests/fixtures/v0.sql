@@ -0,0 +1,79 @@
+-- Schema as shipped before versioned migrations: no user_version, no clients.payment_day.
+CREATE TABLE clients (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    contact TEXT,
+    payment_type TEXT NOT NULL DEFAULT 'monthly',
+    amount REAL,
+    currency TEXT NOT NULL DEFAULT 'RUB',
+    notes TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE client_payments (
+    id TEXT PRIMARY KEY,
+    client_id TEXT NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    period TEXT NOT NULL,
+    paid INTEGER NOT NULL DEFAULT 0,
+    paid_at TEXT,
+    UNIQUE(client_id, period)
+);
+
+CREATE TABLE services (
+    id TEXT PRIMARY KEY,
+    project_name TEXT NOT NULL,
+    service_name TEXT NOT NULL,
+    login TEXT,
+    url TEXT,
+    expires_at TEXT NOT NULL,
+    cost REAL,
+    currency TEXT NOT NULL DEFAULT 'USD',
+    notes TEXT,
+    category TEXT,
+    notify_days INTEGER NOT NULL DEFAULT 7,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE categories (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    color TEXT NOT NULL DEFAULT '#1a73e8'
+);
+
+CREATE TABLE notes (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    content TEXT,
+    category_id TEXT REFERENCES categories(id) ON DELETE SET NULL,
+    completed INTEGER NOT NULL DEFAULT 0,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+CREATE TABLE chat_history (
+    id TEXT PRIMARY KEY,
+    role TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+INSERT INTO settings (key, value) VALUES ('theme', 'dark');
+INSERT INTO settings (key, value) VALUES ('ai_api_key', 'sk-fixture');
+
+INSERT INTO clients (id, name, contact, payment_type, amount, currency, notes, created_at)
+VALUES ('c1', 'ООО Ромашка', '@romashka', 'monthly', 50000, 'RUB', NULL, '2024-01-15T10:00:00+00:00');
+INSERT INTO client_payments (id, client_id, period, paid, paid_at)
+VALUES ('p1', 'c1', '2024-02', 1, '2024-02-05T09:00:00+00:00');
+
+INSERT INTO services (id, project_name, service_name, login, url, expires_at, cost, currency, notes, category, notify_days, created_at)
+VALUES ('s1', 'Blueprint', 'Hetzner', 'admin', 'https://hetzner.com', '2025-03-01', 12.5, 'EUR', NULL, 'hosting', 14, '2024-01-01T00:00:00+00:00');
+
+INSERT INTO categories (id, name, color) VALUES ('cat1', 'Работа', '#ff0000');
+INSERT INTO notes (id, title, content, category_id, completed, sort_order, created_at, updated_at)
+VALUES ('n1', 'Позвонить', NULL, 'cat1', 0, 0, '2024-01-01T00:00:00+00:00', '2024-01-01T00:00:00+00:00');
@@ -0,0 +1,128 @@
+mod common;
+
+use blueprint_lib::commands::charges::{self, LogChargeInput};
+use blueprint_lib::commands::fx::{self, Converter, FxRateInput};
+use common::{day, open_db, TempDir};
+
+fn rate(base: &str, quote: &str, date: &str, rate: f64) -> FxRateInput {
+    FxRateInput {
+        base: base.to_string(),
+        quote: quote.to_string(),
+        date: date.to_string(),
+        rate,
+    }
+}
+
+#[test]
+fn currency_codes_are_normalized_and_checked() {
+    assert_eq!(fx::normalize_currency(" usd ").unwrap(), "USD");
+    assert_eq!(fx::normalize_currency("RUB").unwrap(), "RUB");
+    for bad in ["", "US", "USDT", "XYZ", "руб"] {
+        assert_eq!(fx::normalize_currency(bad).unwrap_err().code(), "validation", "{bad}");
+    }
+}
+
+#[test]
+fn converter_uses_latest_rate_inverse_and_cross_rates() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+    fx::set_rate(&conn, rate("usd", "RUB", "2026-01-01", 90.0)).unwrap();
+    fx::set_rate(&conn, rate("USD", "RUB", "2026-02-01", 100.0)).unwrap();
+    fx::set_rate(&conn, rate("EUR", "USD", "2026-01-01", 1.1)).unwrap();
+    // Setting the same pair and day again replaces the rate.
+    let replaced = fx::set_rate(&conn, rate("EUR", "USD", "2026-01-01", 1.2)).unwrap();
+    assert_eq!(replaced.rate, 1.2);
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM fx_rates", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 3);
+
+    let converter = Converter::load(&conn).unwrap();
+    let convert = |amount, from, to, on| converter.convert(amount, from, to, day(on)).unwrap();
+    assert_eq!(convert(10.0, "USD", "USD", "2025-01-01"), 10.0);
+    assert_eq!(convert(10.0, "USD", "RUB", "2026-01-15"), 900.0);
+    assert_eq!(convert(10.0, "USD", "RUB", "2026-03-01"), 1000.0);
+    assert_eq!(convert(1000.0, "RUB", "USD", "2026-02-01"), 10.0);
+    assert!((convert(10.0, "EUR", "RUB", "2026-02-10") - 1200.0).abs() < 1e-9);
+
+    let missing = converter.convert(1.0, "USD", "RUB", day("2025-12-31")).unwrap_err();
+    assert_eq!(missing.code(), "validation");
+    assert!(converter.convert(1.0, "GBP", "RUB", day("2026-02-01")).is_err());
+}
+
+#[test]
+fn invalid_rates_are_rejected() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+    assert!(fx::set_rate(&conn, rate("USD", "USD", "2026-01-01", 1.0)).is_err());
+    assert!(fx::set_rate(&conn, rate("USD", "RUB", "2026-01-01", 0.0)).is_err());
+    assert!(fx::set_rate(&conn, rate("USD", "RUB", "01.01.2026", 90.0)).is_err());
+    assert!(fx::set_rate(&conn, rate("USD", "ABC", "2026-01-01", 90.0)).is_err());
+}
+
+#[test]
+fn rates_import_from_csv_and_json_all_or_nothing() {
+    let dir = TempDir::new();
+    let mut conn = open_db(&dir);
+
+    let csv = "base,quote,date,rate\nUSD,RUB,2026-01-01,90\n\neur;usd;2026-01-01;1.1\n";
+    assert_eq!(fx::import_rates(&mut conn, csv).unwrap(), 2);
+    let json = r#"[{"base": "GBP", "quote": "USD", "date": "2026-01-01", "rate": 1.3}]"#;
+    assert_eq!(fx::import_rates(&mut conn, json).unwrap(), 1);
+
+    let broken = "USD,RUB,2026-02-01,95\nUSD,RUB,2026-03-01\n";
+    let err = fx::import_rates(&mut conn, broken).unwrap_err();
+    assert!(err.to_string().contains('2'), "{err}");
+    assert!(fx::import_rates(&mut conn, "USD,RUB,2026-02-01,abc").is_err());
+    assert!(fx::import_rates(&mut conn, "USD,XXX,2026-02-01,1").is_err());
+
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM fx_rates", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn spending_converts_each_charge_at_its_own_date() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+    conn.execute_batch(
+        "
+        INSERT INTO services (id, project_name, service_name, expires_at, cost, currency, created_at)
+        VALUES ('s1', 'Site', 'Hosting', '2026-06-01', 10, 'USD', '2026-01-01T00:00:00+00:00'),
+               ('s2', 'Site', 'Fonts', '2026-06-01', NULL, 'EUR', '2026-01-01T00:00:00+00:00');
+        ",
+    )
+    .unwrap();
+    fx::set_rate(&conn, rate("USD", "RUB", "2026-01-01", 90.0)).unwrap();
+    fx::set_rate(&conn, rate("USD", "RUB", "2026-02-01", 100.0)).unwrap();
+    fx::set_rate(&conn, rate("EUR", "RUB", "2026-01-01", 110.0)).unwrap();
+    for (service_id, charged_on, amount) in
+        [("s1", "2026-01-10", 10.0), ("s1", "2026-02-10", 10.0), ("s2", "2026-02-10", 1.0)]
+    {
+        let input = LogChargeInput {
+            service_id: service_id.to_string(),
+            charged_on: charged_on.to_string(),
+            amount,
+            currency: None,
+            note: None,
+        };
+        charges::log_charge(&conn, input).unwrap();
+    }
+
+    let summary = charges::spending_summary_in(&conn, "2026-01-01", "2026-12-31", "rub").unwrap();
+    assert_eq!(summary.len(), 1);
+    assert_eq!(summary[0].currency, "RUB");
+    assert_eq!(summary[0].total, 900.0 + 1000.0 + 110.0);
+    assert_eq!(summary[0].charges, 3);
+
+    let bad_currency = LogChargeInput {
+        service_id: "s1".to_string(),
+        charged_on: "2026-02-10".to_string(),
+        amount: 1.0,
+        currency: Some("dollars".to_string()),
+        note: None,
+    };
+    assert!(charges::log_charge(&conn, bad_currency).is_err());
+    assert!(charges::spending_summary_in(&conn, "2026-01-01", "2026-12-31", "GBP").is_err());
+}
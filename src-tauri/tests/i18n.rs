@@ -0,0 +1,48 @@
+mod common;
+
+use blueprint_lib::db;
+use blueprint_lib::i18n::{self, Language, MESSAGES};
+use common::{open_db, TempDir};
+
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut found: Vec<&str> = text
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .collect();
+    found.sort();
+    found
+}
+
+#[test]
+fn every_message_has_matching_placeholders() {
+    for (key, ru, en) in MESSAGES {
+        assert!(!ru.is_empty() && !en.is_empty(), "{} is empty", key);
+        assert_eq!(placeholders(ru), placeholders(en), "{}", key);
+    }
+}
+
+#[test]
+fn messages_are_formatted_in_the_requested_language() {
+    let args: &[(&str, &dyn std::fmt::Display)] = &[("days", &3), ("name", &"Hosting")];
+
+    assert_eq!(
+        i18n::trf_in(Language::En, "notify.expires_in", args),
+        "Expires in 3 d: Hosting"
+    );
+    assert_eq!(
+        i18n::trf_in(Language::Ru, "notify.expires_in", args),
+        "Истекает через 3 дн: Hosting"
+    );
+    assert_eq!(i18n::tr_in(Language::En, "no.such.key"), "no.such.key");
+}
+
+#[test]
+fn language_setting_defaults_to_russian() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+
+    assert_eq!(db::get_setting(&conn, "language").as_deref(), Some("ru"));
+    assert_eq!(Language::parse("en"), Some(Language::En));
+    assert_eq!(Language::parse("de"), None);
+}
@@ -0,0 +1,268 @@
+mod common;
+
+use blueprint_lib::commands::clients;
+use blueprint_lib::commands::invoices::{self, CreateInvoiceInput, InvoiceItemInput};
+use blueprint_lib::pdf::{PdfDocument, TrueTypeFont, SYSTEM_FONTS};
+use common::{seeded_db, TempDir};
+use rusqlite::Connection;
+use std::path::Path;
+
+const SEED: &str = "
+    INSERT INTO clients (id, name, contact, payment_type, amount, currency, payment_day, created_at)
+    VALUES ('c1', 'Acme <Corp>', 'billing@acme.test', 'monthly', 1500, 'EUR', 10, '2026-01-01T00:00:00+00:00'),
+           ('c2', 'Globex', NULL, 'monthly', NULL, 'USD', NULL, '2026-01-01T00:00:00+00:00');
+    UPDATE settings SET value = 'A-{year}-' WHERE key = 'invoice_prefix';
+    INSERT OR REPLACE INTO settings (key, value) VALUES ('company_name', 'Blueprint Studio');
+    INSERT OR REPLACE INTO settings (key, value) VALUES ('company_bank', 'IBAN DE00 0000');
+";
+
+fn input(client_id: &str, period: Option<&str>) -> CreateInvoiceInput {
+    CreateInvoiceInput {
+        client_id: client_id.to_string(),
+        period: period.map(str::to_string),
+        issued_on: Some("2026-03-01".to_string()),
+        due_on: Some("2026-03-15".to_string()),
+        currency: None,
+        notes: None,
+        items: None,
+    }
+}
+
+fn paid(conn: &Connection, period: &str) -> Option<bool> {
+    conn.query_row(
+        "SELECT paid FROM client_payments WHERE client_id = 'c1' AND period = ?1",
+        [period],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+#[test]
+fn invoices_are_numbered_in_sequence_and_default_to_the_client_amount() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+
+    let first = invoices::create(&mut conn, input("c1", Some("2026-03"))).unwrap();
+    assert_eq!(first.invoice.number, "A-2026-0001");
+    assert_eq!(first.invoice.currency, "EUR");
+    assert_eq!(first.total, 1500.0);
+    assert_eq!(first.items.len(), 1);
+    assert_eq!(first.items[0].period.as_deref(), Some("2026-03"));
+
+    // An invoice that already holds the next number is skipped over.
+    conn.execute("UPDATE invoices SET number = 'A-2026-0002'", []).unwrap();
+    let mut custom = input("c1", None);
+    custom.items = Some(vec![
+        InvoiceItemInput {
+            description: "Design".to_string(),
+            quantity: Some(2.5),
+            unit_price: 100.0,
+            period: Some("2026-01".to_string()),
+        },
+        InvoiceItemInput {
+            description: "Hosting".to_string(),
+            quantity: None,
+            unit_price: 20.0,
+            period: None,
+        },
+    ]);
+    let second = invoices::create(&mut conn, custom).unwrap();
+    assert_eq!(second.invoice.number, "A-2026-0003");
+    assert_eq!(second.total, 270.0);
+    assert_eq!(second.items[1].period, None);
+    assert_eq!(invoices::list(&conn, Some("c1")).unwrap().len(), 2);
+
+    let no_amount = invoices::create(&mut conn, input("c2", Some("2026-03"))).unwrap_err();
+    assert_eq!(no_amount.code(), "validation");
+    assert!(invoices::create(&mut conn, input("c1", Some("March"))).is_err());
+    assert_eq!(invoices::create(&mut conn, input("nope", None)).unwrap_err().code(), "not_found");
+
+    let item = |description: &str, quantity, unit_price| InvoiceItemInput {
+        description: description.to_string(),
+        quantity,
+        unit_price,
+        period: None,
+    };
+    for bad in [
+        item("  ", None, 10.0),
+        item("Design", None, f64::NAN),
+        item("Design", Some(0.0), 10.0),
+        item("Design", Some(-1.0), 10.0),
+        item("Design", Some(f64::INFINITY), 10.0),
+    ] {
+        let mut invalid = input("c1", None);
+        invalid.items = Some(vec![bad]);
+        let err = invoices::create(&mut conn, invalid).unwrap_err();
+        assert_eq!(err.code(), "validation");
+        assert!(!err.to_string().contains("сумма") && !err.to_string().contains("amount"), "{err}");
+    }
+    assert_eq!(invoices::list(&conn, Some("c1")).unwrap().len(), 2);
+
+    let mut early = input("c1", Some("2026-04"));
+    early.due_on = Some("2026-02-28".to_string());
+    assert_eq!(invoices::create(&mut conn, early).unwrap_err().code(), "validation");
+    let mut unpadded = input("c1", Some("2026-04"));
+    unpadded.due_on = Some("2026-3-5".to_string());
+    let padded = invoices::create(&mut conn, unpadded).unwrap();
+    assert_eq!(padded.invoice.due_on.as_deref(), Some("2026-03-05"));
+}
+
+#[test]
+fn paying_an_invoice_updates_client_payments_both_ways() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    let mut multi = input("c1", Some("2026-03"));
+    multi.items = Some(vec![
+        InvoiceItemInput {
+            description: "February".to_string(),
+            quantity: None,
+            unit_price: 1500.0,
+            period: Some("2026-02".to_string()),
+        },
+        InvoiceItemInput {
+            description: "March".to_string(),
+            quantity: None,
+            unit_price: 1500.0,
+            period: None,
+        },
+    ]);
+    let invoice = invoices::create(&mut conn, multi).unwrap();
+    assert_eq!(paid(&conn, "2026-02"), None);
+
+    let marked = invoices::set_paid(&mut conn, &invoice.invoice.id, true).unwrap();
+    assert_eq!(marked.invoice.status, invoices::STATUS_PAID);
+    assert!(marked.invoice.paid_at.is_some());
+    assert_eq!(paid(&conn, "2026-02"), Some(true));
+    assert_eq!(paid(&conn, "2026-03"), Some(true));
+
+    let reopened = invoices::set_paid(&mut conn, &invoice.invoice.id, false).unwrap();
+    assert_eq!(reopened.invoice.status, invoices::STATUS_ISSUED);
+    assert_eq!(paid(&conn, "2026-03"), Some(false));
+}
+
+#[test]
+fn reopening_an_invoice_keeps_periods_covered_elsewhere() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    let first = invoices::create(&mut conn, input("c1", Some("2026-02"))).unwrap();
+    let mut both = input("c1", None);
+    both.items = Some(
+        ["2026-02", "2026-03", "2026-04"]
+            .into_iter()
+            .map(|period| InvoiceItemInput {
+                description: period.to_string(),
+                quantity: None,
+                unit_price: 1500.0,
+                period: Some(period.to_string()),
+            })
+            .collect(),
+    );
+    let second = invoices::create(&mut conn, both).unwrap();
+    invoices::set_paid(&mut conn, &first.invoice.id, true).unwrap();
+    invoices::set_paid(&mut conn, &second.invoice.id, true).unwrap();
+    clients::record_payment(
+        &mut conn,
+        clients::RecordPaymentInput {
+            client_id: "c1".to_string(),
+            period: "2026-03".to_string(),
+            paid_on: Some("2026-03-10".to_string()),
+            amount: 1500.0,
+            currency: None,
+            method: None,
+            note: None,
+        },
+    )
+    .unwrap();
+
+    invoices::set_paid(&mut conn, &second.invoice.id, false).unwrap();
+    // February is still on the first invoice, March was paid in full.
+    assert_eq!(paid(&conn, "2026-02"), Some(true));
+    assert_eq!(paid(&conn, "2026-03"), Some(true));
+    assert_eq!(paid(&conn, "2026-04"), Some(false));
+}
+
+#[test]
+fn invoices_render_to_escaped_html_and_pdf() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    let invoice = invoices::create(&mut conn, input("c1", Some("2026-03"))).unwrap();
+
+    let html = invoices::render_html(&conn, &invoice.invoice.id).unwrap();
+    assert!(html.contains("A-2026-0001"));
+    assert!(html.contains("Acme &lt;Corp&gt;"));
+    assert!(html.contains("Blueprint Studio"));
+    assert!(html.contains("1500.00 EUR"));
+
+    let pdf = invoices::render_pdf(&conn, &invoice.invoice.id, None).unwrap();
+    assert!(pdf.starts_with(b"%PDF-1.4"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("/BaseFont /Helvetica"));
+    let xref: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+    assert!(pdf[xref..].starts_with(b"xref"));
+}
+
+#[test]
+fn pdf_embeds_a_truetype_font_for_cyrillic_text() {
+    let Some(font) = SYSTEM_FONTS.iter().find_map(|p| TrueTypeFont::load(Path::new(p))) else {
+        // No system font on this machine; the Helvetica path is covered above.
+        return;
+    };
+    assert!(font.covers("Счёт Acme"));
+    let mut doc = PdfDocument::new(Some(font));
+    assert!(doc.embeds_font());
+    assert!(doc.text_width("Счёт", 10.0) > 0.0);
+    doc.text(50.0, 800.0, 12.0, "Счёт № 1");
+    doc.new_page();
+    doc.text_right(545.0, 800.0, 12.0, "Итого");
+    let pdf = doc.render();
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("/Subtype /CIDFontType2"));
+    assert!(text.contains("/FontFile2"));
+    assert!(text.contains("/Count 2"));
+}
+
+// A font whose only real content is a format 12 cmap with the given groups;
+// every other table points at the same zeroed block.
+fn font_with_cmap_groups(groups: &[(u32, u32, u32)]) -> Vec<u8> {
+    let tags: [&[u8; 4]; 6] = [b"cmap", b"glyf", b"head", b"hhea", b"hmtx", b"maxp"];
+    let (shared, cmap) = (128u32, 256u32);
+    let mut data = vec![0u8; cmap as usize];
+    data[4..6].copy_from_slice(&(tags.len() as u16).to_be_bytes());
+    for (i, tag) in tags.iter().enumerate() {
+        let record = 12 + 16 * i;
+        data[record..record + 4].copy_from_slice(*tag);
+        let offset = if **tag == *b"cmap" { cmap } else { shared };
+        data[record + 8..record + 12].copy_from_slice(&offset.to_be_bytes());
+    }
+    for value in [0u16, 1, 3, 10] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data.extend_from_slice(&12u32.to_be_bytes());
+    for value in [12u16, 0] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    for value in [16 + 12 * groups.len() as u32, 0, groups.len() as u32] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    for (start, end, glyph) in groups {
+        for value in [start, end, glyph] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    data
+}
+
+#[test]
+fn malformed_cmap_ranges_are_skipped() {
+    let font = TrueTypeFont::parse(font_with_cmap_groups(&[
+        (0xFFFF_FFF0, 0xFFFF_FFFF, 1),
+        (0x41, 0x42, u32::MAX),
+        (0x43, 0x43, 0x1_0000),
+        (0x44, 0x44, 7),
+    ]))
+    .unwrap();
+    assert!(font.covers("D"));
+    assert!(!font.covers("A"));
+    assert!(!font.covers("C"));
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use blueprint_lib::db;
+use common::{v0_fixture, TempDir};
+use rusqlite::Connection;
+
+fn columns(conn: &Connection, table: &str) -> Vec<String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info(?1)")
+        .unwrap();
+    stmt.query_map([table], |row| row.get(0))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect()
+}
+
+#[test]
+fn fresh_database_is_created_at_current_version() {
+    let dir = TempDir::new();
+    let conn = db::open(&dir.path("blueprint.db"), None).unwrap();
+
+    assert_eq!(db::schema_version(&conn).unwrap(), db::SCHEMA_VERSION);
+    assert!(columns(&conn, "clients").contains(&"payment_day".to_string()));
+    let theme: String = conn
+        .query_row("SELECT value FROM settings WHERE key = 'theme'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(theme, "system");
+}
+
+#[test]
+fn v0_database_is_upgraded_and_keeps_data() {
+    let dir = TempDir::new();
+    let path = dir.path("blueprint.db");
+    v0_fixture(&path);
+
+    let conn = db::open(&path, None).unwrap();
+
+    assert_eq!(db::schema_version(&conn).unwrap(), db::SCHEMA_VERSION);
+    assert!(columns(&conn, "clients").contains(&"payment_day".to_string()));
+
+    let (name, payment_day): (String, Option<i32>) = conn
+        .query_row("SELECT name, payment_day FROM clients WHERE id = 'c1'", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(name, "ООО Ромашка");
+    assert_eq!(payment_day, None);
+
+    let theme: String = conn
+        .query_row("SELECT value FROM settings WHERE key = 'theme'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(theme, "dark");
+
+    let paid: i32 = conn
+        .query_row("SELECT paid FROM client_payments WHERE id = 'p1'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(paid, 1);
+}
+
+#[test]
+fn legacy_database_with_payment_day_is_upgraded() {
+    let dir = TempDir::new();
+    let path = dir.path("blueprint.db");
+    v0_fixture(&path);
+    {
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("ALTER TABLE clients ADD COLUMN payment_day INTEGER; UPDATE clients SET payment_day = 10;")
+            .unwrap();
+    }
+
+    let conn = db::open(&path, None).unwrap();
+
+    assert_eq!(db::schema_version(&conn).unwrap(), db::SCHEMA_VERSION);
+    let payment_day: Option<i32> = conn
+        .query_row("SELECT payment_day FROM clients WHERE id = 'c1'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(payment_day, Some(10));
+}
+
+#[test]
+fn reopening_is_a_no_op() {
+    let dir = TempDir::new();
+    let path = dir.path("blueprint.db");
+    drop(db::open(&path, None).unwrap());
+
+    let conn = db::open(&path, None).unwrap();
+    assert_eq!(db::schema_version(&conn).unwrap(), db::SCHEMA_VERSION);
+}
+
+#[test]
+fn database_from_newer_build_is_refused() {
+    let dir = TempDir::new();
+    let path = dir.path("blueprint.db");
+    {
+        let conn = Connection::open(&path).unwrap();
+        conn.pragma_update(None, "user_version", db::SCHEMA_VERSION + 1)
+            .unwrap();
+    }
+
+    assert!(db::open(&path, None).is_err());
+}
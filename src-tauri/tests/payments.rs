@@ -0,0 +1,117 @@
+mod common;
+
+use blueprint_lib::commands::clients::{self, RecordPaymentInput};
+use blueprint_lib::commands::dashboard;
+use common::{day, seeded_db, TempDir};
+
+const SEED: &str = "
+    INSERT INTO fx_rates (id, base, quote, date, rate)
+    VALUES ('usd', 'USD', 'RUB', '2026-01-01', 100);
+    INSERT INTO clients (id, name, payment_type, amount, currency, payment_day, created_at)
+    VALUES ('c1', 'Acme', 'monthly', 10000, 'RUB', 10, '2026-01-01T00:00:00+00:00');
+";
+
+fn payment(period: &str, paid_on: &str, amount: f64, currency: Option<&str>) -> RecordPaymentInput {
+    RecordPaymentInput {
+        client_id: "c1".to_string(),
+        period: period.to_string(),
+        paid_on: Some(paid_on.to_string()),
+        amount,
+        currency: currency.map(str::to_string),
+        method: Some("  card ".to_string()),
+        note: None,
+    }
+}
+
+#[test]
+fn partial_payments_add_up_to_a_settled_period() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+
+    let first = clients::record_payment(&mut conn, payment("2026-02", "2026-02-08", 4000.0, None)).unwrap();
+    assert_eq!(first.currency, "RUB");
+    assert_eq!(first.method.as_deref(), Some("card"));
+
+    let periods = clients::payment_periods(&conn, "c1").unwrap();
+    assert_eq!(periods.len(), 1);
+    assert!(!periods[0].payment.paid);
+    assert_eq!(periods[0].received, 4000.0);
+    assert_eq!(periods[0].outstanding, Some(6000.0));
+
+    // 60 USD at 100 covers the rest.
+    let second =
+        clients::record_payment(&mut conn, payment("2026-02", "2026-02-12", 60.0, Some("usd"))).unwrap();
+    let period = &clients::payment_periods(&conn, "c1").unwrap()[0];
+    assert!(period.payment.paid);
+    assert_eq!(period.payment.paid_at.as_deref(), Some("2026-02-12"));
+    assert_eq!(period.transactions.len(), 2);
+    assert_eq!(period.received, 10000.0);
+    assert_eq!(period.outstanding, Some(0.0));
+
+    clients::delete_payment(&mut conn, &second.id).unwrap();
+    let period = &clients::payment_periods(&conn, "c1").unwrap()[0];
+    assert!(!period.payment.paid);
+    assert_eq!(period.payment.paid_at, None);
+    assert_eq!(period.outstanding, Some(6000.0));
+}
+
+#[test]
+fn transactions_never_unmark_a_period_paid_by_hand() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    clients::set_payment(&conn, "c1", "2026-02", true).unwrap();
+
+    let partial = clients::record_payment(&mut conn, payment("2026-02", "2026-02-08", 4000.0, None)).unwrap();
+    let period = &clients::payment_periods(&conn, "c1").unwrap()[0];
+    assert!(period.payment.paid);
+    assert_eq!(period.outstanding, Some(0.0));
+
+    clients::delete_payment(&mut conn, &partial.id).unwrap();
+    assert!(clients::payment_periods(&conn, "c1").unwrap()[0].payment.paid);
+
+    // A receipt that cannot be converted neither settles nor unsettles.
+    clients::record_payment(&mut conn, payment("2026-03", "2026-03-08", 200.0, Some("EUR"))).unwrap();
+    let march = &clients::payment_periods(&conn, "c1").unwrap()[0];
+    assert_eq!(march.payment.period, "2026-03");
+    assert!(!march.payment.paid);
+    clients::set_payment(&conn, "c1", "2026-03", true).unwrap();
+    let extra = clients::record_payment(&mut conn, payment("2026-03", "2026-03-09", 100.0, None)).unwrap();
+    clients::delete_payment(&mut conn, &extra.id).unwrap();
+    assert!(clients::payment_periods(&conn, "c1").unwrap()[0].payment.paid);
+}
+
+#[test]
+fn invalid_payments_are_rejected() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+
+    for input in [
+        payment("2026-02", "2026-02-08", 0.0, None),
+        payment("2026-02", "2026-02-08", -5.0, None),
+        payment("2026-13", "2026-02-08", 100.0, None),
+        payment("2026-02", "08.02.2026", 100.0, None),
+        payment("2026-02", "2026-02-08", 100.0, Some("XXX")),
+    ] {
+        let err = clients::record_payment(&mut conn, input).unwrap_err();
+        assert_eq!(err.code(), "validation");
+    }
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM client_payments", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn dashboard_counts_partial_payments() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    clients::record_payment(&mut conn, payment("2026-02", "2026-02-08", 2500.0, None)).unwrap();
+
+    let dash = dashboard::build_dashboard(&conn, day("2026-02-01"), day("2026-02-28"), "RUB", day("2026-03-01"))
+        .unwrap();
+    assert_eq!(dash.income[0].expected, 10000.0);
+    assert_eq!(dash.income[0].received, 2500.0);
+    assert_eq!(dash.overdue.len(), 1);
+    assert_eq!(dash.overdue[0].amount, 7500.0);
+    assert_eq!(dash.overdue_total, 7500.0);
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use blueprint_lib::commands::profile::{
+    apply_import, build_export, build_preview, decrypt_payload, parse_profile, ImportOptions,
+    ENCRYPTED_FORMAT,
+};
+use common::{seeded_db, test_vault, TempDir};
+use rusqlite::Connection;
+
+const SEED: &str = "
+    INSERT INTO services (id, project_name, service_name, login, expires_at, created_at)
+    VALUES ('s1', 'Site', 'Hosting', 'admin@example.com', '2026-12-01',
+            '2026-01-01T00:00:00+00:00');
+";
+
+// The seed plus an app secret and a service password in the vault.
+fn seeded_with_secrets(dir: &TempDir) -> Connection {
+    let conn = seeded_db(dir, SEED);
+    let vault = test_vault();
+    vault.set(&conn, "ai_api_key", "sk-local").unwrap();
+    vault.set(&conn, "service:s1:password", "hunter2").unwrap();
+    conn
+}
+
+fn service_login(conn: &Connection) -> Option<String> {
+    conn.query_row("SELECT login FROM services WHERE id = 's1'", [], |row| {
+        row.get(0)
+    })
+    .unwrap()
+}
+
+#[test]
+fn encrypted_export_round_trips_with_passphrase() {
+    let dir = TempDir::new();
+    let conn = seeded_with_secrets(&dir);
+    let encrypted = build_export(&conn, &test_vault())
+        .unwrap()
+        .encrypt("correct horse")
+        .unwrap();
+    let payload = serde_json::to_value(&encrypted).unwrap();
+
+    assert_eq!(payload["format"], ENCRYPTED_FORMAT);
+    assert!(!payload.to_string().contains("sk-local"));
+    assert!(!payload.to_string().contains("admin@example.com"));
+    assert!(!payload.to_string().contains("hunter2"));
+
+    let profile = parse_profile(decrypt_payload(payload, Some("correct horse")).unwrap()).unwrap();
+    assert_eq!(
+        profile.services[0].login.as_deref(),
+        Some("admin@example.com")
+    );
+    assert!(profile
+        .secrets
+        .iter()
+        .any(|s| s.name == "ai_api_key" && s.value == "sk-local"));
+}
+
+#[test]
+fn wrong_or_missing_passphrase_is_reported() {
+    let dir = TempDir::new();
+    let conn = seeded_with_secrets(&dir);
+    let encrypted = build_export(&conn, &test_vault())
+        .unwrap()
+        .encrypt("correct horse")
+        .unwrap();
+    let payload = serde_json::to_value(&encrypted).unwrap();
+
+    let err = decrypt_payload(payload.clone(), Some("wrong")).unwrap_err();
+    assert_eq!(err.field.as_deref(), Some("passphrase"));
+
+    let preview = build_preview(&conn, &test_vault(), payload, &ImportOptions::default()).unwrap();
+    assert!(!preview.valid);
+    assert_eq!(preview.issues[0].field.as_deref(), Some("passphrase"));
+}
+
+#[test]
+fn oversized_kdf_parameters_are_refused() {
+    let dir = TempDir::new();
+    let conn = seeded_with_secrets(&dir);
+    let encrypted = build_export(&conn, &test_vault())
+        .unwrap()
+        .encrypt("correct horse")
+        .unwrap();
+    let payload = serde_json::to_value(&encrypted).unwrap();
+
+    for (field, value) in [("memory_kib", 64 * 1024 * 1024), ("iterations", 1_000_000), ("parallelism", 255)] {
+        let mut crafted = payload.clone();
+        crafted["kdf"][field] = value.into();
+        let err = decrypt_payload(crafted, Some("correct horse")).unwrap_err();
+        assert_eq!(err.field.as_deref(), Some("passphrase"), "{field}");
+        assert!(err.message.contains("слишком велики") || err.message.contains("too large"), "{field}");
+    }
+}
+
+#[test]
+fn stripped_export_keeps_local_secrets_on_import() {
+    let dir = TempDir::new();
+    let mut conn = seeded_with_secrets(&dir);
+    let mut export = build_export(&conn, &test_vault()).unwrap();
+    export.strip_secrets();
+
+    assert!(!export.secrets_included);
+    assert!(export.secrets.is_empty());
+    assert_eq!(export.services[0].login, None);
+
+    apply_import(&mut conn, &test_vault(), &export, ImportOptions::default()).unwrap();
+
+    let vault = test_vault();
+    assert_eq!(
+        vault.get(&conn, "ai_api_key").unwrap().as_deref(),
+        Some("sk-local")
+    );
+    assert_eq!(
+        vault.get(&conn, "service:s1:password").unwrap().as_deref(),
+        Some("hunter2")
+    );
+    assert_eq!(service_login(&conn).as_deref(), Some("admin@example.com"));
+}
@@ -0,0 +1,308 @@
+mod common;
+
+use blueprint_lib::commands::profile::{
+    apply_import, build_export, build_preview, parse_profile, ConflictResolution,
+    ConflictStrategy, ImportMode, ImportOptions, ProfileExport, EXPORT_VERSION,
+};
+use blueprint_lib::commands::clients::PaymentType;
+use blueprint_lib::db;
+use common::{seeded_db, test_vault, TempDir};
+use rusqlite::Connection;
+use serde_json::json;
+
+const SEED: &str = "
+    INSERT INTO clients (id, name, payment_type, currency, created_at)
+    VALUES ('c1', 'Local name', 'monthly', 'RUB', '2026-01-01T00:00:00+00:00');
+    INSERT INTO notes (id, title, completed, sort_order, created_at, updated_at)
+    VALUES ('n1', 'Local note', 0, 0, '2026-01-01T00:00:00+00:00', '2026-05-01T00:00:00+00:00');
+";
+
+// Written in the version 1 layout so every test also exercises the upgrade path.
+fn incoming() -> ProfileExport {
+    parse_profile(json!({
+        "version": 1,
+        "exported_at": "2026-06-01T00:00:00+00:00",
+        "settings": [],
+        "clients": [
+            { "id": "c1", "name": "Incoming name", "payment_type": "monthly", "currency": "RUB",
+              "created_at": "2026-01-01T00:00:00+00:00" },
+            { "id": "c2", "name": "New client", "payment_type": "onetime", "currency": "USD",
+              "created_at": "2026-02-01T00:00:00+00:00" }
+        ],
+        "client_payments": [],
+        "services": [],
+        "categories": [],
+        "notes": [
+            { "id": "n1", "title": "Newer note", "completed": 1, "sort_order": 0,
+              "created_at": "2026-01-01T00:00:00+00:00", "updated_at": "2026-06-01T00:00:00+00:00" }
+        ],
+        "chat_history": []
+    }))
+    .unwrap()
+}
+
+fn client_name(conn: &Connection, id: &str) -> String {
+    conn.query_row("SELECT name FROM clients WHERE id = ?1", [id], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn merge_keep_newest_uses_timestamps() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+
+    let report = apply_import(
+        &mut conn,
+        &test_vault(),
+        &incoming(),
+        ImportOptions {
+            mode: ImportMode::Merge,
+            strategy: ConflictStrategy::Newest,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let clients = report.tables.iter().find(|t| t.table == "clients").unwrap();
+    assert_eq!((clients.added, clients.updated, clients.conflicts), (1, 0, 1));
+    assert_eq!(client_name(&conn, "c1"), "Local name");
+    assert_eq!(client_name(&conn, "c2"), "New client");
+
+    let notes = report.tables.iter().find(|t| t.table == "notes").unwrap();
+    assert_eq!((notes.updated, notes.conflicts), (1, 1));
+    let title: String = conn
+        .query_row("SELECT title FROM notes WHERE id = 'n1'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(title, "Newer note");
+
+    assert_eq!(report.conflicts.len(), 2);
+    assert!(report
+        .conflicts
+        .iter()
+        .any(|c| c.id == "c1" && matches!(c.resolution, ConflictResolution::KeptLocal)));
+}
+
+#[test]
+fn merge_keep_incoming_overwrites_and_keeps_local_only_rows() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    conn.execute_batch(
+        "INSERT INTO clients (id, name, payment_type, currency, created_at)
+         VALUES ('c3', 'Local only', 'monthly', 'RUB', '2026-01-01T00:00:00+00:00');",
+    )
+    .unwrap();
+
+    apply_import(
+        &mut conn,
+        &test_vault(),
+        &incoming(),
+        ImportOptions {
+            mode: ImportMode::Merge,
+            strategy: ConflictStrategy::Incoming,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(client_name(&conn, "c1"), "Incoming name");
+    assert_eq!(client_name(&conn, "c3"), "Local only");
+}
+
+#[test]
+fn merge_of_own_export_changes_nothing() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    let export = build_export(&conn, &test_vault()).unwrap();
+
+    let report = apply_import(
+        &mut conn,
+        &test_vault(),
+        &export,
+        ImportOptions {
+            mode: ImportMode::Merge,
+            strategy: ConflictStrategy::Incoming,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert!(report.conflicts.is_empty());
+    assert!(report.tables.iter().all(|t| t.added == 0 && t.updated == 0));
+}
+
+#[test]
+fn replace_mode_wipes_local_rows() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+
+    apply_import(&mut conn, &test_vault(), &incoming(), ImportOptions::default()).unwrap();
+
+    let count: i32 = conn
+        .query_row("SELECT COUNT(*) FROM clients", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(client_name(&conn, "c1"), "Incoming name");
+    assert!(db::get_setting(&conn, "theme").is_none());
+}
+
+#[test]
+fn preview_reports_diff_without_writing() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+
+    let payload = serde_json::to_value(incoming()).unwrap();
+    let preview = build_preview(&conn, &test_vault(), payload, &ImportOptions::default()).unwrap();
+
+    assert!(preview.valid);
+    let clients = preview.tables.iter().find(|t| t.table == "clients").unwrap();
+    assert_eq!(
+        (clients.added, clients.changed, clients.unchanged, clients.removed),
+        (1, 1, 0, 0)
+    );
+    let settings = preview.tables.iter().find(|t| t.table == "settings").unwrap();
+    assert!(settings.removed > 0);
+    assert_eq!(client_name(&conn, "c1"), "Local name");
+}
+
+#[test]
+fn preview_flags_invalid_rows() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    let payload = json!({
+        "version": EXPORT_VERSION,
+        "exported_at": "2026-06-01T00:00:00+00:00",
+        "secrets_included": true,
+        "secrets": [],
+        "settings": [],
+        "clients": [
+            { "id": "c9", "name": "Bad date", "contact": null, "payment_type": "monthly",
+              "amount": null, "currency": "RUB", "notes": null, "payment_day": null,
+              "created_at": "yesterday" }
+        ],
+        "client_payments": [
+            { "id": "p9", "client_id": "missing", "period": "2026-13", "paid": false,
+              "paid_at": null }
+        ],
+        "services": [],
+        "service_renewals": [],
+        "service_charges": [], "fx_rates": [], "invoices": [], "invoice_items": [], "payment_transactions": [], "time_entries": [],
+        "notification_rules": [],
+        "categories": [],
+        "notes": [],
+        "chat_history": []
+    });
+
+    let preview = build_preview(
+        &conn,
+        &test_vault(),
+        payload,
+        &ImportOptions {
+            mode: ImportMode::Merge,
+            ..Default::default()
+        },
+    ).unwrap();
+
+    assert!(!preview.valid);
+    assert!(preview.version_supported);
+    let fields: Vec<(&str, Option<&str>)> = preview
+        .issues
+        .iter()
+        .map(|i| (i.table.as_str(), i.field.as_deref()))
+        .collect();
+    assert!(fields.contains(&("clients", Some("created_at"))));
+    assert!(fields.contains(&("client_payments", Some("client_id"))));
+    assert!(fields.contains(&("client_payments", Some("period"))));
+}
+
+#[test]
+fn misspelled_field_is_reported_with_path() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    let mut payload = serde_json::to_value(incoming()).unwrap();
+    payload["clients"][1]["nmae"] = json!("Typo");
+
+    let preview = build_preview(&conn, &test_vault(), payload, &ImportOptions::default()).unwrap();
+
+    assert!(!preview.valid);
+    assert_eq!(preview.issues.len(), 1);
+    assert_eq!(preview.issues[0].field.as_deref(), Some("clients[1].nmae"));
+    assert!(preview.issues[0].message.contains("nmae"));
+}
+
+#[test]
+fn unknown_version_is_rejected() {
+    let err = parse_profile(json!({ "version": EXPORT_VERSION + 1 })).unwrap_err();
+    assert_eq!(err.field.as_deref(), Some("version"));
+
+    let err = parse_profile(json!({ "exported_at": "2026-01-01T00:00:00+00:00" })).unwrap_err();
+    assert_eq!(err.field.as_deref(), Some("version"));
+}
+
+#[test]
+fn v1_export_is_upgraded_to_typed_rows() {
+    let profile = incoming();
+
+    assert_eq!(profile.version, EXPORT_VERSION);
+    assert!(profile.notes[0].completed);
+    assert_eq!(profile.clients[0].contact, None);
+}
+
+#[test]
+fn pre_billing_model_export_normalizes_payment_types() {
+    let profile = parse_profile(json!({
+        "version": 14,
+        "exported_at": "2026-06-01T00:00:00+00:00",
+        "secrets_included": false,
+        "settings": [],
+        "secrets": [],
+        "clients": [
+            { "id": "c1", "name": "Typo", "payment_type": "Monthly", "currency": "RUB",
+              "created_at": "2026-01-01T00:00:00+00:00" },
+            { "id": "c2", "name": "Once", "payment_type": "onetime", "currency": "USD",
+              "created_at": "2026-02-01T00:00:00+00:00" }
+        ],
+        "client_payments": [],
+        "payment_transactions": [],
+        "invoices": [],
+        "invoice_items": [],
+        "services": [],
+        "service_renewals": [],
+        "service_charges": [],
+        "fx_rates": [],
+        "notification_rules": [],
+        "categories": [],
+        "notes": [],
+        "chat_history": []
+    }))
+    .unwrap();
+
+    assert_eq!(profile.clients[0].payment_type, PaymentType::Monthly);
+    assert_eq!(profile.clients[1].payment_type, PaymentType::Onetime);
+}
+
+#[test]
+fn export_round_trips_through_json() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    let export = build_export(&conn, &test_vault()).unwrap();
+
+    let parsed = parse_profile(serde_json::to_value(&export).unwrap()).unwrap();
+
+    assert_eq!(parsed.clients.len(), 1);
+    assert_eq!(parsed.notes[0].title, "Local note");
+}
+
+#[test]
+fn invalid_import_is_rejected_before_writing() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    let mut payload = incoming();
+    payload.notes[0].category_id = Some("nope".to_string());
+
+    let err = apply_import(&mut conn, &test_vault(), &payload, ImportOptions::default()).unwrap_err();
+
+    assert_eq!(err.code(), "validation");
+    assert!(err.to_string().contains("notes[0].category_id"));
+    assert_eq!(err.details().unwrap()[0]["field"], "category_id");
+    assert_eq!(client_name(&conn, "c1"), "Local name");
+}
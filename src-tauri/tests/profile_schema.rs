@@ -0,0 +1,22 @@
+use blueprint_lib::commands::profile::profile_schema;
+use std::path::PathBuf;
+
+// The published schema must match the export structs. Regenerate it with
+// `BLUEPRINT_UPDATE_SCHEMA=1 cargo test --test profile_schema`.
+#[test]
+fn published_schema_is_up_to_date() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schema/profile-export.schema.json");
+    let generated = serde_json::to_string_pretty(&profile_schema()).unwrap() + "\n";
+
+    if std::env::var_os("BLUEPRINT_UPDATE_SCHEMA").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &generated).unwrap();
+        return;
+    }
+
+    let published = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        published == generated,
+        "schema/profile-export.schema.json is stale, rerun with BLUEPRINT_UPDATE_SCHEMA=1"
+    );
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use blueprint_lib::commands::services::{self, BillingCycle};
+use blueprint_lib::error::BlueprintError;
+use common::{day, seeded_db, TempDir};
+use rusqlite::Connection;
+
+const SEED: &str = "
+    INSERT INTO services (id, project_name, service_name, expires_at, cost, currency,
+                          created_at, billing_cycle, billing_cycle_days, auto_renew)
+    VALUES ('s1', 'Site', 'Hosting', '2026-01-31', 10, 'USD', '2026-01-01T00:00:00+00:00', 'monthly', NULL, 1),
+           ('s2', 'Site', 'Domain', '2026-03-01', 15, 'USD', '2026-01-01T00:00:00+00:00', 'yearly', NULL, 0),
+           ('s3', 'Site', 'CDN', '2026-03-01', 5, 'EUR', '2026-01-01T00:00:00+00:00', NULL, NULL, 0);
+";
+
+fn expires_at(conn: &Connection, id: &str) -> String {
+    conn.query_row("SELECT expires_at FROM services WHERE id = ?1", [id], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn cycles_advance_by_calendar_units() {
+    let jan31 = day("2026-01-31");
+    assert_eq!(BillingCycle::Monthly.advance(jan31, jan31), Some(day("2026-02-28")));
+    // Later steps go back to the anchor's day instead of drifting to the 28th.
+    assert_eq!(BillingCycle::Monthly.advance(jan31, day("2026-02-28")), Some(day("2026-03-31")));
+    assert_eq!(BillingCycle::Monthly.advance(jan31, day("2026-03-31")), Some(day("2026-04-30")));
+    assert_eq!(BillingCycle::Monthly.advance(jan31, day("2026-04-30")), Some(day("2026-05-31")));
+    assert_eq!(BillingCycle::Quarterly.advance(day("2026-11-30"), day("2026-11-30")), Some(day("2027-02-28")));
+    assert_eq!(BillingCycle::Quarterly.advance(day("2026-11-30"), day("2027-02-28")), Some(day("2027-05-30")));
+    assert_eq!(BillingCycle::Yearly.advance(day("2028-02-29"), day("2029-02-28")), Some(day("2030-02-28")));
+    assert_eq!(BillingCycle::Yearly.advance(day("2028-02-29"), day("2031-02-28")), Some(day("2032-02-29")));
+    assert_eq!(BillingCycle::Days(45).advance(day("2026-01-01"), day("2026-12-20")), Some(day("2027-02-03")));
+
+    assert_eq!(BillingCycle::from_columns(Some("custom"), Some(30)), Some(BillingCycle::Days(30)));
+    assert_eq!(BillingCycle::from_columns(Some("custom"), None), None);
+    assert_eq!(BillingCycle::from_columns(Some("weekly"), None), None);
+    assert_eq!(BillingCycle::from_columns(None, Some(30)), None);
+}
+
+#[test]
+fn renewing_advances_expiry_and_records_history() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    conn.execute("UPDATE services SET snoozed_until = '2026-02-20' WHERE id = 's2'", [])
+        .unwrap();
+
+    let renewal = services::renew(&mut conn, "s2", false).unwrap();
+    assert_eq!(renewal.previous_expires_at, "2026-03-01");
+    assert_eq!(renewal.expires_at, "2027-03-01");
+    assert_eq!(renewal.cost, Some(15.0));
+    assert!(!renewal.auto_renewed);
+    assert_eq!(expires_at(&conn, "s2"), "2027-03-01");
+
+    let snoozed: Option<String> = conn
+        .query_row("SELECT snoozed_until FROM services WHERE id = 's2'", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(snoozed, None);
+
+    let err = BlueprintError::from(services::renew(&mut conn, "s3", false).unwrap_err());
+    assert_eq!(err.code(), "validation");
+    let err = BlueprintError::from(services::renew(&mut conn, "missing", false).unwrap_err());
+    assert_eq!(err.code(), "not_found");
+}
+
+#[test]
+fn auto_renew_catches_up_one_cycle_at_a_time() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+
+    let renewals = services::roll_forward(&mut conn, day("2026-04-15")).unwrap();
+    let dates: Vec<&str> = renewals.iter().map(|r| r.expires_at.as_str()).collect();
+    // Crossing February does not pull the 31st back to the 28th.
+    assert_eq!(dates, ["2026-02-28", "2026-03-31", "2026-04-30"]);
+    assert!(renewals.iter().all(|r| r.auto_renewed && r.service_id == "s1"));
+    // s2 is past due too but renews by hand only.
+    assert_eq!(expires_at(&conn, "s2"), "2026-03-01");
+
+    assert!(services::roll_forward(&mut conn, day("2026-04-30")).unwrap().is_empty());
+    let next = services::renew(&mut conn, "s1", false).unwrap();
+    assert_eq!(next.expires_at, "2026-05-31");
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM service_renewals", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 4);
+
+    // A hand-edited expiry date starts a new run.
+    conn.execute("UPDATE services SET expires_at = '2026-06-30' WHERE id = 's1'", []).unwrap();
+    assert_eq!(services::renew(&mut conn, "s1", false).unwrap().expires_at, "2026-07-30");
+}
@@ -0,0 +1,214 @@
+mod common;
+
+use blueprint_lib::commands::clients::PaymentType;
+use blueprint_lib::commands::schedule::{self, Period, PeriodKind, PeriodStatus, ScheduleEntry};
+use blueprint_lib::scheduler;
+use common::{day, seeded_db, TempDir};
+
+const SEED: &str = "
+    INSERT INTO clients (id, name, payment_type, amount, currency, payment_day, created_at,
+                         start_date, end_date, pause_from, pause_to)
+    VALUES ('c1', 'Acme', 'monthly', 100, 'USD', 31, '2026-01-15T00:00:00+00:00', NULL, NULL, NULL, NULL),
+           ('c2', 'Globex', 'monthly', 200, 'USD', 10, '2025-06-01T00:00:00+00:00',
+            '2026-01-20', '2026-07-15', '2026-04-01', '2026-05-31'),
+           ('c3', 'Initech', 'onetime', 50, 'USD', NULL, '2026-01-01T00:00:00+00:00', NULL, NULL, NULL, NULL),
+           ('c4', 'Hooli', 'monthly', NULL, 'USD', 5, '2026-01-01T00:00:00+00:00',
+            NULL, NULL, '2026-02-01', NULL);
+    INSERT INTO client_payments (id, client_id, period, paid)
+    VALUES ('p1', 'c1', '2026-01', 1),
+           ('p2', 'c2', '2026-04', 1),
+           ('p3', 'c3', '2026-03-01', 0),
+           ('p4', 'c3', '2026-04-01', 1);
+";
+
+fn summary(entries: &[ScheduleEntry]) -> Vec<(&str, &str, PeriodStatus, bool)> {
+    entries
+        .iter()
+        .map(|e| (e.period.as_str(), e.due_date.as_str(), e.status, e.scheduled))
+        .collect()
+}
+
+#[test]
+fn month_end_payment_day_is_clamped_and_periods_get_a_status() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+
+    let entries = schedule::client_schedule(&conn, "c1", day("2025-12-01"), day("2026-04-30"), day("2026-03-10"))
+        .unwrap();
+    assert_eq!(
+        summary(&entries),
+        vec![
+            ("2026-01", "2026-01-31", PeriodStatus::Paid, true),
+            ("2026-02", "2026-02-28", PeriodStatus::Overdue, true),
+            ("2026-03", "2026-03-31", PeriodStatus::Unpaid, true),
+            ("2026-04", "2026-04-30", PeriodStatus::Upcoming, true),
+        ]
+    );
+    assert_eq!(entries[0].payment_id.as_deref(), Some("p1"));
+    assert_eq!(entries[1].outstanding, Some(100.0));
+    assert_eq!(entries[1].payment_id, None);
+
+    let leap = schedule::client_schedule(&conn, "c1", day("2028-02-01"), day("2028-02-29"), day("2026-03-10"))
+        .unwrap();
+    assert_eq!(leap[0].due_date, "2028-02-29");
+}
+
+#[test]
+fn start_end_and_pause_limit_the_schedule() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+
+    let entries = schedule::client_schedule(&conn, "c2", day("2026-01-01"), day("2026-12-31"), day("2026-06-01"))
+        .unwrap();
+    assert_eq!(
+        summary(&entries),
+        vec![
+            ("2026-02", "2026-02-10", PeriodStatus::Overdue, true),
+            ("2026-03", "2026-03-10", PeriodStatus::Overdue, true),
+            // Paid during the pause: shown, but not expected.
+            ("2026-04", "2026-04-10", PeriodStatus::Paid, false),
+            ("2026-06", "2026-06-10", PeriodStatus::Unpaid, true),
+            ("2026-07", "2026-07-10", PeriodStatus::Upcoming, true),
+        ]
+    );
+
+    // A pause without an end goes on indefinitely.
+    let open_pause = schedule::client_schedule(&conn, "c4", day("2026-01-01"), day("2026-06-30"), day("2026-03-01"))
+        .unwrap();
+    assert_eq!(summary(&open_pause), vec![("2026-01", "2026-01-05", PeriodStatus::Overdue, true)]);
+    assert_eq!(open_pause[0].outstanding, None);
+}
+
+#[test]
+fn one_time_clients_list_recorded_dates() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+
+    let entries = schedule::client_schedule(&conn, "c3", day("2026-03-01"), day("2026-04-30"), day("2026-03-15"))
+        .unwrap();
+    assert_eq!(
+        summary(&entries),
+        vec![
+            ("2026-03-01", "2026-03-01", PeriodStatus::Overdue, true),
+            ("2026-04-01", "2026-04-01", PeriodStatus::Paid, true),
+        ]
+    );
+
+    let reversed = schedule::client_schedule(&conn, "c3", day("2026-04-30"), day("2026-03-01"), day("2026-03-15"));
+    assert_eq!(reversed.unwrap_err().code(), "validation");
+    let missing = schedule::client_schedule(&conn, "nope", day("2026-03-01"), day("2026-04-30"), day("2026-03-15"));
+    assert_eq!(missing.unwrap_err().code(), "not_found");
+}
+
+#[test]
+fn reminders_skip_paused_and_ended_periods() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+
+    let during_pause = scheduler::payment_reminders(&conn, day("2026-05-12")).unwrap();
+    assert!(during_pause.iter().all(|r| r.entity_id != "c2"));
+    let after_end = scheduler::payment_reminders(&conn, day("2026-09-10")).unwrap();
+    assert!(after_end.iter().all(|r| r.entity_id != "c2"));
+    let active = scheduler::payment_reminders(&conn, day("2026-06-10")).unwrap();
+    assert!(active.iter().any(|r| r.entity_id == "c2" && r.due == "2026-06"));
+}
+
+#[test]
+fn period_keys_round_trip_for_every_model() {
+    for (key, kind, start, end) in [
+        ("2026-03-15", PeriodKind::Day, "2026-03-15", "2026-03-15"),
+        ("2026-W01", PeriodKind::Week, "2025-12-29", "2026-01-04"),
+        ("2026-02", PeriodKind::Month, "2026-02-01", "2026-02-28"),
+        ("2026-Q4", PeriodKind::Quarter, "2026-10-01", "2026-12-31"),
+        ("2028", PeriodKind::Year, "2028-01-01", "2028-12-31"),
+    ] {
+        let period = Period::parse(key).unwrap();
+        assert_eq!(period.kind, kind);
+        assert_eq!(period.start, day(start));
+        assert_eq!(period.end(), day(end));
+        assert_eq!(period.key(), key);
+        assert_eq!(Period::containing(kind, day(end)), period);
+    }
+    assert_eq!(Period::parse("2026-Q1").unwrap().prev().unwrap().key(), "2025-Q4");
+    assert_eq!(Period::parse("2026-W53").unwrap().end(), day("2027-01-03"));
+    assert_eq!(Period::parse("2025-W53"), None);
+    for bad in ["2026-3", "2026-Q5", "2026-13", "26", "2026-W", "soon"] {
+        assert_eq!(Period::parse(bad), None, "{bad}");
+    }
+}
+
+#[test]
+fn weekly_quarterly_and_yearly_clients_get_their_own_periods() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    conn.execute_batch(
+        "
+        INSERT INTO clients (id, name, payment_type, amount, currency, payment_day, created_at)
+        VALUES ('w', 'Weekly', 'weekly', 10, 'USD', 5, '2026-03-01T00:00:00+00:00'),
+               ('q', 'Quarterly', 'quarterly', 300, 'USD', 31, '2026-01-01T00:00:00+00:00'),
+               ('y', 'Yearly', 'yearly', 1200, 'USD', NULL, '2025-06-01T00:00:00+00:00');
+        INSERT INTO clients (id, name, payment_type, amount, currency, payment_day, created_at,
+                             included_hours, hourly_rate)
+        VALUES ('r', 'Retainer', 'retainer', 500, 'USD', 1, '2026-01-01T00:00:00+00:00', 10, 60),
+               ('h', 'Hourly', 'hourly', NULL, 'USD', NULL, '2026-01-01T00:00:00+00:00', NULL, 40);
+        INSERT INTO client_payments (id, client_id, period, paid) VALUES ('pw', 'w', '2026-W10', 1);
+        ",
+    )
+    .unwrap();
+
+    let weekly = schedule::client_schedule(&conn, "w", day("2026-03-01"), day("2026-03-22"), day("2026-03-18"))
+        .unwrap();
+    assert_eq!(
+        summary(&weekly),
+        vec![
+            // 2026-W09 ends on Mar 1, but its Friday came before the client did.
+            ("2026-W10", "2026-03-06", PeriodStatus::Paid, true),
+            ("2026-W11", "2026-03-13", PeriodStatus::Overdue, true),
+            ("2026-W12", "2026-03-20", PeriodStatus::Unpaid, true),
+        ]
+    );
+
+    let quarterly = schedule::client_schedule(&conn, "q", day("2026-01-01"), day("2026-12-31"), day("2026-05-01"))
+        .unwrap();
+    assert_eq!(
+        summary(&quarterly),
+        vec![
+            ("2026-Q1", "2026-01-31", PeriodStatus::Overdue, true),
+            ("2026-Q2", "2026-04-30", PeriodStatus::Overdue, true),
+            ("2026-Q3", "2026-07-31", PeriodStatus::Upcoming, true),
+            ("2026-Q4", "2026-10-31", PeriodStatus::Upcoming, true),
+        ]
+    );
+
+    let yearly = schedule::client_schedule(&conn, "y", day("2025-01-01"), day("2026-12-31"), day("2026-05-01"))
+        .unwrap();
+    assert_eq!(
+        summary(&yearly),
+        vec![
+            ("2025", "2025-12-31", PeriodStatus::Overdue, true),
+            ("2026", "2026-12-31", PeriodStatus::Unpaid, true),
+        ]
+    );
+
+    let retainer = schedule::client_schedule(&conn, "r", day("2026-02-01"), day("2026-02-28"), day("2026-05-01"))
+        .unwrap();
+    assert_eq!(retainer[0].period, "2026-02");
+    assert_eq!(retainer[0].expected, Some(500.0));
+    let hourly = schedule::client_schedule(&conn, "h", day("2026-02-01"), day("2026-02-28"), day("2026-05-01"))
+        .unwrap();
+    assert_eq!(summary(&hourly), vec![("2026-02", "2026-02-28", PeriodStatus::Overdue, true)]);
+    assert_eq!(hourly[0].expected, None);
+
+    let reminders = scheduler::payment_reminders(&conn, day("2026-03-18")).unwrap();
+    assert!(reminders.iter().any(|r| r.entity_id == "w" && r.due == "2026-W11" && r.threshold == "overdue"));
+    assert!(reminders.iter().any(|r| r.entity_id == "w" && r.due == "2026-W12" && r.threshold == "before"));
+}
+
+#[test]
+fn payment_types_are_parsed_strictly() {
+    assert_eq!(PaymentType::parse(" weekly ").unwrap(), PaymentType::Weekly);
+    assert_eq!(PaymentType::parse("Monthly").unwrap_err().code(), "validation");
+    assert_eq!(PaymentType::Retainer.period_kind(), Some(PeriodKind::Month));
+    assert_eq!(PaymentType::Onetime.period_kind(), None);
+    assert_eq!(serde_json::to_value(PaymentType::Hourly).unwrap(), "hourly");
+}
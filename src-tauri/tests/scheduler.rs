@@ -0,0 +1,297 @@
+mod common;
+
+use blueprint_lib::db;
+use blueprint_lib::scheduler::{self, QuietHours};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+use common::{open_db, seeded_db, TempDir};
+
+const SEED: &str = "
+    INSERT INTO services (id, project_name, service_name, expires_at, notify_days, created_at)
+    VALUES ('s1', 'Site', 'Hosting', '2026-02-26', 7, '2026-01-01T00:00:00+00:00'),
+           ('s2', 'Site', 'Domain', '2026-12-01', 7, '2026-01-01T00:00:00+00:00');
+    INSERT INTO clients (id, name, payment_type, amount, currency, payment_day, created_at)
+    VALUES ('c1', 'Acme', 'monthly', 100, 'USD', 31, '2026-01-01T00:00:00+00:00'),
+           ('c2', 'Paid Inc', 'monthly', 50, 'USD', 1, '2026-01-01T00:00:00+00:00');
+    INSERT INTO client_payments (id, client_id, period, paid)
+    VALUES ('p0', 'c1', '2026-01', 1), ('p1', 'c2', '2026-01', 1), ('p2', 'c2', '2026-02', 1);
+";
+
+fn local(s: &str) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap())
+        .unwrap()
+}
+
+fn time(s: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+}
+
+#[test]
+fn quiet_hours_can_cross_midnight() {
+    let night = QuietHours {
+        start: time("22:00"),
+        end: time("08:00"),
+    };
+    assert!(night.contains(time("23:30")));
+    assert!(night.contains(time("07:59")));
+    assert!(!night.contains(time("08:00")));
+    assert!(!night.contains(time("12:00")));
+
+    let lunch = QuietHours {
+        start: time("12:00"),
+        end: time("13:00"),
+    };
+    assert!(lunch.contains(time("12:30")));
+    assert!(!lunch.contains(time("23:00")));
+
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+    assert_eq!(QuietHours::from_settings(&conn), None);
+    db::set_setting(&conn, "quiet_hours_start", "22:00").unwrap();
+    db::set_setting(&conn, "quiet_hours_end", "08:00").unwrap();
+    assert_eq!(QuietHours::from_settings(&conn), Some(night));
+}
+
+#[test]
+fn due_date_is_clamped_to_short_months() {
+    assert_eq!(
+        scheduler::due_date(2026, 2, 31),
+        NaiveDate::from_ymd_opt(2026, 2, 28)
+    );
+    assert_eq!(
+        scheduler::due_date(2028, 2, 30),
+        NaiveDate::from_ymd_opt(2028, 2, 29)
+    );
+    assert_eq!(
+        scheduler::due_date(2026, 4, 15),
+        NaiveDate::from_ymd_opt(2026, 4, 15)
+    );
+}
+
+#[test]
+fn expiring_services_and_unpaid_clients_are_collected() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    let today = NaiveDate::from_ymd_opt(2026, 2, 27).unwrap();
+
+    let services = scheduler::service_reminders(&conn, today).unwrap();
+    assert_eq!(services.len(), 1);
+    assert_eq!(services[0].entity_id, "s1");
+    assert_eq!(services[0].threshold, "expired");
+
+    let upcoming = scheduler::payment_reminders(&conn, today).unwrap();
+    assert_eq!(upcoming.len(), 1);
+    assert_eq!(upcoming[0].entity_id, "c1");
+    assert_eq!(upcoming[0].threshold, "before");
+
+    let last_day = NaiveDate::from_ymd_opt(2026, 2, 28).unwrap();
+    let payments = scheduler::payment_reminders(&conn, last_day).unwrap();
+    assert_eq!(payments.len(), 1);
+    assert_eq!(payments[0].threshold, "due");
+    assert_eq!(payments[0].due, "2026-02");
+    assert!(payments[0].body.contains("100 USD"));
+
+    conn.execute("UPDATE clients SET payment_notify_days = 0 WHERE id = 'c1'", [])
+        .unwrap();
+    assert!(scheduler::payment_reminders(&conn, today).unwrap().is_empty());
+}
+
+#[test]
+fn unpaid_periods_stay_overdue_across_the_month_boundary() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    let march = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+    let overdue = scheduler::payment_reminders(&conn, march).unwrap();
+    let c1: Vec<_> = overdue.iter().filter(|r| r.entity_id == "c1").collect();
+    assert_eq!(c1.len(), 1);
+    assert_eq!(c1[0].threshold, "overdue");
+    assert_eq!(c1[0].due, "2026-02");
+    // c2 pays on the 1st, so March is already late as well.
+    assert!(overdue
+        .iter()
+        .any(|r| r.entity_id == "c2" && r.due == "2026-03" && r.threshold == "overdue"));
+
+    conn.execute(
+        "INSERT INTO client_payments (id, client_id, period, paid) VALUES ('p3', 'c1', '2026-02', 1)",
+        [],
+    )
+    .unwrap();
+    assert!(scheduler::payment_reminders(&conn, march)
+        .unwrap()
+        .iter()
+        .all(|r| r.entity_id != "c1"));
+}
+
+#[test]
+fn clients_without_a_payment_day_are_due_at_period_end() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    conn.execute_batch(
+        "
+        INSERT INTO clients (id, name, payment_type, amount, currency, payment_day, created_at, hourly_rate)
+        VALUES ('w', 'Weekly', 'weekly', 20, 'USD', NULL, '2026-01-01T00:00:00+00:00', NULL),
+               ('h', 'Hourly', 'hourly', NULL, 'USD', NULL, '2026-01-01T00:00:00+00:00', 40);
+        ",
+    )
+    .unwrap();
+
+    // The week of February 23 ended on Sunday, March 1.
+    let reminders = scheduler::payment_reminders(&conn, NaiveDate::from_ymd_opt(2026, 3, 4).unwrap()).unwrap();
+    assert!(reminders.iter().any(|r| r.entity_id == "w" && r.threshold == "overdue"));
+    // February's hours were due on its last day.
+    assert!(reminders
+        .iter()
+        .any(|r| r.entity_id == "h" && r.due == "2026-02" && r.threshold == "overdue"));
+}
+
+#[test]
+fn note_reminders_fire_once_inside_the_window() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+    let remind_at = local("2026-03-01 09:00").to_rfc3339();
+    conn.execute(
+        "INSERT INTO notes (id, title, completed, sort_order, created_at, updated_at, remind_at)
+         VALUES ('n1', 'Call Acme', 0, 0, '2026-01-01T00:00:00+00:00', '2026-01-01T00:00:00+00:00', ?1)",
+        [&remind_at],
+    )
+    .unwrap();
+
+    let before = local("2026-03-01 08:45");
+    let after = local("2026-03-01 09:15");
+    let later = local("2026-03-01 09:30");
+
+    assert!(scheduler::note_reminders(&conn, before - chrono::Duration::minutes(15), before)
+        .unwrap()
+        .is_empty());
+    let due = scheduler::note_reminders(&conn, before, after).unwrap();
+    assert_eq!(due.len(), 1);
+    assert!(due[0].title.contains("Call Acme"));
+    assert!(scheduler::note_reminders(&conn, after, later).unwrap().is_empty());
+}
+
+#[test]
+fn each_threshold_is_logged_and_sent_once() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    let window = NaiveDate::from_ymd_opt(2026, 2, 20).unwrap();
+    let next_day = NaiveDate::from_ymd_opt(2026, 2, 21).unwrap();
+    let expiry = NaiveDate::from_ymd_opt(2026, 2, 26).unwrap();
+
+    let first = scheduler::service_reminders(&conn, window).unwrap();
+    assert!(scheduler::record(&conn, &first[0]).unwrap().is_some());
+    let again = scheduler::service_reminders(&conn, next_day).unwrap();
+    assert!(scheduler::record(&conn, &again[0]).unwrap().is_none());
+
+    let today = scheduler::service_reminders(&conn, expiry).unwrap();
+    assert_eq!(today[0].threshold, "0");
+    assert!(scheduler::record(&conn, &today[0]).unwrap().is_some());
+
+    // Renewing moves `expires_at`, which re-arms the thresholds.
+    conn.execute("UPDATE services SET expires_at = '2027-02-20' WHERE id = 's1'", [])
+        .unwrap();
+    let renewed = scheduler::service_reminders(&conn, NaiveDate::from_ymd_opt(2027, 2, 14).unwrap())
+        .unwrap();
+    assert!(scheduler::record(&conn, &renewed[0]).unwrap().is_some());
+
+    let history = scheduler::history(&conn, 10, false).unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(history.iter().all(|e| e.read_at.is_none()));
+
+    scheduler::mark_read(&conn, &history[0].id).unwrap();
+    let unread = scheduler::history(&conn, 10, true).unwrap();
+    assert_eq!(unread.len(), 2);
+    assert!(unread.iter().all(|e| e.id != history[0].id));
+}
+
+#[test]
+fn snoozed_and_acknowledged_services_stay_silent() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    let today = NaiveDate::from_ymd_opt(2026, 2, 22).unwrap();
+    assert_eq!(scheduler::service_reminders(&conn, today).unwrap().len(), 1);
+
+    conn.execute("UPDATE services SET snoozed_until = '2026-02-24' WHERE id = 's1'", [])
+        .unwrap();
+    assert!(scheduler::service_reminders(&conn, today).unwrap().is_empty());
+    let resumed = NaiveDate::from_ymd_opt(2026, 2, 24).unwrap();
+    assert_eq!(scheduler::service_reminders(&conn, resumed).unwrap().len(), 1);
+
+    conn.execute(
+        "UPDATE services SET acknowledged_expires_at = expires_at WHERE id = 's1'",
+        [],
+    )
+    .unwrap();
+    assert!(scheduler::service_reminders(&conn, resumed).unwrap().is_empty());
+
+    conn.execute("UPDATE services SET expires_at = '2026-03-01' WHERE id = 's1'", [])
+        .unwrap();
+    assert_eq!(scheduler::service_reminders(&conn, resumed).unwrap().len(), 1);
+}
+
+#[test]
+fn days_remaining_counts_calendar_days() {
+    let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    assert_eq!(scheduler::days_remaining("2026-03-01", day(2026, 2, 28)), Some(1));
+    assert_eq!(scheduler::days_remaining("2028-03-01", day(2028, 2, 28)), Some(2));
+    assert_eq!(scheduler::days_remaining("2027-01-01", day(2026, 12, 31)), Some(1));
+    assert_eq!(scheduler::days_remaining("2026-06-15", day(2026, 6, 15)), Some(0));
+    assert_eq!(scheduler::days_remaining("2026-06-14", day(2026, 6, 15)), Some(-1));
+    assert_eq!(scheduler::days_remaining("2027-06-15", day(2026, 6, 15)), Some(365));
+    assert_eq!(scheduler::days_remaining("2026-02-30", day(2026, 2, 1)), None);
+    assert_eq!(scheduler::days_remaining("soon", day(2026, 2, 1)), None);
+}
+
+#[test]
+fn thresholds_parse_and_resolve() {
+    assert_eq!(
+        scheduler::parse_thresholds(" 1, 30,7,0,7 "),
+        Some(vec![30, 7, 1, 0])
+    );
+    assert_eq!(scheduler::parse_thresholds("7,-1"), None);
+    assert_eq!(scheduler::parse_thresholds("week"), None);
+    assert_eq!(scheduler::parse_thresholds(""), None);
+
+    let rules = [30, 7, 1, 0];
+    assert_eq!(scheduler::reached_threshold(&rules, 45), None);
+    assert_eq!(scheduler::reached_threshold(&rules, 30), Some(30));
+    assert_eq!(scheduler::reached_threshold(&rules, 12), Some(30));
+    assert_eq!(scheduler::reached_threshold(&rules, 3), Some(7));
+    assert_eq!(scheduler::reached_threshold(&rules, 0), Some(0));
+}
+
+#[test]
+fn service_rule_beats_category_rule_beats_default() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    conn.execute_batch(
+        "
+        UPDATE services SET category = 'hosting' WHERE id IN ('s1', 's2');
+        INSERT INTO notification_rules (category, thresholds) VALUES ('hosting', '14,3');
+        ",
+    )
+    .unwrap();
+
+    let rules = scheduler::NotificationRules::load(&conn).unwrap();
+    assert_eq!(rules.default, vec![30, 7, 1, 0]);
+    assert_eq!(rules.thresholds_for(None, Some("hosting")), vec![14, 3]);
+    assert_eq!(rules.thresholds_for(Some("2"), Some("hosting")), vec![2]);
+    assert_eq!(rules.thresholds_for(None, Some("domains")), vec![30, 7, 1, 0]);
+
+    // s1 expires 2026-02-26: ten days out only the category's 14 applies.
+    let today = NaiveDate::from_ymd_opt(2026, 2, 16).unwrap();
+    let reminders = scheduler::service_reminders(&conn, today).unwrap();
+    assert_eq!(reminders.len(), 1);
+    assert_eq!(reminders[0].threshold, "14");
+
+    conn.execute("UPDATE services SET notify_thresholds = '5' WHERE id = 's1'", [])
+        .unwrap();
+    assert!(scheduler::service_reminders(&conn, today).unwrap().is_empty());
+
+    db::set_setting(&conn, "notify_days_before", "60").unwrap();
+    conn.execute_batch("DELETE FROM notification_rules; UPDATE services SET notify_thresholds = NULL;")
+        .unwrap();
+    let far = scheduler::service_reminders(&conn, today).unwrap();
+    assert_eq!(far.len(), 1);
+    assert_eq!(far[0].threshold, "60");
+}
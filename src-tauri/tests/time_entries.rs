@@ -0,0 +1,240 @@
+mod common;
+
+use blueprint_lib::commands::invoices::{self, CreateInvoiceInput};
+use blueprint_lib::commands::time_entries::{self, Rounding, RoundingMode, StartTimerInput, TimeEntryInput};
+use blueprint_lib::commands::{clients, schedule};
+use blueprint_lib::db;
+use chrono::{DateTime, Utc};
+use common::{day, seeded_db, TempDir};
+
+fn at(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+}
+
+const SEED: &str = "
+    INSERT INTO clients (id, name, payment_type, amount, currency, payment_day, created_at,
+                         included_hours, hourly_rate)
+    VALUES ('h', 'Hourly', 'hourly', NULL, 'USD', NULL, '2026-01-01T00:00:00+00:00', NULL, 40),
+           ('r', 'Retainer', 'retainer', 500, 'USD', 1, '2026-01-01T00:00:00+00:00', 2, 60);
+";
+
+fn entry(client_id: &str, started_at: &str, minutes: i64) -> TimeEntryInput {
+    TimeEntryInput {
+        client_id: client_id.to_string(),
+        project: Some("  site ".to_string()),
+        note: None,
+        started_at: started_at.to_string(),
+        ended_at: None,
+        minutes: Some(minutes),
+        billable: None,
+    }
+}
+
+fn timer(client_id: &str) -> StartTimerInput {
+    StartTimerInput {
+        client_id: client_id.to_string(),
+        project: None,
+        note: Some("calls".to_string()),
+    }
+}
+
+#[test]
+fn only_one_timer_runs_at_a_time() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+
+    assert!(time_entries::running(&conn).unwrap().is_none());
+    let first = time_entries::start(&mut conn, timer("h"), at("2026-03-02T09:00:00+00:00")).unwrap();
+    let running = time_entries::running_timer(&conn, at("2026-03-02T09:45:00+00:00")).unwrap().unwrap();
+    assert_eq!(running.entry.id, first.id);
+    assert_eq!(running.client_name, "Hourly");
+    assert_eq!(running.elapsed_minutes, 45);
+
+    // Starting another timer closes the first one.
+    let second = time_entries::start(&mut conn, timer("r"), at("2026-03-02T10:30:00+00:00")).unwrap();
+    let all = time_entries::list(&conn, None, None, None).unwrap();
+    assert_eq!(all.len(), 2);
+    let closed = all.iter().find(|e| e.id == first.id).unwrap();
+    assert_eq!(closed.minutes(Utc::now()), 90);
+    assert_eq!(time_entries::running(&conn).unwrap().unwrap().id, second.id);
+
+    let stopped = time_entries::stop(&conn, at("2026-03-02T11:00:00+00:00")).unwrap().unwrap();
+    assert_eq!(stopped.minutes(Utc::now()), 30);
+    assert!(time_entries::stop(&conn, at("2026-03-02T12:00:00+00:00")).unwrap().is_none());
+
+    let missing = time_entries::start(&mut conn, timer("nope"), at("2026-03-02T12:00:00+00:00"));
+    assert_eq!(missing.unwrap_err().code(), "not_found");
+}
+
+#[test]
+fn manual_entries_are_validated() {
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+
+    let added = time_entries::add(&conn, entry("h", "2026-03-03T10:00:00+00:00", 50)).unwrap();
+    assert_eq!(added.project.as_deref(), Some("site"));
+    assert_eq!(added.ended_at.as_deref(), Some("2026-03-03T10:50:00+00:00"));
+    assert!(added.billable);
+
+    let backwards = TimeEntryInput {
+        ended_at: Some("2026-03-03T09:00:00+00:00".to_string()),
+        ..entry("h", "2026-03-03T10:00:00+00:00", 0)
+    };
+    assert_eq!(time_entries::add(&conn, backwards).unwrap_err().code(), "validation");
+    assert_eq!(time_entries::add(&conn, entry("h", "2026-03-03T10:00:00+00:00", 0)).unwrap_err().code(), "validation");
+    assert_eq!(time_entries::add(&conn, entry("h", "yesterday", 30)).unwrap_err().code(), "validation");
+    for huge in [i64::MAX, i64::MAX / 60, 6_000_000_000_000] {
+        assert_eq!(time_entries::add(&conn, entry("h", "2026-03-03T10:00:00+00:00", huge)).unwrap_err().code(), "validation");
+    }
+
+    let in_march = time_entries::list(&conn, Some("h"), Some(day("2026-03-01")), Some(day("2026-03-03"))).unwrap();
+    assert_eq!(in_march.len(), 1);
+    assert!(time_entries::list(&conn, Some("r"), None, None).unwrap().is_empty());
+
+    time_entries::delete(&conn, &added.id).unwrap();
+    assert_eq!(time_entries::delete(&conn, &added.id).unwrap_err().code(), "not_found");
+}
+
+#[test]
+fn rounding_applies_per_entry() {
+    let up = Rounding {
+        increment: 15,
+        mode: RoundingMode::Up,
+    };
+    assert_eq!(up.apply(1), 15);
+    assert_eq!(up.apply(30), 30);
+    assert_eq!(Rounding { mode: RoundingMode::Nearest, ..up }.apply(37), 30);
+    assert_eq!(Rounding { mode: RoundingMode::Nearest, ..up }.apply(38), 45);
+    assert_eq!(Rounding { mode: RoundingMode::Down, ..up }.apply(44), 30);
+    assert_eq!(Rounding::NONE.apply(7), 7);
+    assert_eq!(Rounding::parse_increment("61"), None);
+    assert_eq!(Rounding::parse_mode("sideways"), None);
+
+    let dir = TempDir::new();
+    let conn = seeded_db(&dir, SEED);
+    assert_eq!(Rounding::load(&conn), Rounding::NONE);
+    db::set_setting(&conn, "time_rounding_minutes", "15").unwrap();
+    time_entries::add(&conn, entry("h", "2026-03-03T10:00:00+00:00", 50)).unwrap();
+    time_entries::add(&conn, entry("h", "2026-03-04T10:00:00+00:00", 5)).unwrap();
+
+    let hours = time_entries::hours_between(&conn, "h", day("2026-03-01"), day("2026-03-31")).unwrap();
+    assert_eq!(hours.len(), 1);
+    assert_eq!(hours[0].period, "2026-03");
+    assert_eq!(hours[0].entries, 2);
+    assert_eq!(hours[0].minutes, 75);
+    assert_eq!(hours[0].hours, 1.25);
+}
+
+#[test]
+fn tracked_hours_set_what_a_period_should_bring_in() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    time_entries::add(&conn, entry("h", "2026-02-10T12:00:00+00:00", 90)).unwrap();
+    time_entries::add(&conn, entry("r", "2026-02-10T12:00:00+00:00", 180)).unwrap();
+    let unbilled = TimeEntryInput {
+        billable: Some(false),
+        ..entry("r", "2026-02-11T12:00:00+00:00", 600)
+    };
+    time_entries::add(&conn, unbilled).unwrap();
+
+    let hourly = schedule::client_schedule(&conn, "h", day("2026-02-01"), day("2026-03-31"), day("2026-04-01"))
+        .unwrap();
+    assert_eq!(hourly[0].period, "2026-02");
+    assert_eq!(hourly[0].expected, Some(60.0));
+    assert_eq!(hourly[1].expected, None);
+
+    // One hour over the two included, at 60.
+    let retainer = schedule::client_schedule(&conn, "r", day("2026-02-01"), day("2026-03-31"), day("2026-04-01"))
+        .unwrap();
+    assert_eq!(retainer[0].expected, Some(560.0));
+    assert_eq!(retainer[1].expected, Some(500.0));
+
+    clients::record_payment(
+        &mut conn,
+        clients::RecordPaymentInput {
+            client_id: "h".to_string(),
+            period: "2026-02".to_string(),
+            paid_on: Some("2026-03-01".to_string()),
+            amount: 40.0,
+            currency: None,
+            method: None,
+            note: None,
+        },
+    )
+    .unwrap();
+    let period = &clients::payment_periods(&conn, "h").unwrap()[0];
+    assert!(!period.payment.paid);
+    assert_eq!(period.outstanding, Some(20.0));
+}
+
+fn invoice_input(client_id: &str, period: &str) -> CreateInvoiceInput {
+    CreateInvoiceInput {
+        client_id: client_id.to_string(),
+        period: Some(period.to_string()),
+        issued_on: Some("2026-03-01".to_string()),
+        due_on: None,
+        currency: None,
+        notes: None,
+        items: None,
+    }
+}
+
+#[test]
+fn invoices_bill_tracked_time_once() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    time_entries::add(&conn, entry("h", "2026-02-10T12:00:00+00:00", 90)).unwrap();
+    time_entries::add(&conn, entry("r", "2026-02-10T12:00:00+00:00", 150)).unwrap();
+
+    let hourly = invoices::create(&mut conn, invoice_input("h", "2026-02")).unwrap();
+    assert_eq!(hourly.items.len(), 1);
+    assert_eq!(hourly.items[0].quantity, 1.5);
+    assert_eq!(hourly.items[0].unit_price, 40.0);
+    assert_eq!(hourly.total, 60.0);
+    let billed = time_entries::list(&conn, Some("h"), None, None).unwrap();
+    assert_eq!(billed[0].invoice_id.as_deref(), Some(hourly.invoice.id.as_str()));
+
+    // Everything in February is billed now.
+    let again = invoices::create(&mut conn, invoice_input("h", "2026-02"));
+    assert_eq!(again.unwrap_err().code(), "validation");
+
+    let retainer = invoices::create(&mut conn, invoice_input("r", "2026-02")).unwrap();
+    assert_eq!(retainer.items.len(), 2);
+    assert_eq!(retainer.items[1].quantity, 0.5);
+    assert_eq!(retainer.total, 530.0);
+    // The fee is billed once per period.
+    let second = invoices::create(&mut conn, invoice_input("r", "2026-02"));
+    assert_eq!(second.unwrap_err().code(), "validation");
+
+    // Deleting the invoice frees its time for the next one.
+    conn.execute("DELETE FROM invoices WHERE id = ?1", [&hourly.invoice.id]).unwrap();
+    assert_eq!(invoices::create(&mut conn, invoice_input("h", "2026-02")).unwrap().total, 60.0);
+}
+
+#[test]
+fn retainer_overtime_is_billed_only_for_new_hours() {
+    let dir = TempDir::new();
+    let mut conn = seeded_db(&dir, SEED);
+    // 12h on a retainer with 2h included.
+    time_entries::add(&conn, entry("r", "2026-02-10T08:00:00+00:00", 720)).unwrap();
+
+    let first = invoices::create(&mut conn, invoice_input("r", "2026-02")).unwrap();
+    assert_eq!(first.items.len(), 2);
+    assert_eq!(first.items[1].quantity, 10.0);
+    assert_eq!(first.total, 1100.0);
+
+    // One more hour: all of it is overtime, none of the earlier hours are.
+    time_entries::add(&conn, entry("r", "2026-02-20T08:00:00+00:00", 60)).unwrap();
+    let second = invoices::create(&mut conn, invoice_input("r", "2026-02")).unwrap();
+    assert_eq!(second.items.len(), 1);
+    assert_eq!(second.items[0].quantity, 1.0);
+    assert_eq!(second.total, 60.0);
+
+    // Hours still within the allowance add nothing to bill.
+    time_entries::add(&conn, entry("r", "2026-03-02T08:00:00+00:00", 60)).unwrap();
+    let march = invoices::create(&mut conn, invoice_input("r", "2026-03")).unwrap();
+    assert_eq!(march.total, 500.0);
+    time_entries::add(&conn, entry("r", "2026-03-03T08:00:00+00:00", 30)).unwrap();
+    let again = invoices::create(&mut conn, invoice_input("r", "2026-03"));
+    assert_eq!(again.unwrap_err().code(), "validation");
+}
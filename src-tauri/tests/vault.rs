@@ -0,0 +1,100 @@
+mod common;
+
+use blueprint_lib::commands::profile::parse_profile;
+use blueprint_lib::db;
+use blueprint_lib::vault::{self, ServiceSecretKind, Vault};
+use common::{open_db, test_vault, TempDir};
+use serde_json::json;
+
+#[test]
+fn secrets_are_stored_encrypted() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+    let vault = test_vault();
+
+    vault.set(&conn, "ai_api_key", "sk-live-123456789").unwrap();
+
+    let raw: Vec<u8> = conn
+        .query_row("SELECT ciphertext FROM secrets WHERE name = 'ai_api_key'", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert!(!String::from_utf8_lossy(&raw).contains("sk-live"));
+    assert_eq!(
+        vault.get(&conn, "ai_api_key").unwrap().as_deref(),
+        Some("sk-live-123456789")
+    );
+    assert_eq!(
+        vault.status(&conn, "ai_api_key").unwrap().hint.as_deref(),
+        Some("••••6789")
+    );
+    assert!(Vault::new([8; 32]).get(&conn, "ai_api_key").is_err());
+
+    vault.set(&conn, "ai_api_key", "").unwrap();
+    assert_eq!(vault.get(&conn, "ai_api_key").unwrap(), None);
+    assert!(vault.set(&conn, "not_a_secret", "x").is_err());
+}
+
+#[test]
+fn service_secrets_are_removed_with_the_service() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+    let vault = test_vault();
+    conn.execute_batch(
+        "INSERT INTO services (id, project_name, service_name, expires_at, created_at)
+         VALUES ('s1', 'Site', 'Hosting', '2026-12-01', '2026-01-01T00:00:00+00:00');",
+    )
+    .unwrap();
+    let name = vault::service_secret_name("s1", ServiceSecretKind::Token);
+
+    vault.set(&conn, &name, "tok").unwrap();
+    assert_eq!(vault::service_id_of(&name), Some("s1"));
+    conn.execute("DELETE FROM services WHERE id = 's1'", []).unwrap();
+
+    assert!(vault::names(&conn).unwrap().is_empty());
+}
+
+#[test]
+fn legacy_setting_keys_move_into_the_vault() {
+    let dir = TempDir::new();
+    let conn = open_db(&dir);
+    let vault = test_vault();
+    conn.execute_batch(
+        "UPDATE settings SET value = 'sk-old' WHERE key = 'ai_api_key';
+         INSERT INTO settings (key, value) VALUES ('groq_api_key', '');",
+    )
+    .unwrap();
+
+    vault::migrate_legacy_settings(&conn, &vault).unwrap();
+
+    assert_eq!(vault.get(&conn, "ai_api_key").unwrap().as_deref(), Some("sk-old"));
+    assert_eq!(vault.get(&conn, "groq_api_key").unwrap(), None);
+    assert_eq!(db::get_setting(&conn, "ai_api_key"), None);
+    assert_eq!(db::get_setting(&conn, "groq_api_key"), None);
+}
+
+#[test]
+fn v3_export_keys_are_upgraded_to_secrets() {
+    let profile = parse_profile(json!({
+        "version": 3,
+        "exported_at": "2026-06-01T00:00:00+00:00",
+        "secrets_included": true,
+        "settings": [
+            { "key": "theme", "value": "dark" },
+            { "key": "ai_api_key", "value": "sk-old" },
+            { "key": "groq_api_key", "value": "" }
+        ],
+        "clients": [],
+        "client_payments": [],
+        "services": [],
+        "categories": [],
+        "notes": [],
+        "chat_history": []
+    }))
+    .unwrap();
+
+    assert_eq!(profile.settings.len(), 1);
+    assert_eq!(profile.secrets.len(), 1);
+    assert_eq!(profile.secrets[0].name, "ai_api_key");
+    assert_eq!(profile.secrets[0].updated_at, "2026-06-01T00:00:00+00:00");
+}
@@ -13,6 +13,7 @@ import { ToastProvider } from "./components/ToastProvider";
 import CommandPalette from "./components/CommandPalette";
 import { useSettingsStore } from "./stores/settingsStore";
 import { invoke } from "@tauri-apps/api/core";
+import { onAction, registerActionTypes } from "@tauri-apps/plugin-notification";
 
 function AppInner() {
   const [paletteOpen, setPaletteOpen] = useState(false);
@@ -28,6 +29,19 @@ function AppInner() {
     return () => window.removeEventListener("keydown", handler);
   }, []);
 
+  useEffect(() => {
+    let last = 0;
+    const report = () => {
+      const now = Date.now();
+      if (now - last < 30_000) return;
+      last = now;
+      invoke("record_activity").catch(() => {});
+    };
+    const events = ["mousemove", "mousedown", "keydown", "wheel", "touchstart"];
+    events.forEach((e) => window.addEventListener(e, report, { passive: true }));
+    return () => events.forEach((e) => window.removeEventListener(e, report));
+  }, []);
+
   return (
     <>
       <HashRouter>
@@ -56,6 +70,30 @@ export default function App() {
     invoke("check_and_notify").catch(() => {});
   }, [loadSettings]);
 
+  // Notification buttons exist only where the platform supports actions;
+  // elsewhere both calls reject and the Services page menu is the way.
+  useEffect(() => {
+    registerActionTypes([
+      {
+        id: "service-reminder",
+        actions: [
+          { id: "snooze", title: "Отложить на день" },
+          { id: "acknowledge", title: "Не напоминать до продления" },
+        ],
+      },
+    ]).catch(() => {});
+    const listener = onAction((payload) => {
+      // Typed as the notification, but delivered as { actionId, notification }.
+      const event = payload as unknown as { actionId?: string; notification?: typeof payload };
+      const serviceId = (event.notification ?? payload).extra?.service_id as string | undefined;
+      const actionId = event.actionId;
+      if (!serviceId) return;
+      if (actionId === "snooze") invoke("snooze_service", { id: serviceId, days: 1 }).catch(() => {});
+      else if (actionId === "acknowledge") invoke("acknowledge_service", { id: serviceId }).catch(() => {});
+    }).catch(() => null);
+    return () => { listener.then((l) => l?.unregister()); };
+  }, []);
+
   const muiTheme = useMemo(() => {
     if (theme === "dark") return darkTheme;
     if (theme === "light") return lightTheme;
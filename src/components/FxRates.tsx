@@ -0,0 +1,127 @@
+import { useCallback, useEffect, useRef, useState } from "react";
+import { Box, Button, IconButton, MenuItem, TextField, Typography } from "@mui/material";
+import DeleteOutlineRoundedIcon from "@mui/icons-material/DeleteOutlineRounded";
+import { invoke } from "@tauri-apps/api/core";
+import { format } from "date-fns";
+import { useSettingsStore } from "../stores/settingsStore";
+import { useToast } from "./ToastProvider";
+import { errorMessage } from "../errors";
+
+interface FxRate {
+  id: string;
+  base: string;
+  quote: string;
+  date: string;
+  rate: number;
+}
+
+const today = () => format(new Date(), "yyyy-MM-dd");
+
+export default function FxRates() {
+  const { toast } = useToast();
+  const baseCurrency = useSettingsStore((s) => s.baseCurrency);
+  const saveSetting = useSettingsStore((s) => s.saveSetting);
+  const [currencies, setCurrencies] = useState<string[]>([]);
+  const [rates, setRates] = useState<FxRate[]>([]);
+  const [form, setForm] = useState({ base: "USD", quote: baseCurrency, date: today(), rate: "" });
+  const fileRef = useRef<HTMLInputElement>(null);
+
+  const load = useCallback(async () => {
+    setRates(await invoke<FxRate[]>("get_fx_rates"));
+  }, []);
+
+  useEffect(() => {
+    invoke<string[]>("get_currencies").then(setCurrencies).catch(() => {});
+    load().catch((e) => toast(errorMessage(e), "error"));
+  }, [load, toast]);
+
+  const add = async () => {
+    try {
+      await invoke("set_fx_rate", { ...form, rate: parseFloat(form.rate.replace(",", ".")) });
+      setForm({ ...form, rate: "" });
+      await load();
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
+  };
+
+  const remove = async (id: string) => {
+    try {
+      await invoke("delete_fx_rate", { id });
+      await load();
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
+  };
+
+  const handleFile = async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.target.files?.[0];
+    e.target.value = "";
+    if (!file) return;
+    try {
+      const count = await invoke<number>("import_fx_rates", { content: await file.text() });
+      toast(`Импортировано курсов: ${count}`, "success");
+      await load();
+    } catch (err) {
+      toast(errorMessage(err), "error");
+    }
+  };
+
+  const currencySelect = (label: string, value: string, onChange: (v: string) => void) => (
+    <TextField select size="small" label={label} value={value} onChange={(e) => onChange(e.target.value)} sx={{ minWidth: 96 }}>
+      {currencies.map((c) => (
+        <MenuItem key={c} value={c}>{c}</MenuItem>
+      ))}
+    </TextField>
+  );
+
+  return (
+    <Box sx={{ display: "flex", flexDirection: "column", gap: 1.5 }}>
+      {currencySelect("Базовая валюта отчётов", baseCurrency, (v) =>
+        saveSetting("base_currency", v).catch((e) => toast(errorMessage(e), "error")),
+      )}
+      <Box sx={{ display: "flex", gap: 1, flexWrap: "wrap", alignItems: "center" }}>
+        {currencySelect("1 ед.", form.base, (v) => setForm({ ...form, base: v }))}
+        {currencySelect("=", form.quote, (v) => setForm({ ...form, quote: v }))}
+        <TextField
+          size="small"
+          label="Курс"
+          value={form.rate}
+          onChange={(e) => setForm({ ...form, rate: e.target.value })}
+          sx={{ maxWidth: 110 }}
+        />
+        <TextField
+          size="small"
+          type="date"
+          label="Дата"
+          value={form.date}
+          onChange={(e) => setForm({ ...form, date: e.target.value })}
+          InputLabelProps={{ shrink: true }}
+        />
+        <Button size="small" variant="text" onClick={add} disabled={!form.rate || !form.date}>
+          Добавить
+        </Button>
+      </Box>
+      <input ref={fileRef} type="file" accept=".csv,.json,text/csv,application/json" style={{ display: "none" }} onChange={handleFile} />
+      <Box>
+        <Button size="small" variant="outlined" onClick={() => fileRef.current?.click()}>
+          Импорт из CSV / JSON
+        </Button>
+        <Typography variant="caption" color="text.secondary" sx={{ display: "block", mt: 0.5 }}>
+          Строки вида base,quote,date,rate — например USD,RUB,2026-01-15,92.5
+        </Typography>
+      </Box>
+      {rates.slice(0, 50).map((r) => (
+        <Box key={r.id} sx={{ display: "flex", alignItems: "center", gap: 1 }}>
+          <Typography sx={{ fontSize: "0.875rem", minWidth: 96 }} color="text.secondary">{r.date}</Typography>
+          <Typography sx={{ fontSize: "0.875rem", flex: 1 }}>
+            1 {r.base} = {r.rate} {r.quote}
+          </Typography>
+          <IconButton size="small" onClick={() => remove(r.id)}>
+            <DeleteOutlineRoundedIcon fontSize="small" />
+          </IconButton>
+        </Box>
+      ))}
+    </Box>
+  );
+}
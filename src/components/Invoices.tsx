@@ -0,0 +1,276 @@
+import { useCallback, useEffect, useState } from "react";
+import {
+  Box,
+  Button,
+  Chip,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  DialogTitle,
+  IconButton,
+  TextField,
+  Tooltip,
+  Typography,
+} from "@mui/material";
+import DeleteOutlineRoundedIcon from "@mui/icons-material/DeleteOutlineRounded";
+import PictureAsPdfOutlinedIcon from "@mui/icons-material/PictureAsPdfOutlined";
+import OpenInNewRoundedIcon from "@mui/icons-material/OpenInNewRounded";
+import { invoke } from "@tauri-apps/api/core";
+import { addDays, format } from "date-fns";
+import { periodKey, type Client } from "../stores/clientsStore";
+import { useToast } from "./ToastProvider";
+import { errorMessage } from "../errors";
+
+export interface InvoiceItem {
+  id: string;
+  invoice_id: string;
+  position: number;
+  description: string;
+  quantity: number;
+  unit_price: number;
+  period?: string;
+}
+
+export interface Invoice {
+  id: string;
+  number: string;
+  client_id: string;
+  client_name: string;
+  period?: string;
+  issued_on: string;
+  due_on?: string;
+  currency: string;
+  status: "issued" | "paid";
+  notes?: string;
+  paid_at?: string;
+  created_at: string;
+  items: InvoiceItem[];
+  total: number;
+}
+
+function download(name: string, blob: Blob) {
+  const url = URL.createObjectURL(blob);
+  const a = document.createElement("a");
+  a.href = url;
+  a.download = name;
+  document.body.appendChild(a);
+  a.click();
+  a.remove();
+  URL.revokeObjectURL(url);
+}
+
+export function InvoicesDialog({
+  client,
+  onClose,
+  onPaidChange,
+}: {
+  client: Client | null;
+  onClose: () => void;
+  onPaidChange: () => void;
+}) {
+  const { toast } = useToast();
+  const [invoices, setInvoices] = useState<Invoice[]>([]);
+  const [preview, setPreview] = useState<string | null>(null);
+  const [form, setForm] = useState({ period: "", due_on: "", notes: "" });
+
+  const load = useCallback(async () => {
+    if (!client) return;
+    setInvoices(await invoke<Invoice[]>("get_invoices", { clientId: client.id }));
+  }, [client]);
+
+  useEffect(() => {
+    const now = new Date();
+    setForm({
+      period: client && client.payment_type !== "onetime" ? periodKey(client.payment_type, now) : "",
+      due_on: format(addDays(now, 14), "yyyy-MM-dd"),
+      notes: "",
+    });
+    load().catch((e) => toast(errorMessage(e), "error"));
+  }, [client, load, toast]);
+
+  const run = async (action: () => Promise<unknown>) => {
+    try {
+      await action();
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
+  };
+
+  const create = () =>
+    run(async () => {
+      await invoke("create_invoice", {
+        input: {
+          client_id: client!.id,
+          period: form.period || null,
+          due_on: form.due_on || null,
+          notes: form.notes || null,
+        },
+      });
+      await load();
+    });
+
+  const setPaid = (invoice: Invoice, paid: boolean) =>
+    run(async () => {
+      await invoke("mark_invoice_paid", { id: invoice.id, paid });
+      await load();
+      onPaidChange();
+    });
+
+  const remove = (invoice: Invoice) =>
+    run(async () => {
+      await invoke("delete_invoice", { id: invoice.id });
+      await load();
+    });
+
+  const savePdf = (invoice: Invoice) =>
+    run(async () => {
+      const data = await invoke<string>("render_invoice_pdf", { id: invoice.id });
+      const bytes = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+      download(`${invoice.number}.pdf`, new Blob([bytes], { type: "application/pdf" }));
+    });
+
+  const showHtml = (invoice: Invoice) =>
+    run(async () => setPreview(await invoke<string>("render_invoice_html", { id: invoice.id })));
+
+  return (
+    <>
+      <Dialog open={!!client} onClose={onClose} maxWidth="sm" fullWidth>
+        <DialogTitle>Счета · {client?.name}</DialogTitle>
+        <DialogContent sx={{ display: "flex", flexDirection: "column", gap: 1 }}>
+          {invoices.length === 0 && (
+            <Typography variant="body2" color="text.secondary" sx={{ mt: 1 }}>
+              Счетов пока нет
+            </Typography>
+          )}
+          {invoices.map((inv) => (
+            <Box key={inv.id} sx={{ display: "flex", alignItems: "center", gap: 1 }}>
+              <Typography sx={{ fontSize: "0.875rem", fontWeight: 500, minWidth: 120 }}>{inv.number}</Typography>
+              <Typography sx={{ fontSize: "0.8125rem", flex: 1 }} color="text.secondary">
+                {inv.issued_on}
+                {inv.period ? ` · ${inv.period}` : ""}
+              </Typography>
+              <Typography sx={{ fontSize: "0.875rem", minWidth: 100, textAlign: "right" }}>
+                {inv.total.toFixed(2)} {inv.currency}
+              </Typography>
+              <Chip
+                size="small"
+                label={inv.status === "paid" ? "Оплачен" : "Не оплачен"}
+                color={inv.status === "paid" ? "success" : "default"}
+                onClick={() => setPaid(inv, inv.status !== "paid")}
+              />
+              <Tooltip title="Открыть">
+                <IconButton size="small" onClick={() => showHtml(inv)}>
+                  <OpenInNewRoundedIcon fontSize="small" />
+                </IconButton>
+              </Tooltip>
+              <Tooltip title="Скачать PDF">
+                <IconButton size="small" onClick={() => savePdf(inv)}>
+                  <PictureAsPdfOutlinedIcon fontSize="small" />
+                </IconButton>
+              </Tooltip>
+              <IconButton size="small" onClick={() => remove(inv)}>
+                <DeleteOutlineRoundedIcon fontSize="small" />
+              </IconButton>
+            </Box>
+          ))}
+          <Box sx={{ display: "flex", gap: 1, mt: 2, flexWrap: "wrap" }}>
+            <TextField
+              size="small"
+              label="Период"
+              placeholder="2026-03"
+              value={form.period}
+              onChange={(e) => setForm({ ...form, period: e.target.value })}
+              sx={{ width: 130 }}
+            />
+            <TextField
+              size="small"
+              type="date"
+              label="Оплатить до"
+              value={form.due_on}
+              onChange={(e) => setForm({ ...form, due_on: e.target.value })}
+              InputLabelProps={{ shrink: true }}
+            />
+            <TextField
+              size="small"
+              label="Комментарий"
+              value={form.notes}
+              onChange={(e) => setForm({ ...form, notes: e.target.value })}
+              sx={{ flex: 1, minWidth: 160 }}
+            />
+          </Box>
+        </DialogContent>
+        <DialogActions>
+          <Button variant="text" onClick={onClose}>Закрыть</Button>
+          <Button variant="contained" onClick={create} disabled={!client}>
+            Выставить счёт
+          </Button>
+        </DialogActions>
+      </Dialog>
+      <Dialog open={preview !== null} onClose={() => setPreview(null)} maxWidth="md" fullWidth>
+        <DialogContent sx={{ p: 0, height: "80vh" }}>
+          <iframe title="invoice" srcDoc={preview ?? ""} style={{ border: 0, width: "100%", height: "100%", background: "#fff" }} />
+        </DialogContent>
+        <DialogActions>
+          <Button
+            variant="text"
+            onClick={() => preview && download("invoice.html", new Blob([preview], { type: "text/html;charset=utf-8" }))}
+          >
+            Сохранить HTML
+          </Button>
+          <Button variant="text" onClick={() => setPreview(null)}>Закрыть</Button>
+        </DialogActions>
+      </Dialog>
+    </>
+  );
+}
+
+const COMPANY_FIELDS = [
+  { key: "company_name", label: "Название или ФИО", multiline: false },
+  { key: "company_details", label: "Адрес, ИНН, контакты", multiline: true },
+  { key: "company_bank", label: "Банковские реквизиты", multiline: true },
+  { key: "invoice_prefix", label: "Префикс номера ({year} — год)", multiline: false },
+  { key: "invoice_next_number", label: "Следующий номер", multiline: false },
+];
+
+export function CompanySettings() {
+  const { toast } = useToast();
+  const [values, setValues] = useState<Record<string, string>>({});
+  const [saved, setSaved] = useState<Record<string, string>>({});
+
+  useEffect(() => {
+    invoke<Record<string, string>>("get_settings")
+      .then((settings) => {
+        setValues(settings);
+        setSaved(settings);
+      })
+      .catch(() => {});
+  }, []);
+
+  const save = async (key: string) => {
+    const value = values[key] ?? "";
+    if (value === (saved[key] ?? "")) return;
+    try {
+      await invoke("save_setting", { key, value });
+      setSaved({ ...saved, [key]: value });
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
+  };
+
+  return (
+    <Box sx={{ display: "flex", flexDirection: "column", gap: 1.5, maxWidth: 480 }}>
+      {COMPANY_FIELDS.map((f) => (
+        <TextField
+          key={f.key}
+          size="small"
+          label={f.label}
+          multiline={f.multiline}
+          minRows={f.multiline ? 2 : undefined}
+          value={values[f.key] ?? ""}
+          onChange={(e) => setValues({ ...values, [f.key]: e.target.value })}
+          onBlur={() => save(f.key)}
+        />
+      ))}
+    </Box>
+  );
+}
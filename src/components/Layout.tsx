@@ -1,6 +1,7 @@
-import React from "react";
+import React, { useState } from "react";
 import { Outlet, useNavigate, useLocation } from "react-router-dom";
 import {
+  Badge,
   Box,
   Drawer,
   List,
@@ -14,8 +15,10 @@ import DnsOutlinedIcon from "@mui/icons-material/DnsOutlined";
 import AssignmentOutlinedIcon from "@mui/icons-material/AssignmentOutlined";
 import SettingsOutlinedIcon from "@mui/icons-material/SettingsOutlined";
 import LayersOutlinedIcon from "@mui/icons-material/LayersOutlined";
+import NotificationsNoneOutlinedIcon from "@mui/icons-material/NotificationsNoneOutlined";
 import { invoke } from "@tauri-apps/api/core";
 import TitleBar from "./TitleBar";
+import NotificationInbox, { useNotificationInbox } from "./NotificationInbox";
 
 const SIDEBAR_WIDTH = 56;
 
@@ -45,6 +48,8 @@ export default function Layout({ onOpenPalette }: { onOpenPalette?: () => void }
 
   const sidebarBg = isDark ? "#171717" : "#f7f7f8";
   const borderColor = isDark ? "#272727" : "#ebebeb";
+  const unread = useNotificationInbox();
+  const [inboxAnchor, setInboxAnchor] = useState<HTMLElement | null>(null);
 
   const NavBtn = ({
     path,
@@ -59,8 +64,10 @@ export default function Layout({ onOpenPalette }: { onOpenPalette?: () => void }
   }) => {
     const selected = path ? location.pathname === path : false;
 
-    const handleClick = () => {
-      if (action === "overlay") {
+    const handleClick = (e: React.MouseEvent<HTMLElement>) => {
+      if (action === "inbox") {
+        setInboxAnchor(e.currentTarget);
+      } else if (action === "overlay") {
         invoke("toggle_overlay_window");
       } else if (action === "palette") {
         onOpenPalette?.();
@@ -190,11 +197,22 @@ export default function Layout({ onOpenPalette }: { onOpenPalette?: () => void }
             }}
           >
             <List disablePadding sx={{ px: 1 }}>
+              <NavBtn
+                path={null}
+                label="Уведомления"
+                action="inbox"
+                icon={
+                  <Badge badgeContent={unread} color="error" max={9}>
+                    <NotificationsNoneOutlinedIcon sx={{ fontSize: 20 }} />
+                  </Badge>
+                }
+              />
               {bottomItems.map((item, idx) => (
                 <NavBtn key={idx} {...item} />
               ))}
             </List>
           </Box>
+          <NotificationInbox anchorEl={inboxAnchor} onClose={() => setInboxAnchor(null)} />
         </Drawer>
 
         <Box
@@ -0,0 +1,92 @@
+import { useEffect } from "react";
+import { useNavigate } from "react-router-dom";
+import { Box, Button, List, ListItemButton, Popover, Typography } from "@mui/material";
+import { listen } from "@tauri-apps/api/event";
+import { format, parseISO } from "date-fns";
+import { useNotificationsStore, NotificationEntry } from "../stores/notificationsStore";
+
+const ROUTES: Record<NotificationEntry["kind"], string> = {
+  service: "/services",
+  payment: "/clients",
+  note: "/notes",
+};
+
+export function useNotificationInbox() {
+  const { entries, fetchHistory } = useNotificationsStore();
+
+  useEffect(() => {
+    fetchHistory().catch(() => {});
+    const unlistenPromise = listen("notifications-logged", () => {
+      fetchHistory().catch(() => {});
+    });
+    return () => { unlistenPromise.then((fn) => fn()); };
+  }, [fetchHistory]);
+
+  return entries.filter((e) => !e.read_at).length;
+}
+
+export default function NotificationInbox({
+  anchorEl,
+  onClose,
+}: {
+  anchorEl: HTMLElement | null;
+  onClose: () => void;
+}) {
+  const navigate = useNavigate();
+  const { entries, markRead, markAllRead } = useNotificationsStore();
+
+  const open = (entry: NotificationEntry) => {
+    markRead(entry.id).catch(() => {});
+    navigate(ROUTES[entry.kind]);
+    onClose();
+  };
+
+  return (
+    <Popover
+      open={Boolean(anchorEl)}
+      anchorEl={anchorEl}
+      onClose={onClose}
+      anchorOrigin={{ vertical: "bottom", horizontal: "right" }}
+      transformOrigin={{ vertical: "bottom", horizontal: "left" }}
+      slotProps={{ paper: { sx: { width: 320, maxHeight: 420, ml: 1 } } }}
+    >
+      <Box sx={{ display: "flex", alignItems: "center", px: 2, py: 1 }}>
+        <Typography variant="subtitle2" sx={{ flex: 1 }}>
+          Уведомления
+        </Typography>
+        <Button
+          size="small"
+          disabled={!entries.some((e) => !e.read_at)}
+          onClick={() => markAllRead().catch(() => {})}
+        >
+          Прочитать все
+        </Button>
+      </Box>
+      {entries.length === 0 ? (
+        <Typography variant="body2" color="text.secondary" sx={{ px: 2, pb: 2 }}>
+          Пока ничего не было
+        </Typography>
+      ) : (
+        <List disablePadding>
+          {entries.map((entry) => (
+            <ListItemButton
+              key={entry.id}
+              onClick={() => open(entry)}
+              sx={{ display: "block", py: 1, opacity: entry.read_at ? 0.6 : 1 }}
+            >
+              <Typography variant="body2" sx={{ fontWeight: entry.read_at ? 400 : 600 }}>
+                {entry.title}
+              </Typography>
+              <Typography variant="caption" color="text.secondary" component="div">
+                {entry.body}
+              </Typography>
+              <Typography variant="caption" color="text.secondary">
+                {format(parseISO(entry.sent_at), "dd.MM.yyyy HH:mm")}
+              </Typography>
+            </ListItemButton>
+          ))}
+        </List>
+      )}
+    </Popover>
+  );
+}
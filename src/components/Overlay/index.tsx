@@ -16,8 +16,11 @@ import CircleOutlinedIcon from "@mui/icons-material/CircleOutlined";
 import CheckCircleOutlineRoundedIcon from "@mui/icons-material/CheckCircleOutlineRounded";
 import ErrorOutlineRoundedIcon from "@mui/icons-material/ErrorOutlineRounded";
 import WarningAmberRoundedIcon from "@mui/icons-material/WarningAmberRounded";
+import StopRoundedIcon from "@mui/icons-material/StopRounded";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { formatMinutes, RunningTimer } from "../../stores/timeStore";
 import { lightTheme, darkTheme } from "../../theme";
 
 interface Note {
@@ -54,6 +57,7 @@ function getExpiryStatus(days: number): "ok" | "warning" | "critical" | "expired
 export default function Overlay() {
   const [notes, setNotes] = useState<Note[]>([]);
   const [urgentServices, setUrgentServices] = useState<Service[]>([]);
+  const [timer, setTimer] = useState<RunningTimer | null>(null);
   const [isDark, setIsDark] = useState(false);
 
   useEffect(() => {
@@ -86,16 +90,35 @@ export default function Overlay() {
     }
   };
 
+  const loadTimer = async () => {
+    try {
+      setTimer(await invoke<RunningTimer | null>("get_running_timer"));
+    } catch (e) {
+      console.error(e);
+    }
+  };
+
   useEffect(() => {
     loadNotes();
     loadServices();
+    loadTimer();
     const interval = setInterval(() => {
       loadNotes();
       loadServices();
+      loadTimer();
     }, 5000);
-    return () => clearInterval(interval);
+    const unlistenPromise = listen("timer-changed", () => loadTimer());
+    return () => {
+      clearInterval(interval);
+      unlistenPromise.then((fn) => fn());
+    };
   }, []);
 
+  const handleStopTimer = async () => {
+    await invoke("stop_timer");
+    setTimer(null);
+  };
+
   const handleToggle = async (id: string) => {
     await invoke("toggle_note", { id, completed: true });
     setNotes((prev) => prev.filter((n) => n.id !== id));
@@ -147,6 +170,35 @@ export default function Overlay() {
             </IconButton>
           </Box>
 
+          {timer && (
+            <Box
+              sx={{
+                display: "flex",
+                alignItems: "center",
+                gap: 1,
+                px: 2,
+                py: 0.75,
+                backgroundColor: isDark ? "rgba(34,197,94,0.06)" : "rgba(34,197,94,0.05)",
+                borderBottom: `1px solid ${isDark ? "#1e1e21" : "#f4f4f5"}`,
+              }}
+            >
+              <Box sx={{ flex: 1, minWidth: 0 }}>
+                <Typography sx={{ fontSize: "0.9375rem", color: isDark ? "#d4d4d8" : "#27272a", overflow: "hidden", textOverflow: "ellipsis", whiteSpace: "nowrap" }}>
+                  {timer.client_name}
+                </Typography>
+                <Typography sx={{ fontSize: "0.8125rem", color: isDark ? "#52525b" : "#a1a1aa" }}>
+                  {[timer.project, timer.note].filter(Boolean).join(" · ") || "Таймер"}
+                </Typography>
+              </Box>
+              <Typography sx={{ fontSize: "0.8125rem", fontWeight: 600, color: isDark ? "#22c55e" : "#15803d", flexShrink: 0 }}>
+                {formatMinutes(timer.elapsed_minutes)}
+              </Typography>
+              <IconButton size="small" onClick={handleStopTimer} sx={{ width: 28, height: 28, color: isDark ? "#ef4444" : "#dc2626" }}>
+                <StopRoundedIcon sx={{ fontSize: 18 }} />
+              </IconButton>
+            </Box>
+          )}
+
           {urgentServices.length > 0 && (
             <>
               <Box sx={{ px: 2, pt: 1.25, pb: 0.5 }}>
@@ -196,7 +248,7 @@ export default function Overlay() {
           )}
 
           <Box sx={{ flex: 1, overflow: "auto" }}>
-            {notes.length === 0 && urgentServices.length === 0 && (
+            {notes.length === 0 && urgentServices.length === 0 && !timer && (
               <Box
                 sx={{
                   display: "flex",
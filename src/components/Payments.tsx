@@ -0,0 +1,166 @@
+import { useEffect, useState } from "react";
+import {
+  Box,
+  Button,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  DialogTitle,
+  IconButton,
+  TextField,
+  Typography,
+} from "@mui/material";
+import DeleteOutlineRoundedIcon from "@mui/icons-material/DeleteOutlineRounded";
+import { format } from "date-fns";
+import { useClientsStore, periodKey, Client } from "../stores/clientsStore";
+import { useToast } from "./ToastProvider";
+import { errorMessage } from "../errors";
+
+const money = (value: number, currency: string) => `${value.toFixed(2)} ${currency}`;
+
+// Partial payments for one client: every period with what came in, what is
+// still owed, and the individual transfers.
+export function PaymentsDialog({ client, onClose }: { client: Client | null; onClose: () => void }) {
+  const { payments, recordPayment, deletePaymentTransaction } = useClientsStore();
+  const { toast } = useToast();
+  const [form, setForm] = useState({ period: "", paid_on: "", amount: "", currency: "", method: "", note: "" });
+
+  useEffect(() => {
+    if (!client) return;
+    const now = new Date();
+    setForm({
+      period: periodKey(client.payment_type, now),
+      paid_on: format(now, "yyyy-MM-dd"),
+      amount: "",
+      currency: client.currency,
+      method: "",
+      note: "",
+    });
+  }, [client]);
+
+  const periods = client ? payments[client.id] || [] : [];
+
+  const run = async (action: () => Promise<unknown>) => {
+    try {
+      await action();
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
+  };
+
+  const record = () =>
+    run(async () => {
+      await recordPayment({
+        client_id: client!.id,
+        period: form.period,
+        paid_on: form.paid_on || undefined,
+        amount: parseFloat(form.amount),
+        currency: form.currency || undefined,
+        method: form.method || undefined,
+        note: form.note || undefined,
+      });
+      setForm({ ...form, amount: "", note: "" });
+    });
+
+  return (
+    <Dialog open={!!client} onClose={onClose} maxWidth="sm" fullWidth>
+      <DialogTitle>Поступления · {client?.name}</DialogTitle>
+      <DialogContent sx={{ display: "flex", flexDirection: "column", gap: 1.5 }}>
+        {periods.length === 0 && (
+          <Typography variant="body2" color="text.secondary" sx={{ mt: 1 }}>
+            Поступлений пока нет
+          </Typography>
+        )}
+        {periods.map((p) => (
+          <Box key={p.id}>
+            <Box sx={{ display: "flex", alignItems: "baseline", gap: 1 }}>
+              <Typography sx={{ fontSize: "0.875rem", fontWeight: 500, flex: 1 }}>{p.period}</Typography>
+              <Typography sx={{ fontSize: "0.8125rem" }} color="text.secondary">
+                {money(p.received, p.currency)}
+                {p.expected != null ? ` из ${money(p.expected, p.currency)}` : ""}
+              </Typography>
+              <Typography
+                sx={{ fontSize: "0.75rem", minWidth: 110, textAlign: "right" }}
+                color={p.outstanding === 0 ? "success.main" : "warning.main"}
+              >
+                {p.outstanding === 0
+                  ? "Оплачено"
+                  : p.outstanding != null
+                    ? `Долг ${money(p.outstanding, p.currency)}`
+                    : "Нет курса"}
+              </Typography>
+            </Box>
+            {p.transactions.map((t) => (
+              <Box key={t.id} sx={{ display: "flex", alignItems: "center", gap: 1, pl: 2 }}>
+                <Typography sx={{ fontSize: "0.8125rem", minWidth: 90 }} color="text.secondary">
+                  {t.paid_on}
+                </Typography>
+                <Typography sx={{ fontSize: "0.8125rem", minWidth: 110 }}>{money(t.amount, t.currency)}</Typography>
+                <Typography sx={{ fontSize: "0.8125rem", flex: 1 }} color="text.secondary" noWrap>
+                  {[t.method, t.note].filter(Boolean).join(" · ")}
+                </Typography>
+                <IconButton size="small" onClick={() => run(() => deletePaymentTransaction(client!.id, t.id))}>
+                  <DeleteOutlineRoundedIcon fontSize="small" />
+                </IconButton>
+              </Box>
+            ))}
+          </Box>
+        ))}
+        <Box sx={{ display: "flex", gap: 1, mt: 2, flexWrap: "wrap" }}>
+          <TextField
+            size="small"
+            label="Период"
+            placeholder={client ? periodKey(client.payment_type, new Date()) : ""}
+            value={form.period}
+            onChange={(e) => setForm({ ...form, period: e.target.value })}
+            sx={{ width: 130 }}
+          />
+          <TextField
+            size="small"
+            type="date"
+            label="Дата"
+            value={form.paid_on}
+            onChange={(e) => setForm({ ...form, paid_on: e.target.value })}
+            InputLabelProps={{ shrink: true }}
+          />
+          <TextField
+            size="small"
+            type="number"
+            label="Сумма"
+            value={form.amount}
+            onChange={(e) => setForm({ ...form, amount: e.target.value })}
+            sx={{ width: 120 }}
+          />
+          <TextField
+            size="small"
+            label="Валюта"
+            value={form.currency}
+            onChange={(e) => setForm({ ...form, currency: e.target.value.toUpperCase() })}
+            sx={{ width: 90 }}
+          />
+          <TextField
+            size="small"
+            label="Способ"
+            placeholder="карта, счёт, наличные"
+            value={form.method}
+            onChange={(e) => setForm({ ...form, method: e.target.value })}
+            sx={{ width: 170 }}
+          />
+          <TextField
+            size="small"
+            label="Комментарий"
+            value={form.note}
+            onChange={(e) => setForm({ ...form, note: e.target.value })}
+            sx={{ flex: 1, minWidth: 160 }}
+          />
+        </Box>
+      </DialogContent>
+      <DialogActions>
+        <Button variant="text" onClick={onClose}>Закрыть</Button>
+        <Button variant="contained" onClick={record} disabled={!client || !form.period || !form.amount}>
+          Записать платёж
+        </Button>
+      </DialogActions>
+    </Dialog>
+  );
+}
@@ -0,0 +1,214 @@
+import { useCallback, useEffect, useState } from "react";
+import {
+  Box,
+  Button,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  DialogTitle,
+  FormControlLabel,
+  IconButton,
+  Switch,
+  TextField,
+  Typography,
+} from "@mui/material";
+import DeleteOutlineRoundedIcon from "@mui/icons-material/DeleteOutlineRounded";
+import { invoke } from "@tauri-apps/api/core";
+import { format, parseISO, startOfYear } from "date-fns";
+import { ru } from "date-fns/locale";
+import type { Service } from "../stores/servicesStore";
+import { useSettingsStore } from "../stores/settingsStore";
+import { useToast } from "./ToastProvider";
+import { errorMessage } from "../errors";
+
+export interface ServiceCharge {
+  id: string;
+  service_id: string;
+  charged_on: string;
+  amount: number;
+  currency: string;
+  note?: string;
+  renewal_id?: string;
+  created_at: string;
+}
+
+interface ProjectSpending {
+  project_name: string;
+  currency: string;
+  total: number;
+  charges: number;
+}
+
+const today = () => format(new Date(), "yyyy-MM-dd");
+
+export function ServiceChargesDialog({ service, onClose }: { service: Service | null; onClose: () => void }) {
+  const { toast } = useToast();
+  const [charges, setCharges] = useState<ServiceCharge[]>([]);
+  const [form, setForm] = useState({ charged_on: today(), amount: "", note: "" });
+
+  const load = useCallback(async () => {
+    if (!service) return;
+    setCharges(await invoke<ServiceCharge[]>("get_service_charges", { serviceId: service.id }));
+  }, [service]);
+
+  useEffect(() => {
+    setForm({ charged_on: today(), amount: service?.cost?.toString() ?? "", note: "" });
+    load().catch((e) => toast(errorMessage(e), "error"));
+  }, [service, load, toast]);
+
+  const add = async () => {
+    if (!service) return;
+    try {
+      await invoke("log_service_charge", {
+        input: {
+          service_id: service.id,
+          charged_on: form.charged_on,
+          amount: parseFloat(form.amount),
+          currency: service.currency,
+          note: form.note || undefined,
+        },
+      });
+      setForm({ ...form, note: "" });
+      await load();
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
+  };
+
+  const remove = async (id: string) => {
+    try {
+      await invoke("delete_service_charge", { id });
+      setCharges((c) => c.filter((x) => x.id !== id));
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
+  };
+
+  return (
+    <Dialog open={!!service} onClose={onClose} maxWidth="sm" fullWidth>
+      <DialogTitle>Платежи: {service?.service_name}</DialogTitle>
+      <DialogContent sx={{ display: "flex", flexDirection: "column", gap: 1 }}>
+        {charges.length === 0 && (
+          <Typography variant="body2" color="text.secondary">
+            Платежей пока нет
+          </Typography>
+        )}
+        {charges.map((c) => (
+          <Box key={c.id} sx={{ display: "flex", alignItems: "center", gap: 1.5 }}>
+            <Typography sx={{ fontSize: "0.875rem", width: 110 }}>
+              {format(parseISO(c.charged_on), "d MMM yyyy", { locale: ru })}
+            </Typography>
+            <Typography sx={{ fontSize: "0.875rem", width: 100, textAlign: "right" }}>
+              {c.amount} {c.currency}
+            </Typography>
+            <Typography sx={{ fontSize: "0.8125rem", flex: 1 }} color="text.secondary">
+              {c.renewal_id ? "Продление" : c.note}
+            </Typography>
+            <IconButton size="small" onClick={() => remove(c.id)} sx={{ width: 28, height: 28 }}>
+              <DeleteOutlineRoundedIcon sx={{ fontSize: 15 }} />
+            </IconButton>
+          </Box>
+        ))}
+        <Box sx={{ display: "flex", gap: 1, mt: 1 }}>
+          <TextField
+            label="Дата"
+            type="date"
+            size="small"
+            value={form.charged_on}
+            onChange={(e) => setForm({ ...form, charged_on: e.target.value })}
+            InputLabelProps={{ shrink: true }}
+          />
+          <TextField
+            label={`Сумма, ${service?.currency ?? ""}`}
+            type="number"
+            size="small"
+            value={form.amount}
+            onChange={(e) => setForm({ ...form, amount: e.target.value })}
+            sx={{ width: 130 }}
+          />
+          <TextField
+            label="Комментарий"
+            size="small"
+            value={form.note}
+            onChange={(e) => setForm({ ...form, note: e.target.value })}
+            sx={{ flex: 1 }}
+          />
+        </Box>
+      </DialogContent>
+      <DialogActions>
+        <Button variant="text" onClick={onClose}>Закрыть</Button>
+        <Button variant="contained" onClick={add} disabled={!form.amount || !form.charged_on}>
+          Добавить платёж
+        </Button>
+      </DialogActions>
+    </Dialog>
+  );
+}
+
+export function SpendingSummaryDialog({ open, onClose }: { open: boolean; onClose: () => void }) {
+  const { toast } = useToast();
+  const [range, setRange] = useState({ from: format(startOfYear(new Date()), "yyyy-MM-dd"), to: today() });
+  const [rows, setRows] = useState<ProjectSpending[]>([]);
+  const baseCurrency = useSettingsStore((s) => s.baseCurrency);
+  const [converted, setConverted] = useState(false);
+
+  useEffect(() => {
+    if (!open || !range.from || !range.to) return;
+    invoke<ProjectSpending[]>("get_spending_summary", {
+      ...range,
+      baseCurrency: converted ? baseCurrency : null,
+    })
+      .then(setRows)
+      .catch((e) => toast(errorMessage(e), "error"));
+  }, [open, range, converted, baseCurrency, toast]);
+
+  return (
+    <Dialog open={open} onClose={onClose} maxWidth="xs" fullWidth>
+      <DialogTitle>Расходы по проектам</DialogTitle>
+      <DialogContent sx={{ display: "flex", flexDirection: "column", gap: 1.5 }}>
+        <Box sx={{ display: "flex", gap: 1, mt: 1 }}>
+          <TextField
+            label="С"
+            type="date"
+            size="small"
+            value={range.from}
+            onChange={(e) => setRange({ ...range, from: e.target.value })}
+            InputLabelProps={{ shrink: true }}
+          />
+          <TextField
+            label="По"
+            type="date"
+            size="small"
+            value={range.to}
+            onChange={(e) => setRange({ ...range, to: e.target.value })}
+            InputLabelProps={{ shrink: true }}
+          />
+        </Box>
+        <FormControlLabel
+          control={<Switch checked={converted} onChange={(e) => setConverted(e.target.checked)} />}
+          label={`Пересчитать в ${baseCurrency}`}
+        />
+        {rows.length === 0 ? (
+          <Typography variant="body2" color="text.secondary">
+            За этот период платежей нет
+          </Typography>
+        ) : (
+          rows.map((r) => (
+            <Box key={`${r.project_name}-${r.currency}`} sx={{ display: "flex", gap: 1 }}>
+              <Typography sx={{ fontSize: "0.875rem", flex: 1 }}>{r.project_name}</Typography>
+              <Typography sx={{ fontSize: "0.8125rem" }} color="text.secondary">
+                {r.charges} шт.
+              </Typography>
+              <Typography sx={{ fontSize: "0.875rem", fontWeight: 500, minWidth: 100, textAlign: "right" }}>
+                {r.total.toFixed(2)} {r.currency}
+              </Typography>
+            </Box>
+          ))
+        )}
+      </DialogContent>
+      <DialogActions>
+        <Button variant="text" onClick={onClose}>Закрыть</Button>
+      </DialogActions>
+    </Dialog>
+  );
+}
@@ -0,0 +1,220 @@
+import { useEffect, useState } from "react";
+import {
+  Box,
+  Button,
+  Checkbox,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  DialogTitle,
+  FormControlLabel,
+  IconButton,
+  TextField,
+  Typography,
+} from "@mui/material";
+import DeleteOutlineRoundedIcon from "@mui/icons-material/DeleteOutlineRounded";
+import { listen } from "@tauri-apps/api/event";
+import { format, subMonths } from "date-fns";
+import { Client } from "../stores/clientsStore";
+import { useTimeStore, formatMinutes, TimeEntry } from "../stores/timeStore";
+import { useToast } from "./ToastProvider";
+import { errorMessage } from "../errors";
+
+const emptyForm = () => ({
+  started_at: format(new Date(), "yyyy-MM-dd'T'HH:mm"),
+  minutes: "",
+  project: "",
+  note: "",
+  billable: true,
+});
+
+function entryMinutes(entry: TimeEntry): number {
+  const end = entry.ended_at ? new Date(entry.ended_at) : new Date();
+  return Math.max(0, Math.floor((end.getTime() - new Date(entry.started_at).getTime()) / 60000));
+}
+
+// Tracked time for one client: the timer, billable hours per period after
+// rounding, and the individual entries.
+export function TimeDialog({ client, onClose }: { client: Client | null; onClose: () => void }) {
+  const { entries, hours, running, fetchEntries, fetchHours, fetchRunning, startTimer, stopTimer, addEntry, deleteEntry } =
+    useTimeStore();
+  const { toast } = useToast();
+  const [timer, setTimer] = useState({ project: "", note: "" });
+  const [form, setForm] = useState(emptyForm);
+
+  const list = client ? entries[client.id] || [] : [];
+  const periods = client ? hours[client.id] || [] : [];
+  const runningHere = !!client && running?.client_id === client.id;
+
+  useEffect(() => {
+    if (!client) return;
+    setForm(emptyForm());
+    const refresh = () => {
+      fetchEntries(client.id).catch(() => {});
+      fetchRunning().catch(() => {});
+    };
+    refresh();
+    // The tray can stop the timer while the dialog is open.
+    const unlistenPromise = listen("timer-changed", refresh);
+    return () => { unlistenPromise.then((fn) => fn()); };
+  }, [client, fetchEntries, fetchRunning]);
+
+  useEffect(() => {
+    if (!client) return;
+    const now = new Date();
+    fetchHours(client.id, format(subMonths(now, 12), "yyyy-MM-dd"), format(now, "yyyy-MM-dd")).catch(() => {});
+  }, [client, list, fetchHours]);
+
+  const run = async (action: () => Promise<unknown>) => {
+    try {
+      await action();
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
+  };
+
+  const add = () =>
+    run(async () => {
+      await addEntry({
+        client_id: client!.id,
+        started_at: form.started_at,
+        minutes: parseInt(form.minutes, 10),
+        project: form.project || undefined,
+        note: form.note || undefined,
+        billable: form.billable,
+      });
+      setForm({ ...emptyForm(), project: form.project });
+    });
+
+  return (
+    <Dialog open={!!client} onClose={onClose} maxWidth="sm" fullWidth>
+      <DialogTitle>Время · {client?.name}</DialogTitle>
+      <DialogContent sx={{ display: "flex", flexDirection: "column", gap: 1.5 }}>
+        <Box sx={{ display: "flex", gap: 1, alignItems: "center", mt: 1 }}>
+          {runningHere ? (
+            <>
+              <Typography sx={{ fontSize: "0.875rem", flex: 1 }}>
+                Таймер идёт с {format(new Date(running!.started_at), "HH:mm")} ·{" "}
+                {formatMinutes(running!.elapsed_minutes)}
+                {running!.project ? ` · ${running!.project}` : ""}
+              </Typography>
+              <Button variant="contained" color="error" onClick={() => run(stopTimer)}>
+                Стоп
+              </Button>
+            </>
+          ) : (
+            <>
+              <TextField
+                size="small"
+                label="Проект"
+                value={timer.project}
+                onChange={(e) => setTimer({ ...timer, project: e.target.value })}
+                sx={{ width: 160 }}
+              />
+              <TextField
+                size="small"
+                label="Что делаю"
+                value={timer.note}
+                onChange={(e) => setTimer({ ...timer, note: e.target.value })}
+                sx={{ flex: 1 }}
+              />
+              <Button
+                variant="contained"
+                onClick={() => run(() => startTimer(client!.id, timer.project || undefined, timer.note || undefined))}
+              >
+                Старт
+              </Button>
+            </>
+          )}
+        </Box>
+        {runningHere || !running ? null : (
+          <Typography variant="caption" color="text.secondary">
+            Сейчас идёт таймер клиента «{running.client_name}», старт его остановит
+          </Typography>
+        )}
+
+        {periods.length > 0 && (
+          <Box>
+            <Typography sx={{ fontSize: "0.8125rem", fontWeight: 500, mb: 0.5 }}>Оплачиваемые часы</Typography>
+            {periods.map((p) => (
+              <Box key={p.period} sx={{ display: "flex", gap: 1 }}>
+                <Typography sx={{ fontSize: "0.8125rem", minWidth: 90 }}>{p.period}</Typography>
+                <Typography sx={{ fontSize: "0.8125rem", flex: 1 }}>{formatMinutes(p.minutes)}</Typography>
+                <Typography sx={{ fontSize: "0.75rem" }} color={p.unbilled_minutes ? "warning.main" : "text.secondary"}>
+                  {p.unbilled_minutes ? `Не выставлено ${formatMinutes(p.unbilled_minutes)}` : "В счетах"}
+                </Typography>
+              </Box>
+            ))}
+          </Box>
+        )}
+
+        {list.length === 0 && (
+          <Typography variant="body2" color="text.secondary">
+            Записей времени пока нет
+          </Typography>
+        )}
+        {list.map((e) => (
+          <Box key={e.id} sx={{ display: "flex", alignItems: "center", gap: 1 }}>
+            <Typography sx={{ fontSize: "0.8125rem", minWidth: 130 }} color="text.secondary">
+              {format(new Date(e.started_at), "dd.MM.yyyy HH:mm")}
+            </Typography>
+            <Typography sx={{ fontSize: "0.8125rem", minWidth: 90 }} color={e.ended_at ? undefined : "success.main"}>
+              {e.ended_at ? formatMinutes(entryMinutes(e)) : "идёт"}
+            </Typography>
+            <Typography sx={{ fontSize: "0.8125rem", flex: 1 }} color="text.secondary" noWrap>
+              {[e.project, e.note, !e.billable && "не оплачивается", e.invoice_id && "в счёте"]
+                .filter(Boolean)
+                .join(" · ")}
+            </Typography>
+            <IconButton size="small" onClick={() => run(() => deleteEntry(client!.id, e.id))}>
+              <DeleteOutlineRoundedIcon fontSize="small" />
+            </IconButton>
+          </Box>
+        ))}
+
+        <Box sx={{ display: "flex", gap: 1, mt: 2, flexWrap: "wrap", alignItems: "center" }}>
+          <TextField
+            size="small"
+            type="datetime-local"
+            label="Начало"
+            value={form.started_at}
+            onChange={(e) => setForm({ ...form, started_at: e.target.value })}
+            InputLabelProps={{ shrink: true }}
+          />
+          <TextField
+            size="small"
+            type="number"
+            label="Минут"
+            value={form.minutes}
+            onChange={(e) => setForm({ ...form, minutes: e.target.value })}
+            sx={{ width: 100 }}
+          />
+          <TextField
+            size="small"
+            label="Проект"
+            value={form.project}
+            onChange={(e) => setForm({ ...form, project: e.target.value })}
+            sx={{ width: 140 }}
+          />
+          <TextField
+            size="small"
+            label="Комментарий"
+            value={form.note}
+            onChange={(e) => setForm({ ...form, note: e.target.value })}
+            sx={{ flex: 1, minWidth: 140 }}
+          />
+          <FormControlLabel
+            control={<Checkbox checked={form.billable} onChange={(e) => setForm({ ...form, billable: e.target.checked })} />}
+            label="Оплачивается"
+          />
+        </Box>
+      </DialogContent>
+      <DialogActions>
+        <Button variant="text" onClick={onClose}>Закрыть</Button>
+        <Button variant="contained" onClick={add} disabled={!client || !form.started_at || !form.minutes}>
+          Добавить запись
+        </Button>
+      </DialogActions>
+    </Dialog>
+  );
+}
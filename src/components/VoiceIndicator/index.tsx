@@ -6,6 +6,7 @@ import { lightTheme, darkTheme } from "../../theme";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow } from "@tauri-apps/api/window";
+import { errorMessage } from "../../errors";
 import {
   isPermissionGranted,
   requestPermission,
@@ -174,7 +175,7 @@ export default function VoiceIndicator() {
           await processText(text);
           await invoke("emit_voice_message_saved");
         } catch (e) {
-          await notify(`Ошибка: ${e}`);
+          await notify(`Ошибка: ${errorMessage(e)}`);
         } finally {
           await invoke("hide_voice_indicator").catch(() =>
             getCurrentWindow().close().catch(() => {})
@@ -0,0 +1,34 @@
+// Shape of every error returned by a Tauri command (see src-tauri/src/error.rs).
+export interface BlueprintError {
+  code:
+    | "validation"
+    | "not_found"
+    | "missing_api_key"
+    | "invalid_passphrase"
+    | "app_locked"
+    | "database_locked"
+    | "network"
+    | "provider"
+    | "database"
+    | "io"
+    | "internal";
+  message: string;
+  details: Record<string, unknown> | unknown[] | null;
+}
+
+export function isBlueprintError(e: unknown): e is BlueprintError {
+  return typeof e === "object" && e !== null && "code" in e && "message" in e;
+}
+
+export function errorMessage(e: unknown): string {
+  if (isBlueprintError(e)) return e.message;
+  if (e instanceof Error) return e.message;
+  return String(e);
+}
+
+// Sends the user to Settings when a command failed because an API key is missing.
+export function openSettingsIfKeyMissing(e: unknown): void {
+  if (isBlueprintError(e) && e.code === "missing_api_key") {
+    window.location.hash = "#/settings";
+  }
+}
@@ -17,6 +17,7 @@ import CloseRoundedIcon from "@mui/icons-material/CloseRounded";
 import { useChatStore } from "../stores/chatStore";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
+import { errorMessage, openSettingsIfKeyMissing } from "../errors";
 
 const SUPPORTED_EXTENSIONS = [
   ".txt", ".md", ".json", ".csv", ".js", ".ts", ".tsx", ".jsx",
@@ -197,7 +198,8 @@ export default function Chat() {
             const text = await invoke<string>("transcribe_audio", { audioBase64: base64 });
             if (text.trim()) await sendMessage(text.trim());
           } catch (e) {
-            addMessage({ role: "assistant", content: `Ошибка транскрипции: ${e}` });
+            addMessage({ role: "assistant", content: `Ошибка транскрипции: ${errorMessage(e)}` });
+            openSettingsIfKeyMissing(e);
           }
         };
         recorder.start();
@@ -26,28 +26,69 @@ import AddRoundedIcon from "@mui/icons-material/AddRounded";
 import DeleteOutlineRoundedIcon from "@mui/icons-material/DeleteOutlineRounded";
 import EditOutlinedIcon from "@mui/icons-material/EditOutlined";
 import SearchRoundedIcon from "@mui/icons-material/SearchRounded";
-import { format, subMonths } from "date-fns";
+import { addMonths, endOfMonth, format, startOfMonth, subMonths } from "date-fns";
 import { ru } from "date-fns/locale";
-import { useClientsStore, Client } from "../stores/clientsStore";
+import { useClientsStore, Client, PaymentType, PAYMENT_TYPE_LABELS, ScheduleEntry } from "../stores/clientsStore";
+import { useDashboardStore } from "../stores/dashboardStore";
+import { InvoicesDialog } from "../components/Invoices";
+import { PaymentsDialog } from "../components/Payments";
+import { TimeDialog } from "../components/TimeTracking";
+import { useToast } from "../components/ToastProvider";
+import { errorMessage } from "../errors";
 
-const MONTHS = Array.from({ length: 12 }, (_, i) =>
-  format(subMonths(new Date(), i), "yyyy-MM")
-);
+// Eleven months back and two ahead, newest first.
+const SCHEDULE_FROM = format(startOfMonth(subMonths(new Date(), 11)), "yyyy-MM-dd");
+const SCHEDULE_TO = format(endOfMonth(addMonths(new Date(), 2)), "yyyy-MM-dd");
 
-function getMonthLabel(period: string) {
+const STATUS_COLORS: Record<ScheduleEntry["status"], string> = {
+  paid: "#10a37f",
+  unpaid: "#f59e0b",
+  overdue: "#ef4444",
+  upcoming: "#8e8ea0",
+};
+
+const STATUS_LABELS: Record<ScheduleEntry["status"], string> = {
+  paid: "Оплачено",
+  unpaid: "Ожидается",
+  overdue: "Просрочено",
+  upcoming: "Впереди",
+};
+
+function getPeriodLabel(period: string) {
+  if (!/^\d{4}-\d{2}$/.test(period)) return period;
   const [y, m] = period.split("-");
   return format(new Date(parseInt(y), parseInt(m) - 1), "MMM ''yy", { locale: ru });
 }
 
+function describeClient(c: Client) {
+  const parts = [PAYMENT_TYPE_LABELS[c.payment_type]];
+  if (c.amount) parts.push(`${c.amount} ${c.currency}`);
+  if (c.payment_type === "retainer" && c.included_hours) parts.push(`${c.included_hours} ч`);
+  if (c.hourly_rate) parts.push(`${c.hourly_rate} ${c.currency}/ч`);
+  if (c.payment_type !== "onetime" && c.payment_day) {
+    parts.push(c.payment_type === "weekly" ? WEEKDAYS[c.payment_day - 1] : `${c.payment_day}-е число`);
+  }
+  return parts.join(" · ");
+}
+
+const WEEKDAYS = ["пн", "вт", "ср", "чт", "пт", "сб", "вс"];
+
 interface ClientFormData {
   name: string;
   contact: string;
-  payment_type: "monthly" | "onetime";
+  payment_type: PaymentType;
   amount: string;
   currency: string;
   notes: string;
   payment_date: string;
   payment_day: string;
+  payment_notify_days: string;
+  start_date: string;
+  end_date: string;
+  pause_from: string;
+  pause_to: string;
+  included_hours: string;
+  hourly_rate: string;
 }
 
 const defaultForm: ClientFormData = {
@@ -59,11 +100,23 @@ const defaultForm: ClientFormData = {
   notes: "",
   payment_date: format(new Date(), "yyyy-MM-dd"),
   payment_day: "",
+  payment_notify_days: "",
+  start_date: "",
+  end_date: "",
+  pause_from: "",
+  pause_to: "",
+  included_hours: "",
+  hourly_rate: "",
 };
 
 export default function Clients() {
-  const { clients, payments, loading, fetchClients, fetchPayments, createClient, updateClient, deleteClient, togglePayment } =
+  const { clients, payments, schedules, loading, fetchClients, fetchPayments, fetchSchedule, createClient, updateClient, deleteClient, togglePayment } =
     useClientsStore();
+  const { toast } = useToast();
+  const { dashboard, fetchDashboard } = useDashboardStore();
+  const [invoicesFor, setInvoicesFor] = useState<Client | null>(null);
+  const [paymentsFor, setPaymentsFor] = useState<Client | null>(null);
+  const [timeFor, setTimeFor] = useState<Client | null>(null);
   const [selected, setSelected] = useState<Client | null>(null);
   const [search, setSearch] = useState("");
   const [dialogOpen, setDialogOpen] = useState(false);
@@ -77,6 +130,13 @@ export default function Clients() {
 
   useEffect(() => { fetchClients(); }, [fetchClients]);
   useEffect(() => { if (selected) fetchPayments(selected.id); }, [selected, fetchPayments]);
+  useEffect(() => {
+    if (selected) fetchSchedule(selected.id, SCHEDULE_FROM, SCHEDULE_TO).catch(() => {});
+  }, [selected, payments, fetchSchedule]);
+  useEffect(() => {
+    const now = new Date();
+    fetchDashboard(format(startOfMonth(now), "yyyy-MM-dd"), format(endOfMonth(now), "yyyy-MM-dd"));
+  }, [clients, payments, fetchDashboard]);
 
   const filtered = clients.filter((c) =>
     c.name.toLowerCase().includes(search.toLowerCase())
@@ -85,28 +145,29 @@ export default function Clients() {
   const openCreate = () => { setEditClient(null); setForm(defaultForm); setDialogOpen(true); };
   const openEdit = (c: Client) => {
     setEditClient(c);
-    setForm({ name: c.name, contact: c.contact || "", payment_type: c.payment_type as "monthly" | "onetime", amount: c.amount?.toString() || "", currency: c.currency, notes: c.notes || "", payment_date: format(new Date(), "yyyy-MM-dd"), payment_day: c.payment_day?.toString() || "" });
+    setForm({ name: c.name, contact: c.contact || "", payment_type: c.payment_type, amount: c.amount?.toString() || "", currency: c.currency, notes: c.notes || "", payment_date: format(new Date(), "yyyy-MM-dd"), payment_day: c.payment_day?.toString() || "", payment_notify_days: c.payment_notify_days?.toString() ?? "", start_date: c.start_date || "", end_date: c.end_date || "", pause_from: c.pause_from || "", pause_to: c.pause_to || "", included_hours: c.included_hours?.toString() ?? "", hourly_rate: c.hourly_rate?.toString() ?? "" });
     setDialogOpen(true);
   };
 
   const handleSave = async () => {
-    const input = { name: form.name, contact: form.contact || undefined, payment_type: form.payment_type, amount: form.amount ? parseFloat(form.amount) : undefined, currency: form.currency, notes: form.notes || undefined, payment_date: form.payment_type === "onetime" ? form.payment_date : undefined, payment_day: form.payment_type === "monthly" && form.payment_day ? parseInt(form.payment_day, 10) : undefined };
-    if (editClient) await updateClient({ ...editClient, ...input });
-    else await createClient(input as Parameters<typeof createClient>[0]);
-    setDialogOpen(false);
+    const recurring = form.payment_type !== "onetime";
+    const input = { name: form.name, contact: form.contact || undefined, payment_type: form.payment_type, amount: form.amount ? parseFloat(form.amount) : undefined, currency: form.currency, notes: form.notes || undefined, payment_date: form.payment_type === "onetime" ? form.payment_date : undefined, payment_day: recurring && form.payment_day ? parseInt(form.payment_day, 10) : undefined, payment_notify_days: recurring && form.payment_notify_days !== "" ? parseInt(form.payment_notify_days, 10) : undefined, start_date: recurring ? form.start_date || undefined : undefined, end_date: recurring ? form.end_date || undefined : undefined, pause_from: recurring ? form.pause_from || undefined : undefined, pause_to: recurring ? form.pause_to || undefined : undefined, included_hours: form.payment_type === "retainer" && form.included_hours ? parseFloat(form.included_hours) : undefined, hourly_rate: (form.payment_type === "retainer" || form.payment_type === "hourly") && form.hourly_rate ? parseFloat(form.hourly_rate) : undefined };
+    try {
+      if (editClient) await updateClient({ ...editClient, ...input });
+      else await createClient(input as Parameters<typeof createClient>[0]);
+      setDialogOpen(false);
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
   };
 
   const clientPayments = selected ? (payments[selected.id] || []) : [];
+  const schedule = selected ? [...(schedules[selected.id] || [])].reverse() : [];
 
-  const monthlyClients = clients.filter((c) => c.payment_type === "monthly");
-  const monthlyIncome = monthlyClients.reduce((sum, c) => sum + (c.amount || 0), 0);
-  const currentMonth = format(new Date(), "yyyy-MM");
-  const paidThisMonth = monthlyClients.filter((c) => {
-    const cp = payments[c.id];
-    return cp?.some((p) => p.period === currentMonth && p.paid);
-  }).length;
-  const paidPercent = monthlyClients.length > 0
-    ? Math.round((paidThisMonth / monthlyClients.length) * 100)
+  const thisMonth = dashboard?.income[0];
+  const monthlyIncome = thisMonth?.expected ?? 0;
+  const paidPercent = monthlyIncome > 0
+    ? Math.round(((thisMonth?.received ?? 0) / monthlyIncome) * 100)
     : 0;
 
   return (
@@ -164,20 +225,32 @@ export default function Clients() {
               <Box sx={{ width: "1px", backgroundColor: border }} />
               <Box sx={{ flex: 1, textAlign: "center" }}>
                 <Typography sx={{ fontSize: "0.9375rem", fontWeight: 700, color: isDark ? "#ececec" : "#0d0d0d", lineHeight: 1.2 }}>
-                  {monthlyIncome > 0 ? `${monthlyIncome.toLocaleString()}` : "—"}
+                  {monthlyIncome > 0 ? `${Math.round(monthlyIncome).toLocaleString()} ${dashboard?.base_currency}` : "—"}
                 </Typography>
                 <Typography sx={{ fontSize: "0.6875rem", color: isDark ? "#6e6e80" : "#8e8ea0", mt: 0.25 }}>в мес</Typography>
               </Box>
               <Box sx={{ width: "1px", backgroundColor: border }} />
               <Box sx={{ flex: 1, textAlign: "center" }}>
                 <Typography sx={{ fontSize: "0.9375rem", fontWeight: 700, color: paidPercent === 100 ? "#10a37f" : isDark ? "#ececec" : "#0d0d0d", lineHeight: 1.2 }}>
-                  {monthlyClients.length > 0 ? `${paidPercent}%` : "—"}
+                  {monthlyIncome > 0 ? `${paidPercent}%` : "—"}
                 </Typography>
                 <Typography sx={{ fontSize: "0.6875rem", color: isDark ? "#6e6e80" : "#8e8ea0", mt: 0.25 }}>оплачено</Typography>
               </Box>
             </Box>
           )}
 
+          {dashboard && dashboard.overdue.length > 0 && (
+            <Typography sx={{ fontSize: "0.75rem", color: "#ef4444", mb: 1.5 }}>
+              Просрочено: {dashboard.overdue.length} · {Math.round(dashboard.overdue_total).toLocaleString()} {dashboard.base_currency}
+            </Typography>
+          )}
+
+          {dashboard && dashboard.unconverted.length > 0 && (
+            <Typography sx={{ fontSize: "0.75rem", color: "warning.main", mb: 1.5 }}>
+              Не учтено без курса: {[...new Set(dashboard.unconverted.map((u) => u.currency))].join(", ")} → {dashboard.base_currency}
+            </Typography>
+          )}
+
           <TextField
             fullWidth
             size="small"
@@ -222,7 +295,7 @@ export default function Clients() {
             >
               <ListItemText
                 primary={c.name}
-                secondary={`${c.payment_type === "monthly" ? "Ежемесячно" : "Разовый"}${c.amount ? ` · ${c.amount} ${c.currency}` : ""}${c.payment_type === "monthly" && c.payment_day ? ` · ${c.payment_day}-е` : ""}`}
+                secondary={describeClient(c)}
                 primaryTypographyProps={{
                   fontWeight: selected?.id === c.id ? 500 : 400,
                   fontSize: "0.875rem",
@@ -287,10 +360,17 @@ export default function Clients() {
                   {selected.name}
                 </Typography>
                 <Typography sx={{ fontSize: "0.875rem", color: isDark ? "#6e6e80" : "#8e8ea0" }}>
-                  {selected.payment_type === "monthly" ? "Ежемесячно" : "Разовый"}
-                  {selected.amount ? ` · ${selected.amount} ${selected.currency}` : ""}
-                  {selected.payment_type === "monthly" && selected.payment_day ? ` · ${selected.payment_day}-е число` : ""}
+                  {describeClient(selected)}
                 </Typography>
+                <Button size="small" variant="text" onClick={() => setTimeFor(selected)} sx={{ ml: "auto" }}>
+                  Время
+                </Button>
+                <Button size="small" variant="text" onClick={() => setPaymentsFor(selected)}>
+                  Поступления
+                </Button>
+                <Button size="small" variant="text" onClick={() => setInvoicesFor(selected)}>
+                  Счета
+                </Button>
               </Box>
               {selected.contact && (
                 <Typography sx={{ fontSize: "0.875rem", color: isDark ? "#6e6e80" : "#8e8ea0" }}>
@@ -306,7 +386,7 @@ export default function Clients() {
 
             <Divider sx={{ mb: 2.5 }} />
 
-            {selected.payment_type === "monthly" && (
+            {selected.payment_type !== "onetime" && (
               <>
                 <Typography
                   sx={{
@@ -318,15 +398,22 @@ export default function Clients() {
                     mb: 1.5,
                   }}
                 >
-                  Платежи — последние 12 месяцев
+                  График платежей
                 </Typography>
+                {schedule.length === 0 && (
+                  <Typography sx={{ fontSize: "0.875rem", color: isDark ? "#4a4a5a" : "#c5c5d2" }}>
+                    Нет ожидаемых платежей за этот период
+                  </Typography>
+                )}
                 <Box sx={{ display: "flex", flexWrap: "wrap", gap: 1 }}>
-                  {MONTHS.map((month) => {
-                    const payment = clientPayments.find((p) => p.period === month);
-                    const paid = payment?.paid || false;
+                  {schedule.map((entry) => {
+                    const month = entry.period;
+                    const paid = entry.status === "paid";
+                    const partial = !paid && entry.received > 0;
                     return (
                       <Box
                         key={month}
+                        title={`${STATUS_LABELS[entry.status]} · до ${entry.due_date}${entry.scheduled ? "" : " · вне графика"}`}
                         onClick={() => togglePayment(selected.id, month, !paid)}
                         sx={{
                           display: "flex",
@@ -354,15 +441,15 @@ export default function Clients() {
                           sx={{
                             fontSize: "0.75rem",
                             fontWeight: 500,
-                            color: paid
-                              ? "#10a37f"
-                              : isDark ? "#6e6e80" : "#acacbe",
+                            color: partial ? STATUS_COLORS.unpaid : STATUS_COLORS[entry.status],
+                            opacity: entry.scheduled ? 1 : 0.6,
                           }}
                         >
-                          {getMonthLabel(month)}
+                          {getPeriodLabel(month)}
                         </Typography>
                         <Checkbox
                           checked={paid}
+                          indeterminate={partial}
                           size="small"
                           disableRipple
                           sx={{
@@ -439,7 +526,11 @@ export default function Clients() {
                           : isDark ? "#f59e0b" : "#b45309",
                       }}
                     >
-                      {p.paid ? "Оплачено" : "Ожидает"}
+                      {p.paid
+                        ? "Оплачено"
+                        : p.received > 0 && p.outstanding != null
+                          ? `Долг ${p.outstanding.toFixed(2)} ${p.currency}`
+                          : "Ожидает"}
                     </Typography>
                   </Box>
                 ))}
@@ -456,9 +547,10 @@ export default function Clients() {
           <TextField label="Контакт" value={form.contact} onChange={(e) => setForm({ ...form, contact: e.target.value })} />
           <FormControl size="small">
             <InputLabel>Тип платежа</InputLabel>
-            <Select value={form.payment_type} label="Тип платежа" onChange={(e) => setForm({ ...form, payment_type: e.target.value as "monthly" | "onetime" })}>
-              <MenuItem value="monthly">Ежемесячный</MenuItem>
-              <MenuItem value="onetime">Разовый</MenuItem>
+            <Select value={form.payment_type} label="Тип платежа" onChange={(e) => setForm({ ...form, payment_type: e.target.value as PaymentType, payment_day: "" })}>
+              {(Object.keys(PAYMENT_TYPE_LABELS) as PaymentType[]).map((type) => (
+                <MenuItem key={type} value={type}>{PAYMENT_TYPE_LABELS[type]}</MenuItem>
+              ))}
             </Select>
           </FormControl>
           <Box sx={{ display: "flex", gap: 1 }}>
@@ -468,18 +560,61 @@ export default function Clients() {
           {form.payment_type === "onetime" && (
             <TextField label="Дата платежа" type="date" value={form.payment_date} onChange={(e) => setForm({ ...form, payment_date: e.target.value })} InputLabelProps={{ shrink: true }} />
           )}
-          {form.payment_type === "monthly" && (
+          {form.payment_type === "retainer" && (
+            <TextField label="Часов в месяц" type="number" value={form.included_hours} onChange={(e) => setForm({ ...form, included_hours: e.target.value })} inputProps={{ min: 0 }} required />
+          )}
+          {(form.payment_type === "retainer" || form.payment_type === "hourly") && (
+            <TextField
+              label={form.payment_type === "retainer" ? "Ставка сверх абонемента" : "Ставка в час"}
+              type="number"
+              value={form.hourly_rate}
+              onChange={(e) => setForm({ ...form, hourly_rate: e.target.value })}
+              inputProps={{ min: 0 }}
+              required={form.payment_type === "hourly"}
+            />
+          )}
+          {form.payment_type !== "onetime" && (
             <TextField
-              label="День оплаты (1–28)"
+              label={form.payment_type === "weekly" ? "День недели (1 — пн, 7 — вс)" : "День оплаты (1–28)"}
               type="number"
               value={form.payment_day}
               onChange={(e) => {
                 const v = e.target.value;
                 const n = parseInt(v, 10);
-                if (v === "" || (n >= 1 && n <= 28)) setForm({ ...form, payment_day: v });
+                const max = form.payment_type === "weekly" ? 7 : 28;
+                if (v === "" || (n >= 1 && n <= max)) setForm({ ...form, payment_day: v });
               }}
-              inputProps={{ min: 1, max: 28 }}
-              helperText="Число месяца, когда ожидается оплата"
+              inputProps={{ min: 1, max: form.payment_type === "weekly" ? 7 : 28 }}
+              helperText={
+                form.payment_type === "weekly"
+                  ? "Пусто — воскресенье"
+                  : form.payment_type === "quarterly" || form.payment_type === "yearly"
+                    ? "Число первого месяца периода; пусто — конец периода"
+                    : "Число месяца, когда ожидается оплата"
+              }
+            />
+          )}
+          {form.payment_type !== "onetime" && (
+            <Box sx={{ display: "flex", gap: 1 }}>
+              <TextField label="Начало работы" type="date" value={form.start_date} onChange={(e) => setForm({ ...form, start_date: e.target.value })} InputLabelProps={{ shrink: true }} sx={{ flex: 1 }} helperText="Пусто — дата добавления" />
+              <TextField label="Окончание" type="date" value={form.end_date} onChange={(e) => setForm({ ...form, end_date: e.target.value })} InputLabelProps={{ shrink: true }} sx={{ flex: 1 }} />
+            </Box>
+          )}
+          {form.payment_type !== "onetime" && (
+            <Box sx={{ display: "flex", gap: 1 }}>
+              <TextField label="Пауза с" type="date" value={form.pause_from} onChange={(e) => setForm({ ...form, pause_from: e.target.value })} InputLabelProps={{ shrink: true }} sx={{ flex: 1 }} />
+              <TextField label="Пауза по" type="date" value={form.pause_to} onChange={(e) => setForm({ ...form, pause_to: e.target.value })} InputLabelProps={{ shrink: true }} sx={{ flex: 1 }} helperText="Пусто — до отмены" />
+            </Box>
+          )}
+          {form.payment_type !== "onetime" && form.payment_day && (
+            <TextField
+              label="Напомнить за (дней)"
+              type="number"
+              value={form.payment_notify_days}
+              onChange={(e) => setForm({ ...form, payment_notify_days: e.target.value })}
+              inputProps={{ min: 0, max: 27 }}
+              placeholder="3"
+              helperText="Пусто — за 3 дня"
             />
           )}
           <TextField label="Заметки" multiline rows={2} value={form.notes} onChange={(e) => setForm({ ...form, notes: e.target.value })} />
@@ -518,6 +653,19 @@ export default function Clients() {
           </Button>
         </DialogActions>
       </Dialog>
+      <PaymentsDialog client={paymentsFor} onClose={() => setPaymentsFor(null)} />
+      <TimeDialog
+        client={timeFor}
+        onClose={() => {
+          if (timeFor) fetchPayments(timeFor.id);
+          setTimeFor(null);
+        }}
+      />
+      <InvoicesDialog
+        client={invoicesFor}
+        onClose={() => setInvoicesFor(null)}
+        onPaidChange={() => invoicesFor && fetchPayments(invoicesFor.id)}
+      />
     </Box>
   );
 }
@@ -24,6 +24,7 @@ import AddRoundedIcon from "@mui/icons-material/AddRounded";
 import DeleteOutlineRoundedIcon from "@mui/icons-material/DeleteOutlineRounded";
 import EditOutlinedIcon from "@mui/icons-material/EditOutlined";
 import AssignmentOutlinedIcon from "@mui/icons-material/AssignmentOutlined";
+import { format, parseISO } from "date-fns";
 import { useNotesStore } from "../stores/notesStore";
 
 const PRESET_COLORS = [
@@ -43,9 +44,9 @@ export default function Notes() {
 
   const [noteDialog, setNoteDialog] = useState(false);
   const [catDialog, setCatDialog] = useState(false);
-  const [editNote, setEditNote] = useState<{ id: string; title: string; content?: string; category_id?: string } | null>(null);
+  const [editNote, setEditNote] = useState<{ id: string; title: string; content?: string; category_id?: string; remind_at?: string } | null>(null);
   const [editCategoryId, setEditCategoryId] = useState<string | null>(null);
-  const [noteForm, setNoteForm] = useState({ title: "", content: "", category_id: "" });
+  const [noteForm, setNoteForm] = useState({ title: "", content: "", category_id: "", remind_at: "" });
   const [catForm, setCatForm] = useState({ name: "", color: "#0ea5e9" });
   const [deleteNoteConfirm, setDeleteNoteConfirm] = useState<string | null>(null);
   const [deleteCatConfirm, setDeleteCatConfirm] = useState<string | null>(null);
@@ -58,16 +59,26 @@ export default function Notes() {
 
   const openCreateNote = () => {
     setEditNote(null);
-    setNoteForm({ title: "", content: "", category_id: selectedCategoryId || "" });
+    setNoteForm({ title: "", content: "", category_id: selectedCategoryId || "", remind_at: "" });
     setNoteDialog(true);
   };
   const openEditNote = (note: typeof notes[0]) => {
     setEditNote(note);
-    setNoteForm({ title: note.title, content: note.content || "", category_id: note.category_id || "" });
+    setNoteForm({
+      title: note.title,
+      content: note.content || "",
+      category_id: note.category_id || "",
+      remind_at: note.remind_at ? format(parseISO(note.remind_at), "yyyy-MM-dd'T'HH:mm") : "",
+    });
     setNoteDialog(true);
   };
   const handleSaveNote = async () => {
-    const input = { title: noteForm.title, content: noteForm.content || undefined, category_id: noteForm.category_id || undefined };
+    const input = {
+      title: noteForm.title,
+      content: noteForm.content || undefined,
+      category_id: noteForm.category_id || undefined,
+      remind_at: noteForm.remind_at ? new Date(noteForm.remind_at).toISOString() : undefined,
+    };
     if (editNote) await updateNote({ ...input, id: editNote.id });
     else await createNote(input);
     setNoteDialog(false);
@@ -539,6 +550,13 @@ export default function Notes() {
               </MenuItem>
             ))}
           </TextField>
+          <TextField
+            label="Напомнить"
+            type="datetime-local"
+            value={noteForm.remind_at}
+            onChange={(e) => setNoteForm({ ...noteForm, remind_at: e.target.value })}
+            InputLabelProps={{ shrink: true }}
+          />
         </DialogContent>
         <DialogActions>
           <Button variant="text" onClick={() => setNoteDialog(false)}>Отмена</Button>
@@ -10,6 +10,13 @@ import {
   TextField,
   IconButton,
   InputAdornment,
+  Menu,
+  MenuItem,
+  FormControl,
+  FormControlLabel,
+  InputLabel,
+  Select,
+  Switch,
   Tooltip,
   CircularProgress,
   Divider,
@@ -20,6 +27,10 @@ import DeleteOutlineRoundedIcon from "@mui/icons-material/DeleteOutlineRounded";
 import EditOutlinedIcon from "@mui/icons-material/EditOutlined";
 import SearchRoundedIcon from "@mui/icons-material/SearchRounded";
 import OpenInNewRoundedIcon from "@mui/icons-material/OpenInNewRounded";
+import NotificationsPausedOutlinedIcon from "@mui/icons-material/NotificationsPausedOutlined";
+import AutorenewRoundedIcon from "@mui/icons-material/AutorenewRounded";
+import ReceiptLongOutlinedIcon from "@mui/icons-material/ReceiptLongOutlined";
+import { listen } from "@tauri-apps/api/event";
 import { format, parseISO } from "date-fns";
 import { ru } from "date-fns/locale";
 import {
@@ -27,7 +38,11 @@ import {
   Service,
   getDaysRemaining,
   getExpiryStatus,
+  isSnoozed,
 } from "../stores/servicesStore";
+import { useToast } from "../components/ToastProvider";
+import { ServiceChargesDialog, SpendingSummaryDialog } from "../components/ServiceCharges";
+import { errorMessage } from "../errors";
 
 function getStatusDot(status: string, isDark: boolean) {
   const map: Record<string, { color: string; label: string }> = {
@@ -50,8 +65,19 @@ interface ServiceFormData {
   notes: string;
   category: string;
   notify_days: string;
+  notify_thresholds: string;
+  billing_cycle: "" | "monthly" | "quarterly" | "yearly" | "custom";
+  billing_cycle_days: string;
+  auto_renew: boolean;
 }
 
+const CYCLE_LABELS: Record<string, string> = {
+  monthly: "Ежемесячно",
+  quarterly: "Ежеквартально",
+  yearly: "Ежегодно",
+  custom: "Свой период",
+};
+
 const defaultForm: ServiceFormData = {
   project_name: "",
   service_name: "",
@@ -63,6 +89,10 @@ const defaultForm: ServiceFormData = {
   notes: "",
   category: "",
   notify_days: "7",
+  notify_thresholds: "",
+  billing_cycle: "",
+  billing_cycle_days: "",
+  auto_renew: false,
 };
 
 const FILTER_DEFS = [
@@ -73,8 +103,22 @@ const FILTER_DEFS = [
 ];
 
 export default function Services() {
-  const { services, loading, fetchServices, createService, updateService, deleteService } =
-    useServicesStore();
+  const {
+    services,
+    loading,
+    fetchServices,
+    createService,
+    updateService,
+    deleteService,
+    snoozeService,
+    acknowledgeService,
+    clearServiceSnooze,
+    renewService,
+  } = useServicesStore();
+  const { toast } = useToast();
+  const [chargesFor, setChargesFor] = useState<Service | null>(null);
+  const [spendingOpen, setSpendingOpen] = useState(false);
+  const [snoozeMenu, setSnoozeMenu] = useState<{ anchor: HTMLElement; service: Service } | null>(null);
   const [search, setSearch] = useState("");
   const [dialogOpen, setDialogOpen] = useState(false);
   const [editService, setEditService] = useState<Service | null>(null);
@@ -88,6 +132,13 @@ export default function Services() {
 
   useEffect(() => { fetchServices(); }, [fetchServices]);
 
+  useEffect(() => {
+    const unlistenPromise = listen("services-renewed", () => {
+      fetchServices();
+    });
+    return () => { unlistenPromise.then((fn) => fn()); };
+  }, [fetchServices]);
+
   const filtered = services.filter((s) => {
     const matchesSearch =
       s.service_name.toLowerCase().includes(search.toLowerCase()) ||
@@ -104,7 +155,9 @@ export default function Services() {
       project_name: s.project_name, service_name: s.service_name, login: s.login || "",
       url: s.url || "", expires_at: s.expires_at, cost: s.cost?.toString() || "",
       currency: s.currency, notes: s.notes || "", category: s.category || "",
-      notify_days: s.notify_days.toString(),
+      notify_days: s.notify_days.toString(), notify_thresholds: s.notify_thresholds || "",
+      billing_cycle: s.billing_cycle ?? "", billing_cycle_days: s.billing_cycle_days?.toString() ?? "",
+      auto_renew: s.auto_renew,
     });
     setDialogOpen(true);
   };
@@ -116,10 +169,18 @@ export default function Services() {
       expires_at: form.expires_at, cost: form.cost ? parseFloat(form.cost) : undefined,
       currency: form.currency, notes: form.notes || undefined,
       category: form.category || undefined, notify_days: parseInt(form.notify_days, 10) || 7,
+      notify_thresholds: form.notify_thresholds.trim() || undefined,
+      billing_cycle: form.billing_cycle || undefined,
+      billing_cycle_days: form.billing_cycle === "custom" ? parseInt(form.billing_cycle_days, 10) || undefined : undefined,
+      auto_renew: form.billing_cycle !== "" && form.auto_renew,
     };
-    if (editService) await updateService({ ...editService, ...input });
-    else await createService(input as Parameters<typeof createService>[0]);
-    setDialogOpen(false);
+    try {
+      if (editService) await updateService({ ...editService, ...input });
+      else await createService(input as Parameters<typeof createService>[0]);
+      setDialogOpen(false);
+    } catch (e) {
+      toast(errorMessage(e), "error");
+    }
   };
 
   const statusCounts = {
@@ -161,6 +222,9 @@ export default function Services() {
           }}
           sx={{ width: 200 }}
         />
+        <Button variant="text" size="small" onClick={() => setSpendingOpen(true)}>
+          Расходы
+        </Button>
         <Button
           startIcon={<AddRoundedIcon sx={{ fontSize: 16 }} />}
           variant="outlined"
@@ -223,6 +287,45 @@ export default function Services() {
         })}
       </Box>
 
+      <ServiceChargesDialog service={chargesFor} onClose={() => setChargesFor(null)} />
+      <SpendingSummaryDialog open={spendingOpen} onClose={() => setSpendingOpen(false)} />
+
+      <Menu
+        anchorEl={snoozeMenu?.anchor}
+        open={Boolean(snoozeMenu)}
+        onClose={() => setSnoozeMenu(null)}
+      >
+        {[1, 3, 7].map((days) => (
+          <MenuItem
+            key={days}
+            onClick={() => {
+              if (snoozeMenu) snoozeService(snoozeMenu.service.id, days);
+              setSnoozeMenu(null);
+            }}
+          >
+            Отложить на {days} дн
+          </MenuItem>
+        ))}
+        <MenuItem
+          onClick={() => {
+            if (snoozeMenu) acknowledgeService(snoozeMenu.service.id);
+            setSnoozeMenu(null);
+          }}
+        >
+          Не напоминать до продления
+        </MenuItem>
+        {snoozeMenu && isSnoozed(snoozeMenu.service) && (
+          <MenuItem
+            onClick={() => {
+              clearServiceSnooze(snoozeMenu.service.id);
+              setSnoozeMenu(null);
+            }}
+          >
+            Возобновить напоминания
+          </MenuItem>
+        )}
+      </Menu>
+
       <Box sx={{ flex: 1, overflow: "auto" }}>
         {loading && (
           <Box sx={{ display: "flex", justifyContent: "center", py: 8 }}>
@@ -319,6 +422,13 @@ export default function Services() {
                   <Typography sx={{ fontSize: "0.8125rem", color: isDark ? "#6e6e80" : "#8e8ea0" }}>
                     {format(parseISO(s.expires_at), "d MMM yyyy", { locale: ru })}
                   </Typography>
+                  {isSnoozed(s) && (
+                    <Typography sx={{ fontSize: "0.75rem", color: isDark ? "#6e6e80" : "#8e8ea0" }}>
+                      {s.acknowledged_expires_at === s.expires_at
+                        ? "Без напоминаний до продления"
+                        : `Отложено до ${format(parseISO(s.snoozed_until!), "d MMM", { locale: ru })}`}
+                    </Typography>
+                  )}
                 </Box>
 
                 {s.cost && (
@@ -356,6 +466,35 @@ export default function Services() {
                       </IconButton>
                     </Tooltip>
                   )}
+                  {s.billing_cycle && (
+                    <Tooltip title={`Продлить (${CYCLE_LABELS[s.billing_cycle].toLowerCase()})`} arrow>
+                      <IconButton
+                        size="small"
+                        onClick={() =>
+                          renewService(s.id)
+                            .then((r) => toast(`Продлено до ${format(parseISO(r.expires_at), "d MMM yyyy", { locale: ru })}`, "success"))
+                            .catch((e) => toast(errorMessage(e), "error"))
+                        }
+                        sx={{ width: 30, height: 30 }}
+                      >
+                        <AutorenewRoundedIcon sx={{ fontSize: 15 }} />
+                      </IconButton>
+                    </Tooltip>
+                  )}
+                  <Tooltip title="Платежи" arrow>
+                    <IconButton size="small" onClick={() => setChargesFor(s)} sx={{ width: 30, height: 30 }}>
+                      <ReceiptLongOutlinedIcon sx={{ fontSize: 15 }} />
+                    </IconButton>
+                  </Tooltip>
+                  <Tooltip title="Напоминания" arrow>
+                    <IconButton
+                      size="small"
+                      onClick={(e) => setSnoozeMenu({ anchor: e.currentTarget, service: s })}
+                      sx={{ width: 30, height: 30 }}
+                    >
+                      <NotificationsPausedOutlinedIcon sx={{ fontSize: 15 }} />
+                    </IconButton>
+                  </Tooltip>
                   <IconButton size="small" onClick={() => openEdit(s)} sx={{ width: 30, height: 30 }}>
                     <EditOutlinedIcon sx={{ fontSize: 15 }} />
                   </IconButton>
@@ -382,7 +521,44 @@ export default function Services() {
             <TextField label="Валюта" value={form.currency} onChange={(e) => setForm({ ...form, currency: e.target.value })} sx={{ width: 90 }} />
           </Box>
           <TextField label="Категория" value={form.category} onChange={(e) => setForm({ ...form, category: e.target.value })} />
-          <TextField label="Уведомить за (дней)" type="number" value={form.notify_days} onChange={(e) => setForm({ ...form, notify_days: e.target.value })} />
+          <Box sx={{ display: "flex", gap: 1, alignItems: "center" }}>
+            <FormControl size="small" sx={{ flex: 1 }}>
+              <InputLabel>Период оплаты</InputLabel>
+              <Select
+                value={form.billing_cycle}
+                label="Период оплаты"
+                onChange={(e) => setForm({ ...form, billing_cycle: e.target.value as ServiceFormData["billing_cycle"] })}
+              >
+                <MenuItem value="">Не задан</MenuItem>
+                {Object.entries(CYCLE_LABELS).map(([value, label]) => (
+                  <MenuItem key={value} value={value}>{label}</MenuItem>
+                ))}
+              </Select>
+            </FormControl>
+            {form.billing_cycle === "custom" && (
+              <TextField
+                label="Дней"
+                type="number"
+                value={form.billing_cycle_days}
+                onChange={(e) => setForm({ ...form, billing_cycle_days: e.target.value })}
+                inputProps={{ min: 1 }}
+                sx={{ width: 90 }}
+              />
+            )}
+          </Box>
+          {form.billing_cycle && (
+            <FormControlLabel
+              control={<Switch checked={form.auto_renew} onChange={(e) => setForm({ ...form, auto_renew: e.target.checked })} />}
+              label="Продлевается автоматически"
+            />
+          )}
+          <TextField
+            label="Напоминать за (дней)"
+            value={form.notify_thresholds}
+            onChange={(e) => setForm({ ...form, notify_thresholds: e.target.value })}
+            placeholder="30,7,1,0"
+            helperText="Через запятую. Пусто — правило категории или общее"
+          />
           <TextField label="Заметки" multiline rows={2} value={form.notes} onChange={(e) => setForm({ ...form, notes: e.target.value })} />
         </DialogContent>
         <DialogActions>
@@ -30,12 +30,18 @@ import VisibilityOffOutlinedIcon from "@mui/icons-material/VisibilityOffOutlined
 import KeyOutlinedIcon from "@mui/icons-material/KeyOutlined";
 import type { ReactNode } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useSettingsStore } from "../stores/settingsStore";
+import FxRates from "../components/FxRates";
+import { CompanySettings } from "../components/Invoices";
+import { errorMessage } from "../errors";
 
 interface ProfileExport {
   version: number;
   exported_at: string;
+  secrets_included: boolean;
   settings: Array<Record<string, unknown>>;
+  secrets: Array<Record<string, unknown>>;
   clients: Array<Record<string, unknown>>;
   client_payments: Array<Record<string, unknown>>;
   services: Array<Record<string, unknown>>;
@@ -44,6 +50,19 @@ interface ProfileExport {
   chat_history: Array<Record<string, unknown>>;
 }
 
+interface SecretStatus {
+  name: string;
+  configured: boolean;
+  hint: string | null;
+  updated_at: string | null;
+}
+
+interface RevealedSecret {
+  name: string;
+  value: string;
+  expires_in_secs: number;
+}
+
 const AI_PROVIDERS = [
   { value: "openai", label: "OpenAI (GPT)" },
   { value: "anthropic", label: "Anthropic (Claude)" },
@@ -291,6 +310,11 @@ const VOICE_PROVIDERS = [
   },
 ];
 
+interface NotificationRule {
+  category: string;
+  thresholds: string;
+}
+
 export default function Settings() {
   const {
     theme,
@@ -302,6 +326,12 @@ export default function Settings() {
     aiModel,
     aiBaseUrl,
     voiceProvider,
+    language,
+    schedulerIntervalMinutes,
+    quietHoursStart,
+    quietHoursEnd,
+    timeRoundingMinutes,
+    timeRoundingMode,
     loadSettings,
     saveSetting,
     setTheme,
@@ -319,6 +349,9 @@ export default function Settings() {
   const [localCustomModel, setLocalCustomModel] = useState("");
   const [localBaseUrl, setLocalBaseUrl] = useState(aiBaseUrl);
   const [localVoiceProvider, setLocalVoiceProvider] = useState(voiceProvider);
+  const [localThresholds, setLocalThresholds] = useState(notifyDaysBefore);
+  const [categoryRules, setCategoryRules] = useState<NotificationRule[]>([]);
+  const [newRule, setNewRule] = useState<NotificationRule>({ category: "", thresholds: "" });
   const [groqKey, setGroqKey] = useState("");
   const [showGroqKey, setShowGroqKey] = useState(false);
   const [snack, setSnack] = useState<{ open: boolean; msg: string; severity: "success" | "error" }>({
@@ -326,6 +359,21 @@ export default function Settings() {
   });
   const [autostartEnabled, setAutostartEnabled] = useState(false);
   const importInputRef = useRef<HTMLInputElement>(null);
+  const [apiKeyHint, setApiKeyHint] = useState("");
+  const [groqKeyHint, setGroqKeyHint] = useState("");
+  const revealedRef = useRef<Record<string, string>>({});
+
+  useEffect(() => { setLocalThresholds(notifyDaysBefore); }, [notifyDaysBefore]);
+
+  const saveCategoryRule = async (category: string, thresholds: string) => {
+    try {
+      await invoke("set_notification_rule", { category, thresholds: thresholds.trim() || null });
+      setCategoryRules(await invoke<NotificationRule[]>("get_notification_rules"));
+      setNewRule({ category: "", thresholds: "" });
+    } catch (e) {
+      setSnack({ open: true, msg: `Ошибка: ${errorMessage(e)}`, severity: "error" });
+    }
+  };
 
   const isCustomModel =
     localProvider === "local" &&
@@ -333,14 +381,54 @@ export default function Settings() {
 
   useEffect(() => {
     loadSettings();
-    invoke<string>("get_ai_key").then((k) => setApiKey(k || ""));
-    invoke<string>("get_groq_key").then((k) => setGroqKey(k || "")).catch(() => {});
+    invoke<SecretStatus>("get_secret_status", { name: "ai_api_key" })
+      .then((s) => setApiKeyHint(s.hint ?? ""))
+      .catch(() => {});
+    invoke<SecretStatus>("get_secret_status", { name: "groq_api_key" })
+      .then((s) => setGroqKeyHint(s.hint ?? ""))
+      .catch(() => {});
     invoke<boolean>("get_autostart_enabled").then((v) => setAutostartEnabled(v)).catch(() => {});
+    invoke<NotificationRule[]>("get_notification_rules").then(setCategoryRules).catch(() => {});
     return () => {
       invoke("resume_hotkeys").catch(() => {});
     };
   }, [loadSettings]);
 
+  useEffect(() => {
+    const unlisten = listen<string>("secret-hidden", (e) => {
+      const revealed = revealedRef.current[e.payload];
+      if (revealed === undefined) return;
+      delete revealedRef.current[e.payload];
+      if (e.payload === "ai_api_key") {
+        setApiKey((v) => (v === revealed ? "" : v));
+        setShowKey(false);
+      } else if (e.payload === "groq_api_key") {
+        setGroqKey((v) => (v === revealed ? "" : v));
+        setShowGroqKey(false);
+      }
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  const toggleSecret = async (
+    name: string,
+    value: string,
+    setValue: (v: string) => void,
+    show: boolean,
+    setShow: (v: boolean) => void,
+  ) => {
+    if (!show && value === "") {
+      const secret = await invoke<RevealedSecret>("reveal_secret", { name }).catch(() => null);
+      if (secret) {
+        revealedRef.current[name] = secret.value;
+        setValue(secret.value);
+      }
+    }
+    setShow(!show);
+  };
+
   const handleAutostartToggle = async (enabled: boolean) => {
     try {
       await invoke("toggle_autostart", { enable: enabled });
@@ -348,7 +436,7 @@ export default function Settings() {
       await saveSetting("autostart", enabled ? "true" : "false");
       setSnack({ open: true, msg: enabled ? "Автозапуск включён" : "Автозапуск отключён", severity: "success" });
     } catch (e) {
-      setSnack({ open: true, msg: `Ошибка: ${e}`, severity: "error" });
+      setSnack({ open: true, msg: `Ошибка: ${errorMessage(e)}`, severity: "error" });
     }
   };
 
@@ -392,7 +480,7 @@ export default function Settings() {
       await saveSetting("voice_hotkey", localVoiceHotkey);
       setSnack({ open: true, msg: "Хоткеи обновлены", severity: "success" });
     } catch (e) {
-      setSnack({ open: true, msg: `Ошибка: ${e}`, severity: "error" });
+      setSnack({ open: true, msg: `Ошибка: ${errorMessage(e)}`, severity: "error" });
     }
   };
 
@@ -429,7 +517,7 @@ export default function Settings() {
       URL.revokeObjectURL(url);
       setSnack({ open: true, msg: "Профиль экспортирован", severity: "success" });
     } catch (e) {
-      setSnack({ open: true, msg: `Ошибка экспорта: ${e}`, severity: "error" });
+      setSnack({ open: true, msg: `Ошибка экспорта: ${errorMessage(e)}`, severity: "error" });
     }
   };
 
@@ -445,7 +533,7 @@ export default function Settings() {
       setSnack({ open: true, msg: "Профиль импортирован. Перезагрузка...", severity: "success" });
       setTimeout(() => window.location.reload(), 800);
     } catch (err) {
-      setSnack({ open: true, msg: `Ошибка импорта: ${err}`, severity: "error" });
+      setSnack({ open: true, msg: `Ошибка импорта: ${errorMessage(err)}`, severity: "error" });
     }
   };
 
@@ -491,7 +579,7 @@ export default function Settings() {
         <Box sx={{ py: 3.5, pr: { md: 4 }, borderBottom: `1px solid ${sectionBorder}` }}>
           <SectionLabel>Интерфейс</SectionLabel>
           <SectionTitle>Внешний вид</SectionTitle>
-          <SectionDesc>Выбери тему оформления</SectionDesc>
+          <SectionDesc>Выбери тему оформления и язык уведомлений</SectionDesc>
         </Box>
         <Box
           sx={{
@@ -519,6 +607,21 @@ export default function Settings() {
               Тёмная
             </ToggleButton>
           </ToggleButtonGroup>
+          <Box sx={{ mt: 2 }}>
+            <ToggleButtonGroup
+              value={language}
+              exclusive
+              onChange={(_, v) =>
+                v &&
+                saveSetting("language", v).catch((e) =>
+                  setSnack({ open: true, msg: `Ошибка: ${errorMessage(e)}`, severity: "error" })
+                )
+              }
+            >
+              <ToggleButton value="ru">Русский</ToggleButton>
+              <ToggleButton value="en">English</ToggleButton>
+            </ToggleButtonGroup>
+          </Box>
         </Box>
 
         <Box
@@ -603,7 +706,7 @@ export default function Settings() {
                   type={showKey ? "text" : "password"}
                   value={apiKey}
                   onChange={(e) => setApiKey(e.target.value)}
-                  placeholder={localProvider === "local" ? "lm-studio (опционально)" : "sk-..."}
+                  placeholder={apiKeyHint || (localProvider === "local" ? "lm-studio (опционально)" : "sk-...")}
                   helperText={
                     localProvider === "local"
                       ? "LM Studio поддерживает OpenAI-совместимый API ключ"
@@ -617,7 +720,10 @@ export default function Settings() {
                     ),
                     endAdornment: (
                       <InputAdornment position="end">
-                        <IconButton size="small" onClick={() => setShowKey((s) => !s)}>
+                        <IconButton
+                          size="small"
+                          onClick={() => toggleSecret("ai_api_key", apiKey, setApiKey, showKey, setShowKey)}
+                        >
                           {showKey
                             ? <VisibilityOffOutlinedIcon sx={{ fontSize: 16 }} />
                             : <VisibilityOutlinedIcon sx={{ fontSize: 16 }} />
@@ -727,7 +833,7 @@ export default function Settings() {
                 type={showGroqKey ? "text" : "password"}
                 value={groqKey}
                 onChange={(e) => setGroqKey(e.target.value)}
-                placeholder="gsk_..."
+                placeholder={groqKeyHint || "gsk_..."}
                 helperText={
                   <span>
                     Получите бесплатно на{" "}
@@ -744,7 +850,10 @@ export default function Settings() {
                   ),
                   endAdornment: (
                     <InputAdornment position="end">
-                      <IconButton size="small" onClick={() => setShowGroqKey((s) => !s)}>
+                      <IconButton
+                        size="small"
+                        onClick={() => toggleSecret("groq_api_key", groqKey, setGroqKey, showGroqKey, setShowGroqKey)}
+                      >
                         {showGroqKey
                           ? <VisibilityOffOutlinedIcon sx={{ fontSize: 16 }} />
                           : <VisibilityOutlinedIcon sx={{ fontSize: 16 }} />
@@ -856,12 +965,85 @@ export default function Settings() {
             <Divider sx={{ mb: 3 }} />
 
             <TextField
-              label="Уведомлять о сервисах за (дней)"
-              type="number"
-              value={notifyDaysBefore}
-              onChange={(e) => saveSetting("notify_days_before", e.target.value)}
+              label="Напоминать о сервисах за (дней)"
+              value={localThresholds}
+              onChange={(e) => setLocalThresholds(e.target.value)}
+              onBlur={() => {
+                if (localThresholds === notifyDaysBefore) return;
+                saveSetting("notify_days_before", localThresholds).catch((e) => {
+                  setLocalThresholds(notifyDaysBefore);
+                  setSnack({ open: true, msg: `Ошибка: ${errorMessage(e)}`, severity: "error" });
+                });
+              }}
+              helperText="Через запятую, например 30,7,1,0"
               sx={{ maxWidth: 280 }}
             />
+
+            <Typography sx={{ fontSize: "0.8125rem", color: isDark ? "#8e8ea0" : "#6e6e80", mt: 2, mb: 1 }}>
+              Правила для категорий сервисов
+            </Typography>
+            {categoryRules.map((rule) => (
+              <Box key={rule.category} sx={{ display: "flex", alignItems: "center", gap: 1, mb: 0.5 }}>
+                <Typography sx={{ fontSize: "0.875rem", minWidth: 140 }}>{rule.category}</Typography>
+                <Typography sx={{ fontSize: "0.875rem", color: isDark ? "#8e8ea0" : "#6e6e80", flex: 1 }}>
+                  {rule.thresholds}
+                </Typography>
+                <Button size="small" variant="text" onClick={() => saveCategoryRule(rule.category, "")}>
+                  Удалить
+                </Button>
+              </Box>
+            ))}
+            <Box sx={{ display: "flex", gap: 1, alignItems: "center", mt: 1 }}>
+              <TextField
+                size="small"
+                label="Категория"
+                value={newRule.category}
+                onChange={(e) => setNewRule({ ...newRule, category: e.target.value })}
+                sx={{ maxWidth: 180 }}
+              />
+              <TextField
+                size="small"
+                label="Дни"
+                value={newRule.thresholds}
+                onChange={(e) => setNewRule({ ...newRule, thresholds: e.target.value })}
+                placeholder="14,3,0"
+                sx={{ maxWidth: 140 }}
+              />
+              <Button
+                size="small"
+                variant="text"
+                disabled={!newRule.category.trim() || !newRule.thresholds.trim()}
+                onClick={() => saveCategoryRule(newRule.category.trim(), newRule.thresholds)}
+              >
+                Добавить
+              </Button>
+            </Box>
+
+            <Box sx={{ display: "flex", flexWrap: "wrap", gap: 2, mt: 2 }}>
+              <TextField
+                label="Проверять каждые (мин)"
+                type="number"
+                value={schedulerIntervalMinutes}
+                onChange={(e) => saveSetting("scheduler_interval_minutes", e.target.value)}
+                sx={{ maxWidth: 200 }}
+              />
+              <TextField
+                label="Тихие часы с"
+                type="time"
+                value={quietHoursStart}
+                onChange={(e) => saveSetting("quiet_hours_start", e.target.value)}
+                InputLabelProps={{ shrink: true }}
+                sx={{ maxWidth: 160 }}
+              />
+              <TextField
+                label="до"
+                type="time"
+                value={quietHoursEnd}
+                onChange={(e) => saveSetting("quiet_hours_end", e.target.value)}
+                InputLabelProps={{ shrink: true }}
+                sx={{ maxWidth: 160 }}
+              />
+            </Box>
           </Box>
         </Box>
 
@@ -897,6 +1079,79 @@ export default function Settings() {
           </Box>
         </Box>
 
+        <Box sx={{ py: 3.5, pr: { md: 4 }, borderTop: `1px solid ${sectionBorder}` }}>
+          <SectionLabel>Финансы</SectionLabel>
+          <SectionTitle>Валюты и курсы</SectionTitle>
+          <SectionDesc>Курсы для пересчёта сумм клиентов и сервисов в базовую валюту</SectionDesc>
+        </Box>
+        <Box
+          sx={{
+            py: 3.5,
+            pl: { md: 4 },
+            borderTop: `1px solid ${sectionBorder}`,
+            borderLeft: { md: `1px solid ${sectionBorder}` },
+          }}
+        >
+          <FxRates />
+        </Box>
+
+        <Box sx={{ py: 3.5, pr: { md: 4 }, borderTop: `1px solid ${sectionBorder}` }}>
+          <SectionLabel>Финансы</SectionLabel>
+          <SectionTitle>Реквизиты для счетов</SectionTitle>
+          <SectionDesc>Данные исполнителя и нумерация выставляемых счетов</SectionDesc>
+        </Box>
+        <Box
+          sx={{
+            py: 3.5,
+            pl: { md: 4 },
+            borderTop: `1px solid ${sectionBorder}`,
+            borderLeft: { md: `1px solid ${sectionBorder}` },
+          }}
+        >
+          <CompanySettings />
+        </Box>
+
+        <Box sx={{ py: 3.5, pr: { md: 4 }, borderTop: `1px solid ${sectionBorder}` }}>
+          <SectionLabel>Финансы</SectionLabel>
+          <SectionTitle>Учёт времени</SectionTitle>
+          <SectionDesc>Округление каждой записи времени при подсчёте оплачиваемых часов</SectionDesc>
+        </Box>
+        <Box
+          sx={{
+            py: 3.5,
+            pl: { md: 4 },
+            borderTop: `1px solid ${sectionBorder}`,
+            borderLeft: { md: `1px solid ${sectionBorder}` },
+          }}
+        >
+          <Box sx={{ display: "flex", flexWrap: "wrap", gap: 2 }}>
+            <FormControl sx={{ minWidth: 200 }}>
+              <InputLabel>Шаг округления</InputLabel>
+              <Select
+                value={String(timeRoundingMinutes)}
+                label="Шаг округления"
+                onChange={(e) => saveSetting("time_rounding_minutes", e.target.value)}
+              >
+                {[0, 5, 6, 10, 15, 30, 60].map((m) => (
+                  <MenuItem key={m} value={String(m)}>{m === 0 ? "Без округления" : `${m} мин`}</MenuItem>
+                ))}
+              </Select>
+            </FormControl>
+            <FormControl sx={{ minWidth: 200 }} disabled={timeRoundingMinutes === 0}>
+              <InputLabel>Направление</InputLabel>
+              <Select
+                value={timeRoundingMode}
+                label="Направление"
+                onChange={(e) => saveSetting("time_rounding_mode", e.target.value)}
+              >
+                <MenuItem value="up">Вверх</MenuItem>
+                <MenuItem value="nearest">До ближайшего</MenuItem>
+                <MenuItem value="down">Вниз</MenuItem>
+              </Select>
+            </FormControl>
+          </Box>
+        </Box>
+
         <Box sx={{ py: 3.5, pr: { md: 4 }, borderTop: `1px solid ${sectionBorder}` }}>
           <SectionLabel>Резервная копия</SectionLabel>
           <SectionTitle>Экспорт и импорт профиля</SectionTitle>
@@ -1,5 +1,7 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage, openSettingsIfKeyMissing } from "../errors";
+import { PAYMENT_TYPE_LABELS, PaymentType } from "./clientsStore";
 
 export interface ChatMessage {
   id: string;
@@ -22,12 +24,14 @@ interface ChatState {
 interface AddClientInput {
   name: string;
   contact?: string;
-  payment_type: "monthly" | "onetime";
+  payment_type: PaymentType;
   amount?: number;
   currency?: string;
   notes?: string;
   payment_date?: string;
   payment_day?: number;
+  included_hours?: number;
+  hourly_rate?: number;
 }
 
 interface AddNoteItem {
@@ -93,8 +97,9 @@ export const useChatStore = create<ChatState>((set, get) => ({
     } catch (e: unknown) {
       get().addMessage({
         role: "assistant",
-        content: `Ошибка: ${e instanceof Error ? e.message : String(e)}`,
+        content: `Ошибка: ${errorMessage(e)}`,
       });
+      openSettingsIfKeyMissing(e);
     } finally {
       set({ loading: false });
     }
@@ -181,13 +186,16 @@ function parseString(value: unknown): string | undefined {
 function buildAddClientInput(data: Record<string, unknown>): AddClientInput {
   const name = parseString(data.name) ?? "Новый клиент";
   const rawType = parseString(data.payment_type)?.toLowerCase();
-  const payment_type: "monthly" | "onetime" = rawType === "onetime" ? "onetime" : "monthly";
+  const payment_type: PaymentType =
+    rawType && rawType in PAYMENT_TYPE_LABELS ? (rawType as PaymentType) : "monthly";
   const amount = parseNumber(data.amount);
   const payment_day = parseIntSafe(data.payment_day ?? data.day);
   const payment_date = parseString(data.payment_date ?? data.date);
   const currency = parseString(data.currency)?.toUpperCase() ?? "RUB";
   const contact = parseString(data.contact);
   const notes = parseString(data.notes);
+  const included_hours = parseNumber(data.included_hours);
+  const hourly_rate = parseNumber(data.hourly_rate);
   return {
     name,
     contact,
@@ -197,6 +205,8 @@ function buildAddClientInput(data: Record<string, unknown>): AddClientInput {
     notes,
     payment_date,
     payment_day,
+    included_hours: payment_type === "retainer" ? included_hours : undefined,
+    hourly_rate: payment_type === "retainer" || payment_type === "hourly" ? hourly_rate : undefined,
   };
 }
 
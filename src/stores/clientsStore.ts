@@ -1,16 +1,64 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
+import { format } from "date-fns";
+
+export type PaymentType = "onetime" | "weekly" | "monthly" | "quarterly" | "yearly" | "retainer" | "hourly";
+
+export const PAYMENT_TYPE_LABELS: Record<PaymentType, string> = {
+  monthly: "Ежемесячно",
+  weekly: "Еженедельно",
+  quarterly: "Ежеквартально",
+  yearly: "Ежегодно",
+  retainer: "Абонемент",
+  hourly: "Почасово",
+  onetime: "Разовый",
+};
+
+// Key of the billing period containing `date`, in the backend's format.
+export function periodKey(type: PaymentType, date: Date): string {
+  switch (type) {
+    case "onetime":
+      return format(date, "yyyy-MM-dd");
+    case "weekly":
+      return format(date, "RRRR-'W'II");
+    case "quarterly":
+      return format(date, "yyyy-'Q'Q");
+    case "yearly":
+      return format(date, "yyyy");
+    default:
+      return format(date, "yyyy-MM");
+  }
+}
 
 export interface Client {
   id: string;
   name: string;
   contact?: string;
-  payment_type: "monthly" | "onetime";
+  payment_type: PaymentType;
   amount?: number;
   currency: string;
   notes?: string;
   payment_day?: number;
   created_at: string;
+  payment_notify_days?: number;
+  start_date?: string;
+  end_date?: string;
+  pause_from?: string;
+  pause_to?: string;
+  included_hours?: number;
+  hourly_rate?: number;
+}
+
+export interface ScheduleEntry {
+  period: string;
+  due_date: string;
+  status: "paid" | "unpaid" | "upcoming" | "overdue";
+  scheduled: boolean;
+  payment_id?: string;
+  currency: string;
+  expected?: number;
+  received: number;
+  outstanding?: number;
 }
 
 export interface ClientPayment {
@@ -19,23 +67,54 @@ export interface ClientPayment {
   period: string;
   paid: boolean;
   paid_at?: string;
+  currency: string;
+  expected?: number;
+  received: number;
+  outstanding?: number;
+  transactions: PaymentTransaction[];
+}
+
+export interface PaymentTransaction {
+  id: string;
+  payment_id: string;
+  paid_on: string;
+  amount: number;
+  currency: string;
+  method?: string;
+  note?: string;
+  created_at: string;
+}
+
+export interface RecordPaymentInput {
+  client_id: string;
+  period: string;
+  paid_on?: string;
+  amount: number;
+  currency?: string;
+  method?: string;
+  note?: string;
 }
 
 interface ClientsState {
   clients: Client[];
   payments: Record<string, ClientPayment[]>;
+  schedules: Record<string, ScheduleEntry[]>;
   loading: boolean;
   fetchClients: () => Promise<void>;
   fetchPayments: (clientId: string) => Promise<void>;
+  fetchSchedule: (clientId: string, from: string, to: string) => Promise<void>;
   createClient: (input: Omit<Client, "id" | "created_at">) => Promise<void>;
   updateClient: (input: Client) => Promise<void>;
   deleteClient: (id: string) => Promise<void>;
   togglePayment: (clientId: string, period: string, paid: boolean) => Promise<void>;
+  recordPayment: (input: RecordPaymentInput) => Promise<void>;
+  deletePaymentTransaction: (clientId: string, id: string) => Promise<void>;
 }
 
 export const useClientsStore = create<ClientsState>((set, get) => ({
   clients: [],
   payments: {},
+  schedules: {},
   loading: false,
 
   fetchClients: async () => {
@@ -53,6 +132,11 @@ export const useClientsStore = create<ClientsState>((set, get) => ({
     set((state) => ({ payments: { ...state.payments, [clientId]: payments } }));
   },
 
+  fetchSchedule: async (clientId: string, from: string, to: string) => {
+    const schedule = await invoke<ScheduleEntry[]>("get_client_schedule", { clientId, from, to });
+    set((state) => ({ schedules: { ...state.schedules, [clientId]: schedule } }));
+  },
+
   createClient: async (input) => {
     await invoke("create_client", { input });
     await get().fetchClients();
@@ -75,4 +159,14 @@ export const useClientsStore = create<ClientsState>((set, get) => ({
     await invoke("toggle_payment", { clientId, period, paid });
     await get().fetchPayments(clientId);
   },
+
+  recordPayment: async (input) => {
+    await invoke("record_payment_transaction", { input });
+    await get().fetchPayments(input.client_id);
+  },
+
+  deletePaymentTransaction: async (clientId: string, id: string) => {
+    await invoke("delete_payment_transaction", { id });
+    await get().fetchPayments(clientId);
+  },
 }));
@@ -0,0 +1,83 @@
+import { create } from "zustand";
+import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../errors";
+
+export interface MonthlyIncome {
+  period: string;
+  expected: number;
+  received: number;
+}
+
+export interface OverdueReceivable {
+  client_id: string;
+  client_name: string;
+  period: string;
+  due_date: string;
+  days_overdue: number;
+  amount: number;
+  original_amount: number;
+  original_currency: string;
+}
+
+export interface UpcomingCost {
+  service_id: string;
+  project_name: string;
+  service_name: string;
+  due_date: string;
+  amount: number;
+  original_amount: number;
+  original_currency: string;
+}
+
+export interface SpendingGroup {
+  name: string | null;
+  total: number;
+  charges: number;
+}
+
+export interface UnconvertedAmount {
+  kind: "receivable" | "upcoming" | "charge";
+  id: string;
+  name: string;
+  date: string;
+  amount: number;
+  currency: string;
+}
+
+export interface Dashboard {
+  base_currency: string;
+  from: string;
+  to: string;
+  income: MonthlyIncome[];
+  overdue: OverdueReceivable[];
+  overdue_total: number;
+  upcoming: UpcomingCost[];
+  upcoming_30_days: number;
+  upcoming_90_days: number;
+  spending_by_project: SpendingGroup[];
+  spending_by_category: SpendingGroup[];
+  unconverted: UnconvertedAmount[];
+}
+
+interface DashboardState {
+  dashboard: Dashboard | null;
+  error: string | null;
+  fetchDashboard: (from: string, to: string, baseCurrency?: string) => Promise<void>;
+}
+
+export const useDashboardStore = create<DashboardState>((set) => ({
+  dashboard: null,
+  error: null,
+
+  fetchDashboard: async (from, to, baseCurrency) => {
+    try {
+      const dashboard = await invoke<Dashboard>("get_dashboard", {
+        range: { from, to },
+        baseCurrency: baseCurrency ?? null,
+      });
+      set({ dashboard, error: null });
+    } catch (e) {
+      set({ error: errorMessage(e) });
+    }
+  },
+}));
@@ -10,6 +10,7 @@ export interface Note {
   sort_order: number;
   created_at: string;
   updated_at: string;
+  remind_at?: string;
 }
 
 export interface Category {
@@ -25,12 +26,13 @@ interface NotesState {
   loading: boolean;
   fetchNotes: (categoryId?: string) => Promise<void>;
   fetchCategories: () => Promise<void>;
-  createNote: (input: { title: string; content?: string; category_id?: string }) => Promise<Note>;
+  createNote: (input: { title: string; content?: string; category_id?: string; remind_at?: string }) => Promise<Note>;
   updateNote: (input: {
     id: string;
     title: string;
     content?: string;
     category_id?: string;
+    remind_at?: string;
   }) => Promise<void>;
   deleteNote: (id: string) => Promise<void>;
   toggleNote: (id: string, completed: boolean) => Promise<void>;
@@ -0,0 +1,47 @@
+import { create } from "zustand";
+import { invoke } from "@tauri-apps/api/core";
+
+export interface NotificationEntry {
+  id: string;
+  kind: "service" | "payment" | "note";
+  entity_id: string;
+  due: string;
+  threshold: string;
+  title: string;
+  body: string;
+  sent_at: string;
+  read_at?: string;
+}
+
+interface NotificationsState {
+  entries: NotificationEntry[];
+  fetchHistory: () => Promise<void>;
+  markRead: (id: string) => Promise<void>;
+  markAllRead: () => Promise<void>;
+}
+
+export const useNotificationsStore = create<NotificationsState>((set, get) => ({
+  entries: [],
+
+  fetchHistory: async () => {
+    const entries = await invoke<NotificationEntry[]>("get_notification_history", {
+      limit: 50,
+    });
+    set({ entries });
+  },
+
+  markRead: async (id) => {
+    await invoke("mark_notification_read", { id });
+    const now = new Date().toISOString();
+    set({
+      entries: get().entries.map((e) => (e.id === id && !e.read_at ? { ...e, read_at: now } : e)),
+    });
+  },
+
+  markAllRead: async () => {
+    const unread = get().entries.filter((e) => !e.read_at);
+    await Promise.all(unread.map((e) => invoke("mark_notification_read", { id: e.id })));
+    const now = new Date().toISOString();
+    set({ entries: get().entries.map((e) => (e.read_at ? e : { ...e, read_at: now })) });
+  },
+}));
@@ -1,6 +1,6 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
-import { differenceInDays, parseISO } from "date-fns";
+import { differenceInDays, format, parseISO } from "date-fns";
 
 export interface Service {
   id: string;
@@ -15,6 +15,28 @@ export interface Service {
   category?: string;
   notify_days: number;
   created_at: string;
+  snoozed_until?: string;
+  acknowledged_expires_at?: string;
+  notify_thresholds?: string;
+  billing_cycle?: "monthly" | "quarterly" | "yearly" | "custom";
+  billing_cycle_days?: number;
+  auto_renew: boolean;
+}
+
+export interface ServiceRenewal {
+  id: string;
+  service_id: string;
+  previous_expires_at: string;
+  expires_at: string;
+  cost?: number;
+  currency: string;
+  auto_renewed: boolean;
+  renewed_at: string;
+}
+
+export function isSnoozed(service: Service): boolean {
+  if (service.acknowledged_expires_at === service.expires_at) return true;
+  return !!service.snoozed_until && service.snoozed_until > format(new Date(), "yyyy-MM-dd");
 }
 
 export function getDaysRemaining(expiresAt: string): number {
@@ -32,9 +54,15 @@ interface ServicesState {
   services: Service[];
   loading: boolean;
   fetchServices: () => Promise<void>;
-  createService: (input: Omit<Service, "id" | "created_at">) => Promise<void>;
+  createService: (
+    input: Omit<Service, "id" | "created_at" | "snoozed_until" | "acknowledged_expires_at">
+  ) => Promise<void>;
   updateService: (input: Service) => Promise<void>;
   deleteService: (id: string) => Promise<void>;
+  snoozeService: (id: string, days: number) => Promise<void>;
+  acknowledgeService: (id: string) => Promise<void>;
+  clearServiceSnooze: (id: string) => Promise<void>;
+  renewService: (id: string) => Promise<ServiceRenewal>;
 }
 
 export const useServicesStore = create<ServicesState>((set, get) => ({
@@ -65,4 +93,25 @@ export const useServicesStore = create<ServicesState>((set, get) => ({
     await invoke("delete_service", { id });
     set((state) => ({ services: state.services.filter((s) => s.id !== id) }));
   },
+
+  snoozeService: async (id, days) => {
+    await invoke("snooze_service", { id, days });
+    await get().fetchServices();
+  },
+
+  acknowledgeService: async (id) => {
+    await invoke("acknowledge_service", { id });
+    await get().fetchServices();
+  },
+
+  clearServiceSnooze: async (id) => {
+    await invoke("clear_service_snooze", { id });
+    await get().fetchServices();
+  },
+
+  renewService: async (id) => {
+    const renewal = await invoke<ServiceRenewal>("renew_service", { id });
+    await get().fetchServices();
+    return renewal;
+  },
 }));
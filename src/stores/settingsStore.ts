@@ -6,11 +6,18 @@ interface SettingsState {
   overlayHotkey: string;
   voiceHotkey: string;
   overlayPosition: string;
-  notifyDaysBefore: number;
+  notifyDaysBefore: string;
   aiProvider: string;
   aiModel: string;
   aiBaseUrl: string;
   voiceProvider: string;
+  language: "ru" | "en";
+  schedulerIntervalMinutes: number;
+  quietHoursStart: string;
+  quietHoursEnd: string;
+  baseCurrency: string;
+  timeRoundingMinutes: number;
+  timeRoundingMode: "up" | "nearest" | "down";
   loadSettings: () => Promise<void>;
   saveSetting: (key: string, value: string) => Promise<void>;
   setTheme: (theme: "light" | "dark" | "system") => void;
@@ -21,11 +28,18 @@ export const useSettingsStore = create<SettingsState>((set) => ({
   overlayHotkey: "Ctrl+Shift+B",
   voiceHotkey: "Ctrl+Shift+V",
   overlayPosition: "bottom-right",
-  notifyDaysBefore: 7,
+  notifyDaysBefore: "30,7,1,0",
   aiProvider: "openai",
   aiModel: "gpt-4o-mini",
   aiBaseUrl: "",
   voiceProvider: "openai",
+  language: "ru",
+  schedulerIntervalMinutes: 15,
+  quietHoursStart: "",
+  quietHoursEnd: "",
+  baseCurrency: "RUB",
+  timeRoundingMinutes: 0,
+  timeRoundingMode: "up",
 
   loadSettings: async () => {
     try {
@@ -35,11 +49,18 @@ export const useSettingsStore = create<SettingsState>((set) => ({
         overlayHotkey: settings.overlay_hotkey || "Ctrl+Shift+B",
         voiceHotkey: settings.voice_hotkey || "Ctrl+Shift+V",
         overlayPosition: settings.overlay_position || "bottom-right",
-        notifyDaysBefore: parseInt(settings.notify_days_before || "7", 10),
+        notifyDaysBefore: settings.notify_days_before || "30,7,1,0",
         aiProvider: settings.ai_provider || "openai",
         aiModel: settings.ai_model || "gpt-4o-mini",
         aiBaseUrl: settings.ai_base_url || "",
         voiceProvider: settings.voice_provider || "openai",
+        language: settings.language === "en" ? "en" : "ru",
+        schedulerIntervalMinutes: parseInt(settings.scheduler_interval_minutes || "15", 10),
+        quietHoursStart: settings.quiet_hours_start || "",
+        quietHoursEnd: settings.quiet_hours_end || "",
+        baseCurrency: settings.base_currency || "RUB",
+        timeRoundingMinutes: parseInt(settings.time_rounding_minutes || "0", 10),
+        timeRoundingMode: (settings.time_rounding_mode as "up" | "nearest" | "down") || "up",
       });
     } catch (e) {
       console.error("Failed to load settings:", e);
@@ -52,12 +73,19 @@ export const useSettingsStore = create<SettingsState>((set) => ({
       overlay_hotkey: { overlayHotkey: value },
       voice_hotkey: { voiceHotkey: value },
       overlay_position: { overlayPosition: value },
-      notify_days_before: { notifyDaysBefore: parseInt(value, 10) },
+      notify_days_before: { notifyDaysBefore: value },
       ai_provider: { aiProvider: value },
       ai_model: { aiModel: value },
       ai_base_url: { aiBaseUrl: value },
       theme: { theme: value as "light" | "dark" | "system" },
       voice_provider: { voiceProvider: value },
+      language: { language: value as "ru" | "en" },
+      scheduler_interval_minutes: { schedulerIntervalMinutes: parseInt(value, 10) },
+      quiet_hours_start: { quietHoursStart: value },
+      quiet_hours_end: { quietHoursEnd: value },
+      base_currency: { baseCurrency: value.trim().toUpperCase() },
+      time_rounding_minutes: { timeRoundingMinutes: parseInt(value, 10) },
+      time_rounding_mode: { timeRoundingMode: value as "up" | "nearest" | "down" },
     };
     if (stateMap[key]) set(stateMap[key] as Partial<SettingsState>);
   },
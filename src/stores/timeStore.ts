@@ -0,0 +1,104 @@
+import { create } from "zustand";
+import { invoke } from "@tauri-apps/api/core";
+
+export interface TimeEntry {
+  id: string;
+  client_id: string;
+  project?: string;
+  note?: string;
+  started_at: string;
+  ended_at?: string;
+  billable: boolean;
+  invoice_id?: string;
+  created_at: string;
+}
+
+export interface RunningTimer extends TimeEntry {
+  client_name: string;
+  elapsed_minutes: number;
+}
+
+export interface PeriodHours {
+  period: string;
+  entries: number;
+  minutes: number;
+  hours: number;
+  unbilled_minutes: number;
+}
+
+export interface TimeEntryInput {
+  client_id: string;
+  project?: string;
+  note?: string;
+  started_at: string;
+  ended_at?: string;
+  minutes?: number;
+  billable?: boolean;
+}
+
+export function formatMinutes(minutes: number): string {
+  const h = Math.floor(minutes / 60);
+  const m = minutes % 60;
+  return h > 0 ? `${h} ч ${String(m).padStart(2, "0")} мин` : `${m} мин`;
+}
+
+interface TimeState {
+  entries: Record<string, TimeEntry[]>;
+  hours: Record<string, PeriodHours[]>;
+  running: RunningTimer | null;
+  fetchEntries: (clientId: string) => Promise<void>;
+  fetchHours: (clientId: string, from: string, to: string) => Promise<void>;
+  fetchRunning: () => Promise<void>;
+  startTimer: (clientId: string, project?: string, note?: string) => Promise<void>;
+  stopTimer: () => Promise<void>;
+  addEntry: (input: TimeEntryInput) => Promise<void>;
+  deleteEntry: (clientId: string, id: string) => Promise<void>;
+}
+
+export const useTimeStore = create<TimeState>((set, get) => ({
+  entries: {},
+  hours: {},
+  running: null,
+
+  fetchEntries: async (clientId: string) => {
+    const entries = await invoke<TimeEntry[]>("get_time_entries", { clientId });
+    set((state) => ({ entries: { ...state.entries, [clientId]: entries } }));
+  },
+
+  fetchHours: async (clientId: string, from: string, to: string) => {
+    const hours = await invoke<PeriodHours[]>("get_billable_hours", { clientId, from, to });
+    set((state) => ({ hours: { ...state.hours, [clientId]: hours } }));
+  },
+
+  fetchRunning: async () => {
+    const running = await invoke<RunningTimer | null>("get_running_timer");
+    set({ running });
+  },
+
+  // Starting a timer stops the previous one, which may belong to another client.
+  startTimer: async (clientId, project, note) => {
+    const previous = get().running?.client_id;
+    await invoke("start_timer", { input: { client_id: clientId, project, note } });
+    await get().fetchRunning();
+    await get().fetchEntries(clientId);
+    if (previous && previous !== clientId) await get().fetchEntries(previous);
+  },
+
+  stopTimer: async () => {
+    const clientId = get().running?.client_id;
+    await invoke("stop_timer");
+    set({ running: null });
+    if (clientId) await get().fetchEntries(clientId);
+  },
+
+  addEntry: async (input) => {
+    await invoke("add_time_entry", { input });
+    await get().fetchEntries(input.client_id);
+  },
+
+  deleteEntry: async (clientId: string, id: string) => {
+    await invoke("delete_time_entry", { id });
+    await get().fetchEntries(clientId);
+    await get().fetchRunning();
+  },
+}));